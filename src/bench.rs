@@ -62,6 +62,30 @@ fn string_insert_1000(b: &mut Bencher) {
 
 }
 
+#[bench]
+fn rope_append_1000_sequential(b: &mut Bencher) {
+    let rope = Rope::from(iter::repeat('a')
+                                .take(100_000)
+                                .collect::<String>());
+    let ropes = (0..1000).map(|i| Rope::from(i.to_string()))
+                         .collect::<Vec<_>>();
+    b.iter(|| {
+        ropes.iter().fold(rope.clone(), |r, other| r.append(other))
+    })
+}
+
+#[bench]
+fn rope_append_all_1000(b: &mut Bencher) {
+    let rope = Rope::from(iter::repeat('a')
+                                .take(100_000)
+                                .collect::<String>());
+    let ropes = (0..1000).map(|i| Rope::from(i.to_string()))
+                         .collect::<Vec<_>>();
+    b.iter(|| {
+        rope.append_all(ropes.iter().cloned())
+    })
+}
+
 macro_rules! insert_benches {
     ( long: $lenl:expr, short: $lens:expr, $($name:ident: $frac:expr),* ) => {
         mod insert {
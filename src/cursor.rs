@@ -0,0 +1,145 @@
+//! A cursor over the text of a `Rope`.
+//!
+//! A [`RopeCursor`] holds a byte offset into a `Rope`, and can be moved
+//! through it one `char` or one grapheme cluster at a time, or sought to an
+//! arbitrary offset. It can also report the line and column of its current
+//! position. This is the one object an editor needs to hold for its caret.
+//!
+//! A `RopeCursor` is also an iterator over the `char`s of the `Rope` from its
+//! current position, and their byte offsets.
+//!
+//! # Examples
+//!
+//! ```
+//! use an_rope::Rope;
+//! use an_rope::metric::Line;
+//! let rope = Rope::from("first\na̐éö̲");
+//! let mut cursor = rope.iter();
+//! cursor.seek(6);
+//! assert_eq!(cursor.next_grapheme(), Some(6..9));
+//! assert_eq!(cursor.line_column(), (Line(1), 2));
+//! assert_eq!(cursor.next(), Some((9, 'é')));
+//! ```
+//!
+//! [`RopeCursor`]: struct.RopeCursor.html
+
+use std::ops;
+
+use metric::Line;
+use Rope;
+
+/// A cursor holding a byte offset into a `Rope`.
+///
+/// A `RopeCursor` is created by [`Rope::iter()`], and starts at the
+/// beginning of the `Rope`. Its offset always falls on a `char` boundary.
+///
+/// [`Rope::iter()`]: ../struct.Rope.html#method.iter
+#[derive(Clone, Debug)]
+pub struct RopeCursor<'a> {
+    rope: &'a Rope
+  , offset: usize
+}
+
+impl<'a> RopeCursor<'a> {
+    pub(crate) fn new(rope: &'a Rope) -> Self {
+        RopeCursor { rope, offset: 0 }
+    }
+
+    /// Returns the byte offset of this cursor from the start of the `Rope`.
+    #[inline]
+    pub fn offset(&self) -> usize { self.offset }
+
+    /// Moves this cursor to the byte offset `index`.
+    ///
+    /// # Panics
+    /// * If `index` is greater than the length of the `Rope`
+    /// * If `index` does not fall on a `char` boundary
+    pub fn seek(&mut self, index: usize) {
+        assert!( index <= self.rope.len()
+               , "RopeCursor::seek: byte index {} is out of bounds \
+                  (length {})"
+               , index, self.rope.len());
+        assert!( self.rope.is_char_boundary(index)
+               , "RopeCursor::seek: byte index {} is not a char boundary"
+               , index);
+        self.offset = index;
+    }
+
+    /// Returns the `char` after this cursor without moving it, or `None` if
+    /// the cursor is at the end of the `Rope`.
+    #[inline]
+    pub fn peek_char(&self) -> Option<char> {
+        self.rope.char_at(self.offset)
+    }
+
+    /// Moves this cursor forward over one `char`, returning that `char` and
+    /// its byte offset, or `None` if the cursor is at the end of the `Rope`.
+    pub fn next_char(&mut self) -> Option<(usize, char)> {
+        let c = self.peek_char()?;
+        let offset = self.offset;
+        self.offset += c.len_utf8();
+        Some((offset, c))
+    }
+
+    /// Moves this cursor back over one `char`, returning that `char` and its
+    /// byte offset, or `None` if the cursor is at the start of the `Rope`.
+    pub fn prev_char(&mut self) -> Option<(usize, char)> {
+        if self.offset == 0 { return None }
+        let mut offset = self.offset - 1;
+        while !self.rope.is_char_boundary(offset) { offset -= 1; }
+        let c = self.rope.char_at(offset)?;
+        self.offset = offset;
+        Some((offset, c))
+    }
+
+    /// Moves this cursor forward over one extended grapheme cluster,
+    /// returning the byte range of that cluster, or `None` if the cursor is
+    /// at the end of the `Rope`.
+    ///
+    /// If the cursor is inside a grapheme cluster, it moves to the end of
+    /// that cluster.
+    ///
+    /// # Time Complexity
+    /// O(log _n_ + _m_), where _m_ is the length of the cluster.
+    pub fn next_grapheme(&mut self) -> Option<ops::Range<usize>> {
+        let offset = self.offset;
+        let next = self.rope.root.grapheme_ranges(offset).next_boundary()?;
+        self.offset = next;
+        Some(offset..next)
+    }
+
+    /// Moves this cursor back over one extended grapheme cluster, returning
+    /// the byte range of that cluster, or `None` if the cursor is at the
+    /// start of the `Rope`.
+    ///
+    /// If the cursor is inside a grapheme cluster, it moves to the start of
+    /// that cluster.
+    ///
+    /// # Time Complexity
+    /// O(log _n_ + _m_), where _m_ is the length of the cluster.
+    pub fn prev_grapheme(&mut self) -> Option<ops::Range<usize>> {
+        let offset = self.offset;
+        let prev = self.rope.root.grapheme_ranges(offset).prev_boundary()?;
+        self.offset = prev;
+        Some(prev..offset)
+    }
+
+    /// Returns the line and column of this cursor.
+    ///
+    /// Lines are numbered from 0, and the column is the number of `char`s
+    /// between the start of the line and the cursor. See
+    /// [`Rope::byte_index_to_position()`].
+    ///
+    /// [`Rope::byte_index_to_position()`]: ../struct.Rope.html#method.byte_index_to_position
+    #[inline]
+    pub fn line_column(&self) -> (Line, usize) {
+        self.rope.byte_index_to_position(self.offset)
+    }
+}
+
+impl<'a> Iterator for RopeCursor<'a> {
+    type Item = (usize, char);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> { self.next_char() }
+}
@@ -0,0 +1,47 @@
+//! Edits to a `Rope`, for implementing undo and redo.
+//!
+//! [`Rope::apply_edit()`] replaces a range of a `Rope` and returns an
+//! [`Edit`] describing how to undo that replacement. Applying the returned
+//! `Edit` restores the original `Rope`, and in turn returns an `Edit` which
+//! redoes the change.
+//!
+//! # Examples
+//!
+//! ```
+//! use an_rope::Rope;
+//! let mut rope = Rope::from("hello world");
+//! let undo = rope.apply_edit(6..11, Rope::from("rope"));
+//! assert_eq!(&rope, "hello rope");
+//!
+//! let redo = rope.apply(undo);
+//! assert_eq!(&rope, "hello world");
+//!
+//! rope.apply(redo);
+//! assert_eq!(&rope, "hello rope");
+//! ```
+//!
+//! [`Rope::apply_edit()`]: ../struct.Rope.html#method.apply_edit
+//! [`Edit`]: struct.Edit.html
+
+use std::ops;
+
+use Rope;
+
+/// A replacement of a range of a `Rope`.
+///
+/// An `Edit` replaces the bytes in `range` with the text of `replacement`.
+#[derive(Clone, Debug)]
+pub struct Edit {
+    /// The byte range to replace.
+    pub range: ops::Range<usize>
+  , /// The text to insert in place of `range`.
+    pub replacement: Rope
+}
+
+impl Edit {
+    /// Returns a new `Edit` replacing `range` with `replacement`.
+    #[inline]
+    pub fn new(range: ops::Range<usize>, replacement: Rope) -> Self {
+        Edit { range, replacement }
+    }
+}
@@ -0,0 +1,78 @@
+//! Errors returned by fallible `Rope` operations.
+//!
+//! Most `Rope` methods panic when handed an invalid index, mirroring the
+//! behaviour of `String` and `str`. The `checked_` variants of those methods
+//! instead return one of the error types in this module, so that callers can
+//! recover from (or report) a bad index.
+
+use std::error;
+use std::fmt;
+
+/// An error indicating that a byte index did not fall on a `char` boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CharBoundaryError {
+    /// The offending byte index.
+    pub index: usize
+}
+
+impl fmt::Display for CharBoundaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "byte index {} is not a char boundary", self.index)
+    }
+}
+
+impl error::Error for CharBoundaryError {
+    fn description(&self) -> &str { "byte index is not a char boundary" }
+}
+
+/// An error returned by [`Rope::try_slice()`] when a range cannot be sliced.
+///
+/// [`Rope::try_slice()`]: ../struct.Rope.html#method.try_slice
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceError {
+    /// The end of the range lies past the end of the `Rope`.
+    OutOfBounds {
+        /// The offending index, in units of the metric being sliced by.
+        index: usize
+      , /// The length of the `Rope`, in units of the same metric.
+        len: usize
+    }
+  , /// An index in the range does not fall on a `char` boundary.
+    NotOnBoundary {
+        /// The offending index, as a byte offset.
+        index: usize
+    }
+  , /// The start of the range is greater than its end.
+    StartAfterEnd {
+        /// The start of the range.
+        start: usize
+      , /// The end of the range.
+        end: usize
+    }
+}
+
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SliceError::OutOfBounds { index, len } =>
+                write!(f, "index {} is out of bounds (length {})", index, len)
+          , SliceError::NotOnBoundary { index } =>
+                write!(f, "byte index {} is not a char boundary", index)
+          , SliceError::StartAfterEnd { start, end } =>
+                write!(f, "start index {} is greater than end index {}"
+                      , start, end)
+        }
+    }
+}
+
+impl error::Error for SliceError {
+    fn description(&self) -> &str {
+        match *self {
+            SliceError::OutOfBounds { .. } => "index is out of bounds"
+          , SliceError::NotOnBoundary { .. } =>
+                "byte index is not a char boundary"
+          , SliceError::StartAfterEnd { .. } =>
+                "start index is greater than end index"
+        }
+    }
+}
@@ -1,13 +1,19 @@
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_segmentation::{ GraphemeIndices as StrGraphemeIndices
                           , UWordBoundIndices as StrUWordBoundIndices
+                          , GraphemeCursor, GraphemeIncomplete
                           };
-use metric::{Metric, Measured};
+use metric::{Metric, Measured, Line};
 
 use std::ops;
+use std::cmp;
 use std::fmt;
+use std::str;
+use std::str::{ CharIndices as StrCharIndices
+               , Bytes as StrBytes
+               };
 use std::convert;
-use std::borrow::{Borrow, ToOwned};
+use std::borrow::{Borrow, Cow, ToOwned};
 
 #[cfg(test)] mod test;
 
@@ -118,6 +124,50 @@ impl NodeLink {
     pub fn new<N>(node: N) -> Self
     where N: convert::Into<Node> { NodeLink(Arc::new(node.into())) }
 
+    /// Returns a mutable reference to the linked `Node`, if no other
+    /// `NodeLink` shares it.
+    #[cfg(not(feature = "atomic"))]
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut Node> { Rc::get_mut(&mut self.0) }
+
+    /// Returns a mutable reference to the linked `Node`, if no other
+    /// `NodeLink` shares it.
+    #[cfg(feature = "atomic")]
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut Node> { Arc::get_mut(&mut self.0) }
+
+    /// Replaces every occurrence of `from` in this subrope with `to`,
+    /// returning `true` if any were replaced.
+    ///
+    /// Nodes which are not shared with any other tree are changed in place;
+    /// shared nodes are copied before they are changed. Subtrees which don't
+    /// contain `from` are left untouched, and stay shared.
+    pub fn replace_chars(&mut self, from: char, to: char) -> bool {
+        if let Some(node) = self.get_mut() {
+            return node.replace_chars(from, to)
+        }
+        let replaced = match ***self {
+            Leaf(ref s) if s.contains(from) =>
+                Some(NodeLink::from(s.replace( from
+                                             , to.encode_utf8(&mut [0; 4]))))
+          , Leaf(_) => None
+          , Branch { ref left, ref right } => {
+                let (mut left, mut right) = (left.clone(), right.clone());
+                let left_changed = left.replace_chars(from, to);
+                let right_changed = right.replace_chars(from, to);
+                if left_changed || right_changed {
+                    Some(Node::new_branch(left, right))
+                } else {
+                    None
+                }
+            }
+        };
+        match replaced {
+            Some(node) => { *self = node; true }
+          , None => false
+        }
+    }
+
     /// Rebalance the subrope starting at this `Node`, returning a new `Node`
     ///
     /// From "Ropes: An Alternative to Strings":
@@ -139,31 +189,63 @@ impl NodeLink {
     /// > of this result, until the result fits into an empty slot in the
     /// > sequence."
     pub fn rebalance(self) -> Self {
-        // TODO: this is a huge mess, I based it on the IBM Java implementation
-        //       please refactor until it stops being ugly!
-        //        - eliza, 12/17/2016
-
         if self.is_balanced() {
             // the subrope is already balanced, do nothing
             self
         } else {
-            // let mut leaves: Vec<Option<Node>> =
-            //     self.into_leaves().map(Option::Some).collect();
-            // let len = leaves.len();
-            // fn _rebalance(l: &mut Vec<Option<Node>>, start: usize, end: usize)
-            //               -> Node {
-            //     match end - start {
-            //         1 => l[start].take().unwrap()
-            //       , 2 => l[start].take().unwrap() + l[start + 1].take().unwrap()
-            //       , n => {
-            //             let mid = start + (n / 2);
-            //             _rebalance(l, start, mid) + _rebalance(l, mid, end)
-            //
-            //         }
-            //     }
-            // };
-            // _rebalance(&mut leaves, 0, len)
-            self
+            self.rebuild_coalescing(0)
+        }
+    }
+
+    /// Splits the rightmost non-empty leaf off of this `Node`'s subtree.
+    ///
+    /// # Returns
+    /// - `Some` with a tuple of the subtree without its last leaf, and the
+    ///   last leaf's string, if the subtree contains any text
+    /// - `None` if the subtree is empty
+    ///
+    /// # Time complexity
+    /// O(log _n_)
+    pub fn split_last_leaf(&self) -> Option<(NodeLink, &str)> {
+        match self.value {
+            Leaf(ref s) if s.is_empty() => None
+          , Leaf(ref s) => Some((Node::empty(), s.as_ref()))
+          , Branch { ref left, ref right } =>
+                match right.split_last_leaf() {
+                    Some((ref rest, s)) if rest.is_empty() =>
+                        Some((left.clone(), s))
+                  , Some((rest, s)) =>
+                        Some((Node::new_branch(left.clone(), rest), s))
+                    // if the right subtree is empty, the last leaf must be
+                    // somewhere in the left subtree
+                  , None => left.split_last_leaf()
+                }
+        }
+    }
+
+    /// Splits the leftmost non-empty leaf off of this `Node`'s subtree.
+    ///
+    /// # Returns
+    /// - `Some` with a tuple of the first leaf's string, and the subtree
+    ///   without its first leaf, if the subtree contains any text
+    /// - `None` if the subtree is empty
+    ///
+    /// # Time complexity
+    /// O(log _n_)
+    pub fn split_first_leaf(&self) -> Option<(&str, NodeLink)> {
+        match self.value {
+            Leaf(ref s) if s.is_empty() => None
+          , Leaf(ref s) => Some((s.as_ref(), Node::empty()))
+          , Branch { ref left, ref right } =>
+                match left.split_first_leaf() {
+                    Some((s, ref rest)) if rest.is_empty() =>
+                        Some((s, right.clone()))
+                  , Some((s, rest)) =>
+                        Some((s, Node::new_branch(rest, right.clone())))
+                    // if the left subtree is empty, the first leaf must be
+                    // somewhere in the right subtree
+                  , None => right.split_first_leaf()
+                }
         }
     }
 
@@ -190,13 +272,15 @@ impl NodeLink {
             Leaf(_) if self.is_empty() =>
                 // splitting an empty leaf node returns two empty leaf nodes
                 (Node::empty(), Node::empty())
-          , Leaf(_) if self.measure().into() == 1 =>
+          , Leaf(_) if self.measure().into() == 1 && index.into() != 0 =>
                 (self.clone(), Node::empty())
           , Leaf(ref s) => {
                 // splitting a leaf node with length >= 2 returns two new Leaf
                 // nodes, one with the left half of the string, and one with
                 // the right
-                // TODO: make this properly respect metric index boundaries
+                // the metric converts `index` to a byte offset on one of its
+                // own boundaries (e.g. a grapheme cluster boundary), so the
+                // leaf is never split inside a multi-byte unit
                 let index = self.to_byte_index(index).expect("invalid index!");
                 let left = Leaf(s[..index].into());
                 let right = Leaf(s[index..].into());
@@ -248,6 +332,81 @@ impl NodeLink {
             }
         }
     }
+
+    /// Inserts `node` at `index` in this `Node`'s subtree.
+    ///
+    /// Rather than splitting the whole tree at `index` and concatenating the
+    /// halves back together, this walks down to the leaf containing `index`,
+    /// and then [`join`]s each rebuilt node with its untouched sibling on the
+    /// way back up. Only the path to `index` is rebuilt and rebalanced; all
+    /// of the rest of the tree is shared with this `Node`.
+    ///
+    /// [`join`]: struct.Node.html#method.join
+    ///
+    /// # Time complexity
+    /// O(log _n_) if this `Node`'s subtree is balanced
+    pub fn insert_at<M>(&self, index: M, node: &NodeLink) -> Self
+    where M: Metric
+        , Self: Measured<M> {
+        match self.value {
+            Leaf(_) => {
+                let (left, right) = self.split(index);
+                Node::join(&Node::join(&left, node), &right)
+            }
+          , Branch { ref left, ref right } if index < self.measure_weight() =>
+                Node::join(&left.insert_at(index, node), right)
+          , Branch { ref left, ref right } =>
+                Node::join( left
+                          , &right.insert_at(index - self.measure_weight(), node))
+        }
+    }
+
+    /// Rebuilds this `Node`'s subtree as a balanced tree, merging runs of
+    /// adjacent small leaves together.
+    ///
+    /// Two adjacent leaves are merged if their combined length is no more
+    /// than `threshold` bytes, and the first doesn't end in a line ending,
+    /// so that each leaf still contains at most one line. Leaves which are
+    /// not merged are shared with this `Node`, and empty leaves are dropped.
+    /// A `threshold` of 0 rebalances the tree without merging any leaves.
+    ///
+    /// # Time complexity
+    /// O(_n_)
+    pub fn rebuild_coalescing(&self, threshold: usize) -> Self {
+        let mut leaves = Vec::new();
+        self.push_leaves(&mut leaves);
+        let mut merged: Vec<NodeLink> = Vec::with_capacity(leaves.len());
+        for leaf in leaves {
+            let joined = match merged.last().and_then(|l| l.as_leaf_str()) {
+                Some(last) if last.len() + leaf.len() <= threshold
+                           && !last.ends_with('\n') => {
+                    let mut s = String::with_capacity(last.len() + leaf.len());
+                    s.push_str(last);
+                    s.push_str(leaf.as_leaf_str().unwrap_or(""));
+                    Some(s)
+                }
+              , _ => None
+            };
+            match joined {
+                Some(s) => { merged.pop(); merged.push(Node::new_leaf(s)); }
+              , None => merged.push(leaf)
+            }
+        }
+        Node::balanced_from(&merged)
+    }
+
+    /// Pushes every non-empty leaf in this `Node`'s subtree onto `leaves`,
+    /// in order.
+    fn push_leaves(&self, leaves: &mut Vec<NodeLink>) {
+        match self.value {
+            Leaf(ref s) if s.is_empty() => {}
+          , Leaf(_) => leaves.push(self.clone())
+          , Branch { ref left, ref right } => {
+                left.push_leaves(leaves);
+                right.push_leaves(leaves);
+            }
+        }
+    }
 }
 
 impl ops::Deref for NodeLink {
@@ -301,17 +460,21 @@ where M: Metric
         #[inline] fn measure_weight(&self) -> M { self.0.measure_weight() }
     }
 
+/// The maximum length (in bytes) of a leaf created by merging two adjacent
+/// small leaves together.
+pub const MERGE_THRESHOLD: usize = 64;
+
 #[cfg(feature = "rebalance")]
 const FIB_LOOKUP: [usize; 93] = [
  0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89, 144, 233, 377, 610, 987, 1597, 2584, 4181, 6765, 10946, 17711, 28657, 46368, 75025, 121393, 196418, 317811, 514229, 832040, 1346269, 2178309, 3524578, 5702887, 9227465, 14930352, 24157817, 39088169, 63245986, 102334155, 165580141, 267914296, 433494437, 701408733, 1134903170, 1836311903, 2971215073, 4807526976, 7778742049, 12586269025, 20365011074, 32951280099, 53316291173, 86267571272, 139583862445, 225851433717, 365435296162, 591286729879, 956722026041, 1548008755920, 2504730781961, 4052739537881, 6557470319842, 10610209857723, 17167680177565, 27777890035288, 44945570212853, 72723460248141, 117669030460994, 190392490709135, 308061521170129, 498454011879264, 806515533049393, 1304969544928657, 2111485077978050, 3416454622906707, 5527939700884757, 8944394323791464, 14472334024676221, 23416728348467685, 37889062373143906, 61305790721611591, 99194853094755497, 160500643816367088, 259695496911122585, 420196140727489673, 679891637638612258, 1100087778366101931, 1779979416004714189, 2880067194370816120, 4660046610375530309, 7540113804746346429 ];
 
 
-/// Returns the _n_th fibonacci number.
+/// Returns the _n_th fibonacci number, or `None` if it does not fit in a
+/// `usize`.
 #[inline]
 #[cfg(feature = "rebalance")]
-fn fibonacci(n: usize) -> usize {
-    if n <= 93 { FIB_LOOKUP[n] }
-    else { fibonacci(n - 1) + fibonacci(n - 2) }
+fn fibonacci(n: usize) -> Option<usize> {
+    FIB_LOOKUP.get(n).cloned()
 }
 
 impl Node {
@@ -330,6 +493,137 @@ impl Node {
         NodeLink::new(Value::new_branch(left.into(), right.into()))
     }
 
+    /// Concatenate a slice of `NodeLink`s into a single balanced tree.
+    ///
+    /// The slice is split in half recursively, so the resulting tree has
+    /// depth at most ⌈log₂ _n_⌉ more than its deepest input.
+    pub fn balanced_from(nodes: &[NodeLink]) -> NodeLink {
+        match nodes.len() {
+            0 => Node::empty()
+          , 1 => nodes[0].clone()
+          , n => {
+                let (left, right) = nodes.split_at(n / 2);
+                Node::new_branch( Node::balanced_from(left)
+                                , Node::balanced_from(right))
+            }
+        }
+    }
+
+    /// Concatenates `left` and `right`, keeping the result balanced.
+    ///
+    /// If the depths of `left` and `right` differ by more than one, the
+    /// shallower tree is joined onto the spine of the deeper one and the
+    /// nodes along that spine are rotated, AVL-style, to restore balance.
+    /// Only nodes on the spine are rebuilt: every other subtree is shared
+    /// with `left` or `right`. If both inputs are balanced, so is the result.
+    ///
+    /// # Time complexity
+    /// O(|_d_(`left`) − _d_(`right`)|)
+    pub fn join(left: &NodeLink, right: &NodeLink) -> NodeLink {
+        if left.is_empty() { return right.clone() }
+        if right.is_empty() { return left.clone() }
+        let (l_depth, r_depth) = (left.depth(), right.depth());
+        if l_depth > r_depth + 1 { Node::join_right(left, right) }
+        else if r_depth > l_depth + 1 { Node::join_left(left, right) }
+        else { Node::new_branch(left.clone(), right.clone()) }
+    }
+
+    /// Joins `right` onto the right spine of `left`, which must be more
+    /// than one level deeper than `right`.
+    fn join_right(left: &NodeLink, right: &NodeLink) -> NodeLink {
+        let (a, b) = left.children();
+        if b.depth() <= right.depth() + 1 {
+            let joined = Node::new_branch(b.clone(), right.clone());
+            if joined.depth() <= a.depth() + 1 {
+                Node::new_branch(a.clone(), joined)
+            } else {
+                Node::new_branch(a.clone(), joined.rotate_right())
+                    .rotate_left()
+            }
+        } else {
+            let joined = Node::join_right(b, right);
+            if joined.depth() <= a.depth() + 1 {
+                Node::new_branch(a.clone(), joined)
+            } else {
+                Node::new_branch(a.clone(), joined).rotate_left()
+            }
+        }
+    }
+
+    /// Joins `left` onto the left spine of `right`, which must be more
+    /// than one level deeper than `left`.
+    fn join_left(left: &NodeLink, right: &NodeLink) -> NodeLink {
+        let (b, c) = right.children();
+        if b.depth() <= left.depth() + 1 {
+            let joined = Node::new_branch(left.clone(), b.clone());
+            if joined.depth() <= c.depth() + 1 {
+                Node::new_branch(joined, c.clone())
+            } else {
+                Node::new_branch(joined.rotate_left(), c.clone())
+                    .rotate_right()
+            }
+        } else {
+            let joined = Node::join_left(left, b);
+            if joined.depth() <= c.depth() + 1 {
+                Node::new_branch(joined, c.clone())
+            } else {
+                Node::new_branch(joined, c.clone()).rotate_right()
+            }
+        }
+    }
+
+    /// Returns the children of this `Node`, which must be a branch.
+    fn children(&self) -> (&NodeLink, &NodeLink) {
+        match **self {
+            Branch { ref left, ref right } => (left, right)
+          , Leaf(_) => unreachable!("Node::children() called on a leaf. \
+                                     Something's broken.")
+        }
+    }
+
+    /// Rotates `(a, (b, c))` to `((a, b), c)`.
+    fn rotate_left(&self) -> NodeLink {
+        let (a, right) = self.children();
+        let (b, c) = right.children();
+        Node::new_branch(Node::new_branch(a.clone(), b.clone()), c.clone())
+    }
+
+    /// Rotates `((a, b), c)` to `(a, (b, c))`.
+    fn rotate_right(&self) -> NodeLink {
+        let (left, c) = self.children();
+        let (a, b) = left.children();
+        Node::new_branch(a.clone(), Node::new_branch(b.clone(), c.clone()))
+    }
+
+    /// Returns a new subrope containing the bytes of this `Node` in the
+    /// range `range`.
+    ///
+    /// Every child node lying entirely within `range` is shared with this
+    /// `Node` rather than copied; only the (at most two) leaves containing
+    /// the ends of `range` have their text copied into new leaves.
+    pub fn subrope(&self, range: ops::Range<usize>) -> NodeLink {
+        // shares `link` itself if `range` covers all of it
+        fn sub_link(link: &NodeLink, range: ops::Range<usize>) -> NodeLink {
+            if range.start == 0 && range.end == link.len() { link.clone() }
+            else { link.subrope(range) }
+        }
+        if range.start == range.end { return Node::empty() }
+        match **self {
+            Leaf(ref s) => Node::new_leaf(&s[range])
+          , Branch { ref left, ref right } => {
+                let weight = left.len();
+                if range.end <= weight {
+                    sub_link(left, range)
+                } else if range.start >= weight {
+                    sub_link(right, range.start - weight .. range.end - weight)
+                } else {
+                    Node::new_branch( sub_link(left, range.start..weight)
+                                    , sub_link(right, 0..range.end - weight))
+                }
+            }
+        }
+    }
+
     #[inline]
     // #[cfg(not(feature = "unstable"))]
     pub fn new_leaf<T>(that: T) -> NodeLink
@@ -349,8 +643,9 @@ impl Node {
     #[inline]
     #[cfg(feature = "rebalance")]
     pub fn is_balanced(&self) -> bool {
-        self.len() >= fibonacci(self.depth() + 2)
-        // true
+        // a node too deep for its minimum length to fit in a `usize` can
+        // never be long enough to be balanced
+        fibonacci(self.depth() + 2).map_or(false, |min| self.len() >= min)
     }
 
     /// Returns true if this node is balanced
@@ -368,18 +663,6 @@ impl Node {
         true
     }
 
-    /// Returns the depth in the tree of a node
-    #[inline]
-    #[cfg(feature = "rebalance")]
-    fn depth(&self) -> usize {
-        use std::cmp::max;
-
-        match *self { Leaf(_) => 0
-                    , Branch(BranchNode { ref left, ref right, .. }) =>
-                        max(left.depth(), right.depth()) + 1
-                    }
-    }
-
 
     /// Returns the length of a node
     #[inline]
@@ -389,6 +672,102 @@ impl Node {
 
     #[inline] pub fn is_empty(&self) -> bool { self.len() == 0 }
 
+    /// Returns true if every leaf in this `Node`'s subrope contains valid
+    /// UTF-8.
+    ///
+    /// # Time complexity
+    /// O(_n_)
+    pub fn is_valid_utf8(&self) -> bool {
+        use std::str;
+        self.leaves().all(|n| match **n {
+            Leaf(ref s) => str::from_utf8(s.as_bytes()).is_ok()
+          , _ => unreachable!("Node.leaves() iterator contained something \
+                               that wasn't a leaf. Something's broken.")
+        })
+    }
+
+    /// Folds `f` over the bytes of each leaf in this `Node`'s subrope, in
+    /// order, starting with `init`.
+    ///
+    /// # Time complexity
+    /// O(_n_)
+    pub fn fold_bytes<B, F>(&self, init: B, mut f: F) -> B
+    where F: FnMut(B, &[u8]) -> B {
+        self.leaves().fold(init, |acc, n| match **n {
+            Leaf(ref s) => f(acc, s.as_bytes())
+          , _ => unreachable!("Node.leaves() iterator contained something \
+                               that wasn't a leaf. Something's broken.")
+        })
+    }
+
+    /// Returns the string of the leaf node containing byte index `i`, and
+    /// the offset of `i` within that string.
+    ///
+    /// # Returns
+    /// - `Some((string, offset))` if `i` is a byte index into this subrope
+    /// - `None` if `i` is out of bounds
+    ///
+    /// # Time complexity
+    /// O(log _n_)
+    pub fn leaf_at(&self, i: usize) -> Option<(&str, usize)> {
+        match **self {
+            Leaf(ref s) if i < s.len() => Some((s.as_ref(), i))
+          , Leaf(_) => None
+          , Branch { ref left, ref right } => {
+                // walk down the side of the tree containing the index,
+                // subtracting the left child's length if we go right
+                let weight = left.len();
+                if i < weight { left.leaf_at(i) }
+                else { right.leaf_at(i - weight) }
+            }
+        }
+    }
+
+    /// Replaces every occurrence of `from` in this subrope with `to`, in
+    /// place, returning `true` if any were replaced.
+    ///
+    /// See `NodeLink::replace_chars()`.
+    fn replace_chars(&mut self, from: char, to: char) -> bool {
+        let changed = match self.value {
+            Leaf(ref s) if !s.contains(from) => false
+          , Leaf(ref mut s) if from.len_utf8() == to.len_utf8()
+                             && to != '\n' => {
+                // the leaf's length and line structure are unchanged, so
+                // the new char can be written over the old one
+                replace_same_width(s, from, to);
+                true
+            }
+          , Leaf(ref mut s) if to != '\n' => {
+                *s = s.replace(from, to.encode_utf8(&mut [0; 4]))[..].into();
+                true
+            }
+          , Leaf(ref s) => {
+                // each leaf may contain only one line, so inserting
+                // newlines requires splitting the leaf
+                let split = NodeLink::from(s.replace(from, "\n"));
+                self.value = split.value.clone();
+                true
+            }
+          , Branch { ref mut left, ref mut right } => {
+                let left_changed = left.replace_chars(from, to);
+                let right_changed = right.replace_chars(from, to);
+                left_changed || right_changed
+            }
+        };
+        if changed { self.invalidate_cache() }
+        changed
+    }
+
+    /// Returns the text of this node if it is a leaf, or `None` if it is a
+    /// branch.
+    #[inline]
+    pub fn as_leaf_str(&self) -> Option<&str> {
+        match **self {
+            Leaf(ref s) => Some(s.as_ref())
+          , Branch {..} => None
+        }
+    }
+
 
     /// Rebalance the subrope starting at this `Node`, returning a new `Node`
     ///
@@ -453,6 +832,13 @@ impl Node {
         Leaves(vec![self])
     }
 
+    /// Returns an iterator over all leaf nodes in this `Node`'s subrope,
+    /// starting from the rightmost leaf
+    #[inline]
+    fn leaves_rev(&self) -> LeavesRev<'_> {
+        LeavesRev(vec![self])
+    }
+
     // /// Returns a move iterator over all leaf nodes in this `Node`'s subrope
     // #[inline]
     // fn into_leaves(self) -> IntoLeaves {
@@ -465,17 +851,31 @@ impl Node {
         #[inline]
         pub fn strings<'a>(&'a self) -> impl Iterator<Item=&'a str> + 'a {
             self.leaves().map(|n| match **n {
-                Leaf(ref s) => s.as_ref()
+                Leaf(ref s) => {
+                    debug_assert!( ::std::str::from_utf8(s.as_bytes()).is_ok()
+                                 , "Node.strings() found a leaf containing \
+                                    invalid UTF-8! Was it created with \
+                                    `from_utf8_unchecked()`?");
+                    s.as_ref()
+                }
               , _ => unreachable!("Node.leaves() iterator contained something \
                                    that wasn't a leaf. Barring _force majeure_, \
                                    this should be impossible. Something's broken.")
             })
         }
 
+        #[doc=
+            "Returns an iterator over all the strings in this `Node`s subrope, \
+             in reverse order."]
         #[inline]
-        pub fn char_indices<'a>(&'a self)
-                               -> impl Iterator<Item=(usize, char)> + 'a {
-             self.chars().enumerate()
+        pub fn strings_rev<'a>(&'a self) -> impl Iterator<Item=&'a str> + 'a {
+            self.leaves_rev().map(|n| match **n {
+                Leaf(ref s) => s.as_ref()
+              , _ => unreachable!("Node.leaves_rev() iterator contained \
+                                   something that wasn't a leaf. Barring \
+                                   _force majeure_, this should be \
+                                   impossible. Something's broken.")
+            })
         }
     }
 
@@ -619,28 +1019,226 @@ impl Node {
         impl split_word_bounds for Node {}
     }
 
+    /// Returns an iterator over the strings in this `Node`'s subrope,
+    /// starting at byte index `i`.
+    ///
+    /// The first string yielded is the remainder of the leaf containing `i`,
+    /// so this walks the tree only once to find the starting leaf.
+    ///
+    /// # Time complexity
+    /// O(log _n_) to find the starting leaf
+    pub fn strings_from(&self, i: usize) -> StringsFrom<'_> {
+        // the stack of right siblings of the nodes on the path to `i`. these
+        // are the subtrees remaining to the right of the starting leaf.
+        let mut stack = Vec::new();
+        let mut node = self;
+        let mut i = i;
+        loop {
+            match **node {
+                Branch { ref left, ref right } => {
+                    let weight = left.len();
+                    if i < weight {
+                        stack.push(&**right);
+                        node = left;
+                    } else {
+                        i -= weight;
+                        node = right;
+                    }
+                }
+              , Leaf(ref s) => {
+                    let first = if i < s.len() { Some(&s[i..]) } else { None };
+                    return StringsFrom { first, rest: Leaves(stack) }
+                }
+            }
+        }
+    }
+
+    /// Returns the byte index of the start of line `line` in this subrope.
+    ///
+    /// Line 0 always starts at byte 0. Line _n_ starts immediately after the
+    /// _n_th line ending.
+    ///
+    /// # Returns
+    /// - `Some` with the byte index of the start of the line, if there are at
+    ///   least `line` line endings in this subrope
+    /// - `None` otherwise
+    ///
+    /// # Time complexity
+    /// O(log _n_)
+    pub fn line_start(&self, line: Line) -> Option<usize> {
+        if line.0 == 0 { return Some(0) }
+        match **self {
+            Leaf(ref s) =>
+                s.match_indices('\n').nth(line.0 - 1).map(|(i, _)| i + 1)
+          , Branch { ref left, ref right } => {
+                let left_lines: Line = left.measure();
+                if line <= left_lines {
+                    left.line_start(line)
+                } else {
+                    right.line_start(line - left_lines)
+                         .map(|i| i + left.len())
+                }
+            }
+        }
+    }
+
+    /// Returns the line containing byte index `i` in this subrope.
+    ///
+    /// This is the number of line endings that occur before `i`.
+    ///
+    /// # Time complexity
+    /// O(log _n_)
+    pub fn line_of(&self, i: usize) -> Line {
+        match **self {
+            Leaf(ref s) => {
+                // `i` may fall inside a multi-byte character, so count
+                // bytes rather than slicing the string
+                let bytes = s.as_bytes();
+                let end = if i < bytes.len() { i } else { bytes.len() };
+                Line(bytes[..end].iter().filter(|&&b| b == b'\n').count())
+            }
+          , Branch { ref left, ref right } => {
+                let weight = left.len();
+                if i < weight {
+                    left.line_of(i)
+                } else {
+                    let left_lines: Line = left.measure();
+                    left_lines + right.line_of(i - weight)
+                }
+            }
+        }
+    }
+
+    /// Returns an iterator over the `char`s in this `Node`'s subrope and
+    /// their byte offsets.
+    ///
+    /// Like `str::char_indices()`, the offsets are byte offsets from the
+    /// start of the subrope, not counts of the preceding `char`s.
+    pub fn char_indices(&self) -> CharIndices<'_> {
+        let mut strings = self.strings();
+        let first_string = strings.next().unwrap_or("");
+        CharIndices { strings: Box::new(strings)
+                    , chars: first_string.char_indices()
+                    , byte_length_so_far: 0
+                    , curr_length: first_string.len() }
+    }
+
+    /// Returns an iterator over the `char`s in this `Node`'s subrope and
+    /// their byte offsets, starting from the last `char` and ending with the
+    /// first.
+    pub fn char_indices_rev(&self) -> CharIndicesRev<'_> {
+        let mut strings = self.strings_rev();
+        let last_string = strings.next().unwrap_or("");
+        CharIndicesRev { strings: Box::new(strings)
+                       , chars: last_string.char_indices()
+                       , leaf_start: self.len() - last_string.len() }
+    }
+
+    /// Returns an iterator over the bytes in this `Node`'s subrope, starting
+    /// from the last byte and ending with the first.
+    pub fn bytes_rev(&self) -> BytesRev<'_> {
+        BytesRev { front: self.leaves()
+                 , back: self.leaves_rev()
+                 , front_bytes: "".bytes()
+                 , back_bytes: "".bytes()
+                 , remaining: self.len() }
+    }
+
     pub fn grapheme_indices(&self) -> GraphemeIndices {
         let mut strings = self.strings();
-        let first_string = strings.next()
-            .expect("grapheme_indices called on empty rope!");
+        // an empty rope has no strings, so start from an empty one
+        let first_string = strings.next().unwrap_or("");
         GraphemeIndices { strings: Box::new(strings)
                         , graphemes: first_string.grapheme_indices(true)
                         , char_length_so_far: 0
-                        , curr_length: first_string.len() }
+                        , curr_length: first_string.len()
+                        , remaining: self.len() }
+    }
+
+    /// Returns an iterator over the byte ranges of the extended grapheme
+    /// clusters in this `Node`'s subrope, starting at byte index `i`.
+    ///
+    /// Unlike `grapheme_indices()`, which segments each leaf separately, the
+    /// subrope is segmented as a whole, so a cluster split across two leaves
+    /// is yielded as a single range. Leaves are looked up only as the
+    /// segmenter asks for them, so nothing is copied.
+    ///
+    /// # Time complexity
+    /// O(log _n_) to find the starting leaf, and O(log _n_) for each further
+    /// leaf that is needed.
+    pub fn grapheme_ranges(&self, i: usize) -> GraphemeRanges<'_> {
+        let cursor = GraphemeCursor::new(i, self.len(), true);
+        let mut ranges = GraphemeRanges { node: self
+                                        , cursor
+                                        , chunk: ""
+                                        , chunk_start: 0 };
+        // at the very end of the subrope, the cursor still needs the last
+        // leaf to step backwards from
+        if !self.is_empty() {
+            ranges.load_chunk(cmp::min(i, self.len() - 1));
+        }
+        ranges
+    }
+
+    /// Returns the text in the byte range `range` of this `Node`'s subrope.
+    ///
+    /// If the range lies within a single leaf, the text is borrowed from it;
+    /// otherwise, it is copied into a new `String`.
+    pub fn text_in(&self, range: ops::Range<usize>) -> Cow<'_, str> {
+        match self.leaf_at(range.start) {
+            Some((s, offset)) if offset + range.len() <= s.len() =>
+                Cow::Borrowed(&s[offset..offset + range.len()])
+          , _ => {
+                let mut text = String::with_capacity(range.len());
+                for s in self.strings_from(range.start) {
+                    let rest = range.len() - text.len();
+                    if s.len() >= rest {
+                        text.push_str(&s[..rest]);
+                        break
+                    }
+                    text.push_str(s);
+                }
+                Cow::Owned(text)
+            }
+        }
     }
 
     pub fn split_word_bound_indices(&self) -> UWordBoundIndices {
         let mut strings = self.strings();
-        let first_string = strings.next()
-            .expect("split_word_bound_indices called on empty rope!");
+        let first_string = strings.next().unwrap_or("");
         UWordBoundIndices { strings: Box::new(strings)
                           , bounds: first_string.split_word_bound_indices()
                           , char_length_so_far: 0
-                          , curr_length: first_string.len() }
+                          , curr_length: first_string.len()
+                          , remaining: self.len() }
     }
 
 }
 
+/// Overwrites every occurrence of `from` in `s` with `to`, which must have
+/// the same UTF-8 length.
+#[cfg(not(feature = "tendril"))]
+fn replace_same_width(s: &mut LeafRepr, from: char, to: char) {
+    debug_assert_eq!(from.len_utf8(), to.len_utf8());
+    let mut buf = [0; 4];
+    let to = to.encode_utf8(&mut buf).as_bytes();
+    let starts = s.match_indices(from).map(|(i, _)| i).collect::<Vec<_>>();
+    // this is safe because a char is only ever overwritten with another
+    // complete char of the same length, so `s` remains valid UTF-8
+    let bytes = unsafe { s.as_bytes_mut() };
+    for i in starts {
+        bytes[i..i + to.len()].copy_from_slice(to);
+    }
+}
+
+/// Overwrites every occurrence of `from` in `s` with `to`, which must have
+/// the same UTF-8 length.
+#[cfg(feature = "tendril")]
+fn replace_same_width(s: &mut LeafRepr, from: char, to: char) {
+    debug_assert_eq!(from.len_utf8(), to.len_utf8());
+    *s = s.replace(from, to.encode_utf8(&mut [0; 4]))[..].into();
+}
+
 /// An that performs a left traversal over a series of `Node`s
 struct Nodes<'a>(Vec<&'a Node>);
 
@@ -680,6 +1278,27 @@ impl<'a> Iterator for Leaves<'a> {
     }
 }
 
+/// An iterator over a series of leaf `Node`s, from right to left
+struct LeavesRev<'a>(Vec<&'a Node>);
+
+impl<'a> Iterator for LeavesRev<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.0.pop() {
+                None => return None
+              , Some(&Node { value: Leaf(ref s), .. }) if s.is_empty() => {}
+              , leaf @ Some(&Node { value: Leaf(_), .. })=> return leaf
+              , Some(&Node { value: Branch { ref left, ref right }, .. }) => {
+                    self.0.push(left);
+                    self.0.push(right);
+                }
+            }
+        }
+    }
+}
+
 // /// A move iterator over a series of leaf `Node`s
 // struct IntoLeaves(Vec<Node>);
 //
@@ -701,11 +1320,139 @@ impl<'a> Iterator for Leaves<'a> {
 //     }
 // }
 
+/// An iterator over the strings in a subrope, starting at a given byte index.
+pub struct StringsFrom<'a> {
+    first: Option<&'a str>
+  , rest: Leaves<'a>
+}
+
+impl<'a> Iterator for StringsFrom<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.first.take().or_else(|| {
+            self.rest.next().map(|n| match **n {
+                Leaf(ref s) => s.as_ref()
+              , _ => unreachable!("Leaves iterator contained something that \
+                                   wasn't a leaf. Something's broken.")
+            })
+        })
+    }
+}
+
+pub struct CharIndices<'a> {
+    strings: Box<dyn Iterator<Item = &'a str> + 'a >
+  , chars: StrCharIndices<'a>
+  , byte_length_so_far: usize
+  , curr_length: usize
+}
+
+impl<'a> Iterator for CharIndices<'a> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the indices yielded by each leaf's `str::CharIndices` start from
+        // zero, so offset them by the lengths of all the preceding leaves
+        self.chars.next()
+            .map(|(i, c)| (i + self.byte_length_so_far, c))
+            .or_else(|| {
+                self.strings.next()
+                    .and_then(|s| { self.byte_length_so_far += self.curr_length;
+                                    self.curr_length = s.len();
+                                    self.chars = s.char_indices();
+                                    self.next() })
+            })
+    }
+}
+
+/// An iterator over the `char`s of a subrope and their byte offsets, from
+/// last to first.
+pub struct CharIndicesRev<'a> {
+    strings: Box<dyn Iterator<Item = &'a str> + 'a >
+  , chars: StrCharIndices<'a>
+  , /// the byte offset of the start of the current leaf
+    leaf_start: usize
+}
+
+impl<'a> Iterator for CharIndicesRev<'a> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the indices yielded by each leaf's `str::CharIndices` start from
+        // zero, so offset them by the start of the leaf, which is found by
+        // subtracting each leaf's length as it is reached
+        self.chars.next_back()
+            .map(|(i, c)| (i + self.leaf_start, c))
+            .or_else(|| {
+                self.strings.next()
+                    .and_then(|s| { self.leaf_start -= s.len();
+                                    self.chars = s.char_indices();
+                                    self.next() })
+            })
+    }
+}
+
+/// An iterator over the bytes of a subrope, from last to first.
+///
+/// Leaves are consumed from the right by `next()` and from the left by
+/// `next_back()`; the count of bytes not yet yielded keeps the two ends
+/// from crossing when both directions are used.
+pub struct BytesRev<'a> {
+    front: Leaves<'a>
+  , back: LeavesRev<'a>
+  , front_bytes: StrBytes<'a>
+  , back_bytes: StrBytes<'a>
+  , remaining: usize
+}
+
+impl<'a> Iterator for BytesRev<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None }
+        loop {
+            if let Some(b) = self.back_bytes.next_back() {
+                self.remaining -= 1;
+                return Some(b)
+            }
+            match self.back.next().and_then(|n| n.as_leaf_str()) {
+                Some(s) => self.back_bytes = s.bytes()
+              , None => return None
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for BytesRev<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None }
+        loop {
+            if let Some(b) = self.front_bytes.next() {
+                self.remaining -= 1;
+                return Some(b)
+            }
+            match self.front.next().and_then(|n| n.as_leaf_str()) {
+                Some(s) => self.front_bytes = s.bytes()
+              , None => return None
+            }
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for BytesRev<'a> {}
+
 pub struct GraphemeIndices<'a> {
     strings: Box<Iterator<Item = &'a str> + 'a >
   , graphemes: StrGraphemeIndices<'a>
   , char_length_so_far: usize
   , curr_length: usize
+  , /// the number of bytes which have not yet been yielded
+    remaining: usize
 }
 
 impl<'a> Iterator for GraphemeIndices<'a> {
@@ -713,7 +1460,8 @@ impl<'a> Iterator for GraphemeIndices<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.graphemes.next()
-            .map(|(i, s)| (i + self.char_length_so_far, s))
+            .map(|(i, s)| { self.remaining -= s.len();
+                            (i + self.char_length_so_far, s) })
             .or_else(|| {
                 self.strings.next()
                     .and_then(|s| { self.char_length_so_far += self.curr_length;
@@ -722,6 +1470,158 @@ impl<'a> Iterator for GraphemeIndices<'a> {
                                     self.next() })
             })
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // each grapheme cluster is at least one byte long, and there is at
+        // least one more cluster if there are any bytes left
+        (cmp::min(self.remaining, 1), Some(self.remaining))
+    }
+}
+
+/// An iterator over the byte ranges of the extended grapheme clusters in a
+/// subrope, which segments across leaf boundaries.
+///
+/// This wraps a `GraphemeCursor`, and hands it whichever leaves it asks for.
+/// As well as iterating forwards, it can be used to find the boundaries on
+/// either side of an arbitrary byte index.
+pub struct GraphemeRanges<'a> {
+    node: &'a Node
+  , cursor: GraphemeCursor
+  , /// the leaf the cursor is currently in
+    chunk: &'a str
+  , /// the byte index of the start of `chunk`
+    chunk_start: usize
+}
+
+impl<'a> GraphemeRanges<'a> {
+    /// Makes the leaf containing byte index `i` the current chunk.
+    fn load_chunk(&mut self, i: usize) {
+        let (s, offset) = self.node.leaf_at(i)
+            .expect("GraphemeRanges: the cursor asked for a leaf past the end \
+                     of the rope. something is broken.");
+        self.chunk = s;
+        self.chunk_start = i - offset;
+    }
+
+    /// Gives the cursor the text it asked for when it returned `incomplete`.
+    fn supply(&mut self, incomplete: GraphemeIncomplete) {
+        match incomplete {
+            GraphemeIncomplete::NextChunk => {
+                let next = self.chunk_start + self.chunk.len();
+                self.load_chunk(next)
+            }
+          , GraphemeIncomplete::PrevChunk => {
+                let prev = self.chunk_start - 1;
+                self.load_chunk(prev)
+            }
+          , GraphemeIncomplete::PreContext(end) => {
+                // the cursor wants the text ending at `end`, which is the
+                // start of the leaf containing `end - 1`
+                let (s, offset) = self.node.leaf_at(end - 1)
+                    .expect("GraphemeRanges: the cursor asked for context \
+                             before the start of the rope. something is \
+                             broken.");
+                self.cursor.provide_context(&s[..offset + 1], end - 1 - offset)
+            }
+          , GraphemeIncomplete::InvalidOffset =>
+                unreachable!("GraphemeRanges: the current leaf doesn't \
+                              contain the cursor. something is broken.")
+        }
+    }
+
+    /// Calls `step` on the cursor until it stops asking for more text.
+    fn drive<T, F>(&mut self, mut step: F) -> T
+    where F: FnMut(&mut GraphemeCursor, &str, usize)
+                   -> Result<T, GraphemeIncomplete> {
+        loop {
+            match step(&mut self.cursor, self.chunk, self.chunk_start) {
+                Ok(result) => return result
+              , Err(incomplete) => self.supply(incomplete)
+            }
+        }
+    }
+
+    /// Returns the byte index of the cursor.
+    #[inline]
+    pub fn offset(&self) -> usize { self.cursor.cur_cursor() }
+
+    /// Returns `true` if the cursor is on a grapheme cluster boundary.
+    pub fn is_boundary(&mut self) -> bool {
+        let offset = self.offset();
+        if offset == self.chunk_start && offset != 0
+                                      && offset != self.node.len() {
+            self.is_boundary_at_seam()
+        } else {
+            self.drive(|cursor, chunk, start| cursor.is_boundary(chunk, start))
+        }
+    }
+
+    /// Decides whether the start of the current leaf, where the cursor is,
+    /// is a grapheme cluster boundary.
+    ///
+    /// `GraphemeCursor` gets this wrong when it looks back from the start
+    /// of a chunk: it counts regional indicators it has already stepped over
+    /// a second time, and it never breaks between a prepended character and
+    /// a control character. So the boundary is decided by a new cursor in
+    /// the middle of a window over the `char`s on either side of it.
+    fn is_boundary_at_seam(&mut self) -> bool {
+        let offset = self.offset();
+        let (prev, i) = self.node.leaf_at(offset - 1)
+            .expect("GraphemeRanges: no leaf before a seam. something is \
+                     broken.");
+        let before = prev[..i + 1].chars().next_back()
+            .expect("GraphemeRanges: empty leaf before a seam");
+        let after = self.chunk.chars().next()
+            .expect("GraphemeRanges: empty leaf after a seam");
+        let mut buf = [0; 8];
+        let n = before.encode_utf8(&mut buf).len();
+        let m = after.encode_utf8(&mut buf[n..]).len();
+        let window = str::from_utf8(&buf[..n + m])
+            .expect("GraphemeRanges: a window of two chars isn't UTF-8");
+        let window_start = offset - n;
+        self.cursor = GraphemeCursor::new(offset, self.node.len(), true);
+        loop {
+            match self.cursor.is_boundary(window, window_start) {
+                Ok(result) => return result
+              , Err(incomplete) => self.supply(incomplete)
+            }
+        }
+    }
+
+    /// Moves the cursor forwards to the next grapheme cluster boundary,
+    /// returning its byte index, or `None` if the cursor is at the end.
+    pub fn next_boundary(&mut self) -> Option<usize> {
+        let mut from = self.offset();
+        loop {
+            match self.cursor.next_boundary(self.chunk, self.chunk_start) {
+                Ok(boundary) => return boundary
+              , Err(GraphemeIncomplete::NextChunk) if self.offset() > from => {
+                    // the cursor has stepped onto the start of the next leaf
+                    let offset = self.offset();
+                    self.load_chunk(offset);
+                    if self.is_boundary_at_seam() { return Some(offset) }
+                    from = offset;
+                }
+              , Err(incomplete) => self.supply(incomplete)
+            }
+        }
+    }
+
+    /// Moves the cursor backwards to the previous grapheme cluster boundary,
+    /// returning its byte index, or `None` if the cursor is at the start.
+    pub fn prev_boundary(&mut self) -> Option<usize> {
+        self.drive(|cursor, chunk, start| cursor.prev_boundary(chunk, start))
+    }
+}
+
+impl<'a> Iterator for GraphemeRanges<'a> {
+    type Item = ops::Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.offset();
+        self.next_boundary().map(|end| start..end)
+    }
 }
 
 pub struct UWordBoundIndices<'a> {
@@ -729,6 +1629,8 @@ pub struct UWordBoundIndices<'a> {
   , bounds: StrUWordBoundIndices<'a>
   , char_length_so_far: usize
   , curr_length: usize
+  , /// the number of bytes which have not yet been yielded
+    remaining: usize
 }
 
 impl<'a> Iterator for UWordBoundIndices<'a> {
@@ -736,7 +1638,8 @@ impl<'a> Iterator for UWordBoundIndices<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.bounds.next()
-            .map(|(i, s)| (i + self.char_length_so_far, s))
+            .map(|(i, s)| { self.remaining -= s.len();
+                            (i + self.char_length_so_far, s) })
             .or_else(|| {
                 self.strings.next()
                     .and_then(|s| { self.char_length_so_far += self.curr_length;
@@ -745,6 +1648,13 @@ impl<'a> Iterator for UWordBoundIndices<'a> {
                                     self.next() })
             })
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // each word boundary segment is at least one byte long, and there is
+        // at least one more segment if there are any bytes left
+        (cmp::min(self.remaining, 1), Some(self.remaining))
+    }
 }
 
 impl ops::Add for NodeLink {
@@ -1,14 +1,17 @@
-use metric::{Measured, Line, Grapheme, Metric};
+use metric::{Measured, Line, Grapheme, Char, Metric};
 use super::{NodeLink, LeafRepr };
 
 use self::Value::*;
 
 use std::cell::Cell;
+use std::cmp;
 use std::convert;
 use std::default::Default;
 use std::fmt;
 use std::ops;
 
+use unicode_segmentation::UnicodeSegmentation;
+
 
 
 /// A lazily-evaluated field
@@ -81,6 +84,18 @@ macro_rules! lazy_field {
     }
 }
 
+/// How the grapheme cluster boundaries just after the seam between a
+/// branch's children differ from those of the right child on its own.
+#[derive(Clone, Copy, Debug)]
+struct Seam { /// the number of boundaries of the branch before `end`
+              whole: usize
+            , /// the number of boundaries of the right child before `end`
+              right: usize
+            , /// the byte index, in the branch, of the first boundary which
+              /// they agree on, after which all of their boundaries agree
+              end: usize
+            }
+
 /// A `Node`.
 #[derive(Clone, Default)]
 pub struct Node { len: Lazy<usize>
@@ -89,6 +104,11 @@ pub struct Node { len: Lazy<usize>
                 , line_weight: Lazy<Line>
                 , grapheme_count: Lazy<Grapheme>
                 , grapheme_weight: Lazy<Grapheme>
+                , char_count: Lazy<Char>
+                , char_weight: Lazy<Char>
+                , depth: Lazy<usize>
+                , cluster_count: Lazy<usize>
+                , seam: Lazy<Seam>
                 , pub value: Value
                 }
 
@@ -97,6 +117,117 @@ impl Node {
         Node { value: value, ..Default::default() }
     }
 
+    /// Discards all of this `Node`'s cached metrics, so that they will be
+    /// recomputed the next time they are needed.
+    ///
+    /// This must be called whenever a `Node`'s value is changed in place.
+    pub fn invalidate_cache(&mut self) {
+        self.len = Lazy::new();
+        self.weight = Lazy::new();
+        self.line_count = Lazy::new();
+        self.line_weight = Lazy::new();
+        self.grapheme_count = Lazy::new();
+        self.grapheme_weight = Lazy::new();
+        self.char_count = Lazy::new();
+        self.char_weight = Lazy::new();
+        self.depth = Lazy::new();
+        self.cluster_count = Lazy::new();
+        self.seam = Lazy::new();
+    }
+
+    /// Returns the depth in the tree of a node
+    ///
+    /// A leaf has depth 0, and a branch is one deeper than its deepest child.
+    /// The depth is cached, so that the depths of the children of a new
+    /// branch can be compared in constant time when it is concatenated.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth.get_or_else(|| match self.value {
+            Leaf(_) => 0
+          , Branch { ref left, ref right } =>
+                cmp::max(left.depth(), right.depth()) + 1
+        })
+    }
+
+    /// Returns the number of grapheme clusters in this `Node`'s subrope,
+    /// segmented as a whole.
+    ///
+    /// Unlike the `Grapheme` metric, which is counted in each leaf
+    /// separately, this counts a cluster split between two leaves once. The
+    /// count is cached, and a branch's count is found from its children's,
+    /// fixing up only the clusters around the seam between them.
+    pub fn grapheme_clusters(&self) -> usize {
+        self.cluster_count.get_or_else(|| match self.value {
+            Leaf(ref string) => string.graphemes(true).count()
+          , Branch { ref left, ref right } => {
+                let seam = self.seam();
+                left.grapheme_clusters() + right.grapheme_clusters()
+                    + seam.whole - seam.right
+            }
+        })
+    }
+
+    /// Returns the number of grapheme cluster boundaries before byte index
+    /// `i` in this `Node`'s subrope, segmented as a whole.
+    ///
+    /// This is the number of clusters which begin before `i`.
+    pub fn grapheme_clusters_before(&self, i: usize) -> usize {
+        match self.value {
+            Leaf(ref string) => string[..i].graphemes(true).count()
+          , Branch { ref left, .. } if i <= left.len() =>
+                // boundaries don't depend on the text after them, so the
+                // right child can't change any boundaries before `i`
+                left.grapheme_clusters_before(i)
+          , Branch { ref left, ref right } => {
+                let mut seam = self.seam();
+                if i < seam.end { seam = self.scan_seam(i) }
+                left.grapheme_clusters() + seam.whole
+                    + right.grapheme_clusters_before(i - left.len())
+                    - seam.right
+            }
+        }
+    }
+
+    /// Returns the cached `Seam` between this branch's children.
+    #[inline]
+    fn seam(&self) -> Seam {
+        self.seam.get_or_else(|| self.scan_seam(self.len()))
+    }
+
+    /// Compares the grapheme cluster boundaries after the seam between this
+    /// branch's children with those of the right child on its own, up to
+    /// the first boundary they agree on or byte index `limit`.
+    ///
+    /// From a boundary they agree on onwards, the text before the seam can
+    /// no longer make a difference, so all of the later boundaries agree.
+    fn scan_seam(&self, limit: usize) -> Seam {
+        let (left, right) = match self.value {
+            Branch { ref left, ref right } => (left, right)
+          , Leaf(_) => unreachable!("Node::scan_seam called on a leaf")
+        };
+        let offset = left.len();
+        let mut seam = Seam { whole: 0, right: 0, end: offset };
+        if right.is_empty() { return seam }
+        let mut whole = self.grapheme_ranges(offset);
+        let mut whole_next = if whole.is_boundary() { Some(offset) }
+                             else { whole.next_boundary() };
+        let mut right = right.grapheme_ranges(0);
+        let mut right_next = Some(offset);
+        // the end of the subrope is a boundary of both
+        while let (Some(w), Some(r)) = (whole_next, right_next) {
+            seam.end = cmp::min(w, r);
+            if w == r || seam.end >= limit { break }
+            if w < r {
+                seam.whole += 1;
+                whole_next = whole.next_boundary();
+            } else {
+                seam.right += 1;
+                right_next = right.next_boundary().map(|i| i + offset);
+            }
+        }
+        seam
+    }
+
     pub fn spanning(&self, i: usize, span_len: usize) -> (&Node, usize)
     where Node: Measured<usize> {
         assert!(self.len() >= span_len);
@@ -188,6 +319,17 @@ impl Measured<Grapheme> for Node {
 
 }
 
+impl Measured<Char> for Node {
+
+        #[inline] fn to_byte_index(&self, index: Char) -> Option<usize>  {
+            self.value.to_byte_index(index)
+        }
+
+        lazy_field!(measure, char_count, Char);
+        lazy_field!(measure_weight, char_weight, Char);
+
+}
+
 impl Measured<Line> for Node {
 
         #[inline] fn to_byte_index(&self, index: Line) -> Option<usize>  {
@@ -255,10 +397,18 @@ where M: Metric
     fn to_byte_index(&self, index: M) -> Option<usize> {
         match *self {
             Leaf(ref r) => r.to_byte_index(index)
-          , Branch { ref left, ref right } =>
-                left.to_byte_index(index)
-                    .or_else(|| { right.to_byte_index(index)
-                                       .map(|i| i + left.len() ) })
+          , Branch { ref left, ref right } => {
+                // if the index is less than the left child's measure, then
+                // it's in the left subtree. otherwise, walk the right
+                // subtree, subtracting the left child's measure.
+                let weight: M = left.measure();
+                if index < weight {
+                    left.to_byte_index(index)
+                } else {
+                    right.to_byte_index(index - weight)
+                         .map(|i| i + left.len())
+                }
+            }
         }
     }
 
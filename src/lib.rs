@@ -47,6 +47,9 @@ macro_rules! or_zero {
 }
 
 #[cfg(feature = "tendril")] extern crate tendril;
+#[cfg(feature = "memchr")] extern crate memchr;
+#[cfg(feature = "width")] extern crate unicode_width;
+#[cfg(feature = "crc")] extern crate crc32fast;
 
 #[cfg(test)] #[macro_use] extern crate quickcheck;
 #[cfg(test)] mod test;
@@ -54,8 +57,18 @@ macro_rules! or_zero {
 
 mod unicode;
 pub mod metric;
+pub mod error;
+pub mod pattern;
+pub mod edit;
+pub mod offsets;
+pub mod cursor;
 
-use metric::{Measured, Metric};
+use metric::{Measured, Metric, Line, Char, Grapheme};
+use error::{CharBoundaryError, SliceError};
+use pattern::RopePattern;
+use edit::Edit;
+use offsets::{Segment, WithByteOffsets};
+use cursor::RopeCursor;
 use self::internals::{Node, NodeLink};
 
 pub use self::slice::{ RopeSlice
@@ -88,6 +101,61 @@ pub struct Rope {
     root: NodeLink
 }
 
+/// A style of line ending.
+///
+/// This is used by [`Rope::normalize_newlines()`] to choose which line ending
+/// to convert to, and by [`Rope::iter_lines_with_endings()`] to report how
+/// each line was terminated.
+///
+/// [`Rope::normalize_newlines()`]: struct.Rope.html#method.normalize_newlines
+/// [`Rope::iter_lines_with_endings()`]: struct.Rope.html#method.iter_lines_with_endings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// A line feed, `\n`, as used on Unix-like systems.
+    Lf
+  , /// A carriage return followed by a line feed, `\r\n`, as used on
+    /// Windows.
+    CrLf
+  , /// A lone carriage return, `\r`, as used on classic Mac OS.
+    Cr
+}
+
+impl LineEnding {
+    /// Returns the text of this line ending.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match *self { LineEnding::Lf => "\n"
+                    , LineEnding::CrLf => "\r\n"
+                    , LineEnding::Cr => "\r"
+                    }
+    }
+}
+
+/// A summary of the shape of a `Rope`'s tree, for diagnosing performance
+/// problems.
+///
+/// This is returned by [`Rope::shrink_leaf_fragmentation_report()`]. A rope
+/// built from many small edits may end up with a large number of tiny leaves,
+/// which makes iteration and tree walks slower than necessary; a low
+/// `mean_leaf_len` relative to `leaf_count` is a sign of this.
+///
+/// Empty leaves are not counted.
+///
+/// [`Rope::shrink_leaf_fragmentation_report()`]: struct.Rope.html#method.shrink_leaf_fragmentation_report
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LeafFragmentationReport {
+    /// The number of non-empty leaves in the tree.
+    pub leaf_count: usize
+  , /// The length in bytes of the shortest non-empty leaf.
+    pub min_leaf_len: usize
+  , /// The length in bytes of the longest leaf.
+    pub max_leaf_len: usize
+  , /// The mean length in bytes of the non-empty leaves.
+    pub mean_leaf_len: f64
+  , /// The depth of the tree. A rope consisting of a single leaf has depth 0.
+    pub depth: usize
+}
+
 pub trait Split: Sized {
     fn split<M>(&self, index: M) -> (Self,Self)
     where M: Metric
@@ -329,6 +397,187 @@ impl Rope {
         Rope::from(String::from_utf8_unchecked(bytes))
     }
 
+    /// Decode an iterator of UTF-8 encoded byte chunks into a `Rope`,
+    /// returning `Err` if the bytes are not valid UTF-8.
+    ///
+    /// A multi-byte `char` may be split across the boundary between two
+    /// chunks; its leading bytes are held back until the rest of it arrives.
+    /// This makes it possible to decode a stream, such as one read from a
+    /// socket, without first collecting all of it into one buffer.
+    ///
+    /// If a chunk contains invalid UTF-8, or the last chunk ends in the
+    /// middle of a `char`, the returned [`FromUtf8Error`] holds the bytes of
+    /// that chunk, preceded by any bytes held back from the previous chunk.
+    ///
+    /// [`FromUtf8Error`]: https://doc.rust-lang.org/std/string/struct.FromUtf8Error.html
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    ///
+    /// // the sparkle heart is split across the two chunks
+    /// let chunks = vec![vec![104, 105, 240, 159], vec![146, 150, 33]];
+    /// let rope = Rope::from_byte_chunks(chunks).unwrap();
+    /// assert_eq!(&rope, "hi💖!");
+    ///
+    /// // the stream ends before the sparkle heart does
+    /// let chunks = vec![vec![104, 105], vec![240, 159, 146]];
+    /// assert!(Rope::from_byte_chunks(chunks).is_err());
+    /// ```
+    pub fn from_byte_chunks<I>(iter: I) -> Result<Rope, string::FromUtf8Error>
+    where I: IntoIterator<Item=Vec<u8>> {
+        let mut leaves = Vec::new();
+        let mut tail = Vec::new();
+        for chunk in iter {
+            let mut bytes = tail;
+            bytes.extend(chunk);
+            let string = match String::from_utf8(bytes) {
+                Ok(string) => { tail = Vec::new(); string }
+                // the chunk ends partway through a `char`, so hold on to
+                // its leading bytes until the next chunk arrives
+              , Err(e) if e.utf8_error().error_len().is_none() => {
+                    let valid_up_to = e.utf8_error().valid_up_to();
+                    let mut bytes = e.into_bytes();
+                    tail = bytes.split_off(valid_up_to);
+                    unsafe { String::from_utf8_unchecked(bytes) }
+                }
+              , Err(e) => return Err(e)
+            };
+            if !string.is_empty() { leaves.push(Rope::from(string).root) }
+        }
+        if !tail.is_empty() {
+            // the last chunk ended partway through a `char`
+            String::from_utf8(tail)?;
+        }
+        Ok(Rope::from(Node::balanced_from(&leaves)))
+    }
+
+    /// Builds a `Rope` whose leaves are the strings in `leaves`.
+    ///
+    /// This is the inverse of [`into_leaf_strings()`], and restores a
+    /// `Rope` with the same chunking as the one the strings were taken from.
+    /// A balanced tree is built over the leaves, and empty strings are
+    /// skipped. To keep each leaf to at most one line, a string containing
+    /// a `'\n'` anywhere but at its end is split after each `'\n'`; strings
+    /// returned by [`into_leaf_strings()`] never need to be.
+    ///
+    /// [`into_leaf_strings()`]: struct.Rope.html#method.into_leaf_strings
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from_leaves(vec![ "Löwe ".to_string()
+    ///                                  , "老虎".to_string() ]);
+    /// assert_eq!(&rope, "Löwe 老虎");
+    /// assert_eq!(rope.strings().collect::<Vec<_>>(), vec!["Löwe ", "老虎"]);
+    /// ```
+    pub fn from_leaves(leaves: Vec<String>) -> Rope {
+        let leaves = leaves.into_iter()
+            .filter(|leaf| !leaf.is_empty())
+            .map(|leaf| match leaf.find('\n') {
+                Some(i) if i + 1 < leaf.len() => NodeLink::from(leaf)
+              , _ => Node::new_leaf(leaf)
+            })
+            .collect::<Vec<_>>();
+        Rope::from(Node::balanced_from(&leaves))
+    }
+
+    /// Builds a `Rope` from an iterator of lines, joining them with `'\n'`.
+    ///
+    /// Each line is placed in its own leaf, so the [`Line`] metric of the
+    /// new `Rope` is correct without any further rebalancing. No newline is
+    /// added after the last line; see [`from_terminated_lines()`] for a
+    /// version which adds one.
+    ///
+    /// [`Line`]: metric/struct.Line.html
+    /// [`from_terminated_lines()`]: struct.Rope.html#method.from_terminated_lines
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from_lines(vec!["Löwe", "老虎", "Léopard"]);
+    /// assert_eq!(&rope, "Löwe\n老虎\nLéopard");
+    /// assert_eq!(rope.lines().count(), 3);
+    /// ```
+    pub fn from_lines<I, S>(iter: I) -> Rope
+    where I: IntoIterator<Item=S>
+        , S: AsRef<str> {
+        Rope::build_from_lines(iter, false)
+    }
+
+    /// Builds a `Rope` from an iterator of lines, ending each one with
+    /// `'\n'`.
+    ///
+    /// This is identical to [`from_lines()`], except that a newline is also
+    /// added after the last line.
+    ///
+    /// [`from_lines()`]: struct.Rope.html#method.from_lines
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from_terminated_lines(vec!["Löwe", "老虎"]);
+    /// assert_eq!(&rope, "Löwe\n老虎\n");
+    /// assert_eq!(&Rope::from_terminated_lines(Vec::<String>::new()), "");
+    /// ```
+    pub fn from_terminated_lines<I, S>(iter: I) -> Rope
+    where I: IntoIterator<Item=S>
+        , S: AsRef<str> {
+        Rope::build_from_lines(iter, true)
+    }
+
+    fn build_from_lines<I, S>(iter: I, terminated: bool) -> Rope
+    where I: IntoIterator<Item=S>
+        , S: AsRef<str> {
+        let mut lines = iter.into_iter().peekable();
+        let mut leaves = Vec::new();
+        while let Some(line) = lines.next() {
+            let mut line = String::from(line.as_ref());
+            if terminated || lines.peek().is_some() { line.push('\n') }
+            // a line containing newlines of its own is split into
+            // several leaves, preserving the one-newline-per-leaf invariant
+            leaves.push(Rope::from(line).root);
+        }
+        Rope::from(Node::balanced_from(&leaves))
+    }
+
+    /// Returns `true` if every leaf of this `Rope` contains valid UTF-8.
+    ///
+    /// A `Rope` built through the safe API is always valid UTF-8, so this
+    /// will only return `false` if the `Rope` (or one it was built from) was
+    /// constructed from invalid bytes using [`from_utf8_unchecked()`]. Such a
+    /// `Rope` may panic, or worse, when it is later iterated over; this
+    /// method allows that corruption to be detected up front.
+    ///
+    /// [`from_utf8_unchecked()`]: struct.Rope.html#method.from_utf8_unchecked
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let sparkle_heart = vec![240, 159, 146, 150];
+    /// let sparkle_heart = unsafe {
+    ///     Rope::from_utf8_unchecked(sparkle_heart)
+    /// };
+    /// assert!(sparkle_heart.is_valid_utf8());
+    /// ```
+    #[inline]
+    pub fn is_valid_utf8(&self) -> bool { self.root.is_valid_utf8() }
+
     /// Returns a new empty Rope
     ///
     /// # Examples
@@ -394,6 +643,266 @@ impl Rope {
     /// ```
     #[inline] pub fn is_empty(&self) -> bool { self.len() == 0 }
 
+    /// Returns the number of `char`s in this `Rope`.
+    ///
+    /// Each node caches the number of `char`s in its subtree, so unlike
+    /// `chars().count()`, this doesn't need to walk the text of the `Rope`.
+    ///
+    /// # Time Complexity
+    /// O(1) if this `Rope` has already been counted. After an edit, only the
+    /// O(log _n_) nodes created by the edit need to be counted again; the
+    /// first count of a new `Rope` is O(_n_).
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("Löwe 老虎 Léopard");
+    /// assert_eq!(an_rope.count_chars(), 15);
+    /// assert_eq!(an_rope.len(), 21);
+    /// ```
+    #[inline]
+    pub fn count_chars(&self) -> usize {
+        let chars: Char = self.root.measure();
+        chars.into()
+    }
+
+    /// Returns the number of times `ch` occurs in this `Rope`.
+    ///
+    /// Each leaf is scanned in turn, so the text of the `Rope` is never
+    /// copied. A `char` which is encoded in a single byte can't occur as
+    /// part of a longer `char`, so for ASCII `char`s, such as `'\t'`, the
+    /// bytes of each leaf are compared directly without decoding them.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("\tLöwe\t老虎\t");
+    /// assert_eq!(rope.count_occurrences_of_char('\t'), 3);
+    /// assert_eq!(rope.count_occurrences_of_char('老'), 1);
+    /// assert_eq!(rope.count_occurrences_of_char('x'), 0);
+    /// ```
+    pub fn count_occurrences_of_char(&self, ch: char) -> usize {
+        if ch.is_ascii() {
+            let b = ch as u8;
+            self.strings()
+                .map(|s| s.bytes().filter(|&x| x == b).count())
+                .sum()
+        } else {
+            self.strings()
+                .map(|s| s.chars().filter(|&c| c == ch).count())
+                .sum()
+        }
+    }
+
+    /// Returns the length of this `Rope` in UTF-16 code units.
+    ///
+    /// This is the length the text would have if it were encoded as UTF-16,
+    /// which is useful when interoperating with APIs (such as the Language
+    /// Server Protocol) that index text by UTF-16 code unit. Characters in the
+    /// Basic Multilingual Plane count as one code unit, while astral
+    /// characters count as two.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎 💖");
+    /// assert_eq!(rope.chars_len_utf16(), 10);
+    /// assert_eq!(Rope::new().chars_len_utf16(), 0);
+    /// ```
+    pub fn chars_len_utf16(&self) -> usize {
+        self.strings().map(|s| s.encode_utf16().count()).sum()
+    }
+
+    /// Returns a 64-bit hash of the text in this `Rope`.
+    ///
+    /// The hash depends only on the bytes of the `Rope`, not on the shape of
+    /// its tree, so two `Rope`s with equal contents always have equal hashes.
+    /// It is computed with a fixed algorithm ([FNV-1a]) rather than with a
+    /// randomly-seeded hasher, so it is stable from one run of a program to
+    /// the next, and may be used as a key for on-disk caches or for
+    /// deduplication. It is not a cryptographic hash.
+    ///
+    /// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let one_leaf = Rope::from("hello world");
+    /// let two_leaves = Rope::from("hello ") + Rope::from("world");
+    /// assert_eq!(one_leaf.hash_content(), two_leaves.hash_content());
+    /// assert!(one_leaf.hash_content() != Rope::from("hello").hash_content());
+    /// ```
+    pub fn hash_content(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+        self.bytes().fold(FNV_OFFSET_BASIS, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    /// Folds `f` over the bytes of this `Rope`, one leaf at a time.
+    ///
+    /// `f` is called once for each leaf, in order, with the accumulated
+    /// value and the leaf's bytes. This allows checksums and hashes to be
+    /// computed over whole slices, without the overhead of iterating over
+    /// the `Rope` one byte at a time.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("hello ") + Rope::from("world");
+    /// let len = rope.fold_bytes(0, |len, bytes| len + bytes.len());
+    /// assert_eq!(len, rope.len());
+    /// ```
+    #[inline]
+    pub fn fold_bytes<B, F>(&self, init: B, f: F) -> B
+    where F: FnMut(B, &[u8]) -> B {
+        self.root.fold_bytes(init, f)
+    }
+
+    /// Returns the CRC32 checksum of the bytes in this `Rope`.
+    ///
+    /// The checksum depends only on the text of this `Rope`, and not on how
+    /// that text is divided between leaves, so it can be used to cheaply
+    /// detect whether the contents of a `Rope` have changed. It is computed
+    /// with [`fold_bytes()`], using the [`crc32fast`] crate.
+    ///
+    /// This method is only available when the `crc` feature is enabled.
+    ///
+    /// [`fold_bytes()`]: struct.Rope.html#method.fold_bytes
+    /// [`crc32fast`]: https://crates.io/crates/crc32fast
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "crc")] {
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("hello ") + Rope::from("world");
+    /// assert_eq!( rope.bytes_checksum_crc32()
+    ///           , Rope::from("hello world").bytes_checksum_crc32());
+    /// assert_eq!(Rope::from("123456789").bytes_checksum_crc32(), 0xCBF43926);
+    /// # }
+    /// ```
+    #[cfg(feature = "crc")]
+    pub fn bytes_checksum_crc32(&self) -> u32 {
+        self.fold_bytes(crc32fast::Hasher::new(), |mut hasher, bytes| {
+                hasher.update(bytes);
+                hasher
+            })
+            .finalize()
+    }
+
+    /// Returns the contents of this `Rope` as a `&str`, if it consists of a
+    /// single leaf, or `None` otherwise.
+    ///
+    /// This provides a zero-cost path to the text of a `Rope` that has not
+    /// yet been split or concatenated, such as one just constructed from a
+    /// `&str` or `String`. Note that constructing a `Rope` from a string
+    /// containing line breaks will split it into one leaf per line.
+    ///
+    /// # Time Complexity
+    /// O(1)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a single leaf");
+    /// assert_eq!(rope.as_str(), Some("a single leaf"));
+    ///
+    /// let rope = rope + Rope::from(" and another");
+    /// assert_eq!(rope.as_str(), None);
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> { self.root.as_leaf_str() }
+
+    /// Returns a [`LeafFragmentationReport`] describing the shape of this
+    /// `Rope`'s tree.
+    ///
+    /// This is intended for diagnosing why operations on a `Rope` are slow,
+    /// such as when it has been fragmented into many tiny leaves.
+    ///
+    /// [`LeafFragmentationReport`]: struct.LeafFragmentationReport.html
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the number of nodes in the tree
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("abc") + Rope::from("de") + Rope::from("f");
+    /// let report = rope.shrink_leaf_fragmentation_report();
+    /// assert_eq!(report.leaf_count, 3);
+    /// assert_eq!(report.min_leaf_len, 1);
+    /// assert_eq!(report.max_leaf_len, 3);
+    /// assert_eq!(report.mean_leaf_len, 2.0);
+    /// assert_eq!(report.depth, 2);
+    /// ```
+    pub fn shrink_leaf_fragmentation_report(&self) -> LeafFragmentationReport {
+        let (count, min, max, total) = self.strings()
+            .map(str::len)
+            .fold( (0, usize::MAX, 0, 0)
+                 , |(count, min, max, total), len|
+                    ( count + 1, cmp::min(min, len), cmp::max(max, len)
+                    , total + len ));
+        LeafFragmentationReport {
+            leaf_count: count
+          , min_leaf_len: if count == 0 { 0 } else { min }
+          , max_leaf_len: max
+          , mean_leaf_len: if count == 0 { 0.0 }
+                           else { total as f64 / count as f64 }
+          , depth: self.root.depth()
+        }
+    }
+
+    /// Rebalances this `Rope` in place, merging adjacent leaves which are
+    /// smaller than `threshold` bytes as the tree is rebuilt.
+    ///
+    /// This combines rebalancing with cleaning up fragmentation in a single
+    /// pass, such as after a `Rope` has been built up from many small edits.
+    /// Two adjacent leaves are merged only if their combined length is at
+    /// most `threshold` bytes, and never across a line ending. Leaves which
+    /// are not merged are shared with the original tree. A `threshold` of 0
+    /// only rebalances the `Rope`. [`shrink_leaf_fragmentation_report()`] can
+    /// be used to decide whether this is worthwhile.
+    ///
+    /// [`shrink_leaf_fragmentation_report()`]: struct.Rope.html#method.shrink_leaf_fragmentation_report
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut rope = Rope::new();
+    /// for c in "fragmented".chars() {
+    ///     rope = rope + Rope::from(c.to_string());
+    /// }
+    /// assert_eq!(rope.shrink_leaf_fragmentation_report().leaf_count, 10);
+    ///
+    /// rope.shrink_small_leaves_threshold(4);
+    /// assert_eq!(&rope, "fragmented");
+    /// let report = rope.shrink_leaf_fragmentation_report();
+    /// assert_eq!(report.leaf_count, 3);
+    /// assert_eq!(report.depth, 2);
+    /// ```
+    pub fn shrink_small_leaves_threshold(&mut self, threshold: usize) {
+        self.root = self.root.rebuild_coalescing(threshold);
+    }
+
     /// Insert `ch` into `index` in this `Rope`, returning a new `Rope`.
     ///
     ///
@@ -455,6 +964,42 @@ impl Rope {
         self.insert_rope(index, &Rope::from(s))
     }
 
+    /// Insert a line break at `index` in this `Rope`, returning a new `Rope`.
+    ///
+    /// The new `'\n'` is placed in a leaf of its own, so the [`Line`] metric
+    /// of the returned `Rope` counts exactly one more line ending than this
+    /// `Rope` does.
+    ///
+    /// [`Line`]: metric/struct.Line.html
+    ///
+    /// # Panics
+    /// * If `index` is out of bounds
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first line second line\n");
+    /// let split = rope.insert_newline(10);
+    /// assert_eq!(&split, "first line\n second line\n");
+    /// assert_eq!(split.lines().count(), 2);
+    /// ```
+    #[inline]
+    pub fn insert_newline<M>(&self, index: M) -> Rope
+    where M: Metric
+        , Self: Measured<M>
+        , NodeLink: Measured<M>
+        , String: Measured<M>
+        , str: Measured<M>
+        {
+        assert!( index <= self.measure()
+               , "Rope::insert_newline: index {:?} was > length {:?}"
+               , index, self.measure());
+        self.insert_rope(index, &Rope::from("\n"))
+    }
+
 
 
     /// Delete the range `range` from this `Rope`,
@@ -476,6 +1021,18 @@ impl Rope {
     /// let an_rope = an_rope.delete((8..12));
     /// assert_eq!(&an_rope, "this is fine");
     /// ```
+    ///
+    /// A range of `Line`s covers whole lines, so both its first and last
+    /// lines are deleted, along with their line endings:
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("one\ntwo\nthree\nfour");
+    /// assert_eq!(&rope.delete(Line(1)..Line(2)), "one\nfour");
+    /// assert_eq!(&rope.delete(Line(0)..Line(0)), "two\nthree\nfour");
+    /// assert_eq!(&rope.delete(Line(3)..Line(3)), "one\ntwo\nthree\n");
+    /// ```
     #[inline]
     #[cfg(feature = "unstable")]
     pub fn delete<R, M>(&self, range: R) -> Rope
@@ -493,8 +1050,9 @@ impl Rope {
 
         assert!( start <= end
                , "invalid index! start {:?} > end {:?}", end, start);
-        let (l, r) = self.root.split(start);
-        let (_, r) = r.split(end - start);
+        let bytes = self.byte_range(start..end);
+        let (l, r) = self.root.split::<usize>(bytes.start);
+        let (_, r) = r.split::<usize>(bytes.end - bytes.start);
         Rope::from(Node::new_branch(l, r))
     }
 
@@ -505,77 +1063,601 @@ impl Rope {
         , String: Measured<M>
         , str: Measured<M>
         {
-        let (l, r) = self.root.split(range.start);
-        let (_, r) = r.split(range.end - range.start);
+        let bytes = self.byte_range(range);
+        let (l, r) = self.root.split::<usize>(bytes.start);
+        let (_, r) = r.split::<usize>(bytes.end - bytes.start);
         Rope::from(Node::new_branch(l, r))
     }
 
-
-    /// Insert `rope` into `index` in this `Rope`, returning a new `Rope`.
+    /// Converts `range`, measured by the metric `M`, into a range of byte
+    /// indices into this `Rope`.
     ///
-    /// # Returns
-    /// * A new `Rope` with `rope` inserted at `index`
+    /// The start of the range is found with [`Metric::range_start()`], so a
+    /// range of `Line`s covers its first line. The remainder of a split
+    /// doesn't start on a boundary of the metric, so callers should split
+    /// on these byte indices rather than on `range.end - range.start`.
     ///
-    /// # Time Complexity
-    /// O(log _n_)
+    /// [`Metric::range_start()`]: metric/trait.Metric.html#method.range_start
     ///
     /// # Panics
-    /// * If `index` is greater than the length of this `Rope`
-    ///
-    /// # Examples
-    ///
-    /// Inserting at index 0 prepends `rope` to this `Rope`:
+    /// * If either end of `range` is outside of this `Rope`
+    fn byte_range<M: Metric>(&self, range: ops::Range<M>) -> ops::Range<usize>
+    where NodeLink: Measured<M> {
+        let start = range.start.range_start(&self.root)
+                         .expect("invalid index!");
+        let end = self.root.to_byte_index(range.end).expect("invalid index!");
+        start..end
+    }
+
+    /// Returns a new `Rope` with the range `range` deleted, leaving this
+    /// `Rope` unchanged.
     ///
-    /// ```
-    /// use an_rope::Rope;
-    /// let an_rope = Rope::from("cd");
-    /// let new_rope = an_rope.insert_rope(0, &Rope::from("ab"));
-    /// assert_eq!(new_rope, Rope::from("abcd"));
-    /// assert_eq!(an_rope, Rope::from("cd"));
-    /// ```
+    /// This is the persistent counterpart to [`remove_range_returning_rope()`].
+    /// The new `Rope` shares all of the nodes outside of `range` with this
+    /// `Rope`, so no text is copied except in the (at most two) leaves that
+    /// `range` begins or ends within.
     ///
-    /// Inserting at index `len` prepends `rope` to this `Rope`:
+    /// [`remove_range_returning_rope()`]: struct.Rope.html#method.remove_range_returning_rope
     ///
-    /// ```
-    /// use an_rope::Rope;
-    /// let an_rope = Rope::from("ab");
-    /// let new_rope = an_rope.insert_rope(an_rope.len(), &Rope::from("cd"));
-    /// assert_eq!(new_rope, Rope::from("abcd"));
-    /// assert_eq!(an_rope, Rope::from("ab"));
-    /// ```
+    /// # Panics
+    /// * If the start or end of `range` are indices outside of the `Rope`
+    /// * If the start index of `range` is greater than the end index
     ///
-    /// Inserting at an index in the middle inserts `rope` at that index:
+    /// # Time Complexity
+    /// O(log _n_)
     ///
+    /// # Examples
     /// ```
     /// use an_rope::Rope;
-    /// let an_rope = Rope::from("ad");
-    /// let new_rope = an_rope.insert_rope(1, &Rope::from("bc"));
-    /// assert_eq!(new_rope, Rope::from("abcd"));
-    /// assert_eq!(an_rope, Rope::from("ad"))
+    /// let an_rope = Rope::from("this is not fine");
+    /// let new_rope = an_rope.with_delete(8..12);
+    /// assert_eq!(&new_rope, "this is fine");
+    /// assert_eq!(&an_rope, "this is not fine");
     /// ```
     #[inline]
-    pub fn insert_rope<M>(&self, index: M, rope: &Rope) -> Rope
+    pub fn with_delete<M>(&self, range: ops::Range<M>) -> Rope
     where M: Metric
         , Self: Measured<M>
         , NodeLink: Measured<M>
         , String: Measured<M>
         , str: Measured<M>
         {
-        if !rope.is_empty() {
-            let len = self.measure();
-            if index.into() == 0 {
-                // if the rope is being inserted at index 0, just prepend it
-                self.prepend(rope)
+        assert!( range.start <= range.end
+               , "invalid index! start {:?} > end {:?}"
+               , range.start, range.end);
+        assert!( range.end <= self.measure()
+               , "Rope::with_delete: index {:?} was > length {:?}"
+               , range.end, self.measure());
+        self.delete(range)
+    }
+
+    /// Returns a new `Rope` with the byte range `range` replaced by `s`,
+    /// leaving this `Rope` unchanged.
+    ///
+    /// This is the persistent counterpart to [`apply_edit()`].
+    ///
+    /// [`apply_edit()`]: struct.Rope.html#method.apply_edit
+    ///
+    /// # Panics
+    /// * If the start index of `range` is greater than the end index
+    /// * If the end of `range` is greater than the length of this `Rope`
+    /// * If either end of `range` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("this is not fine");
+    /// let new_rope = an_rope.with_replace_range(8..11, "totally");
+    /// assert_eq!(&new_rope, "this is totally fine");
+    /// assert_eq!(&an_rope, "this is not fine");
+    /// ```
+    pub fn with_replace_range(&self, range: ops::Range<usize>, s: &str)
+                              -> Rope {
+        let mut rope = self.clone();
+        rope.apply_edit(range, Rope::from(s));
+        rope
+    }
+
+    /// Returns a new `Rope` shortened to `len` bytes, leaving this `Rope`
+    /// unchanged.
+    ///
+    /// Like `String::truncate()`, if `len` is greater than or equal to the
+    /// `Rope`'s current length, the whole `Rope` is returned. Unlike
+    /// [`truncate_bytes()`], which snaps `len` down to a `char` boundary,
+    /// this panics if `len` falls in the middle of a character.
+    ///
+    /// [`truncate_bytes()`]: struct.Rope.html#method.truncate_bytes
+    ///
+    /// # Panics
+    /// If `len` does not fall on a `char` boundary.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("this is fine");
+    /// assert_eq!(&an_rope.with_truncate(4), "this");
+    /// assert_eq!(&an_rope.with_truncate(100), "this is fine");
+    /// assert_eq!(&an_rope, "this is fine");
+    /// ```
+    pub fn with_truncate(&self, len: usize) -> Rope {
+        if len >= self.len() {
+            self.clone()
+        } else {
+            assert!( self.is_char_boundary(len)
+                   , "Rope::with_truncate: byte index {} is not a char \
+                      boundary"
+                   , len);
+            self.split(len).0
+        }
+    }
+
+    /// Splits this `Rope` in two at the byte index `at`, returning the two
+    /// halves and leaving this `Rope` unchanged.
+    ///
+    /// This is the persistent counterpart to `String::split_off()`: the
+    /// first `Rope` contains the bytes `[0, at)`, and the second contains
+    /// the bytes `[at, len)`.
+    ///
+    /// # Panics
+    /// * If `at` is greater than the length of this `Rope`
+    /// * If `at` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("this is fine");
+    /// let (left, right) = an_rope.with_split_off(5);
+    /// assert_eq!(&left, "this ");
+    /// assert_eq!(&right, "is fine");
+    /// assert_eq!(&an_rope, "this is fine");
+    /// ```
+    pub fn with_split_off(&self, at: usize) -> (Rope, Rope) {
+        assert!( at <= self.len()
+               , "Rope::with_split_off: byte index {} is out of bounds \
+                  (length {})"
+               , at, self.len());
+        assert!( self.is_char_boundary(at)
+               , "Rope::with_split_off: byte index {} is not a char boundary"
+               , at);
+        self.split(at)
+    }
+
+    /// Consumes this `Rope`, splitting it after its `n`th grapheme cluster
+    /// and returning the two halves.
+    ///
+    /// Unlike the byte-based [`split`](#method.split), the cut is always
+    /// made between two extended grapheme clusters, so a base character is
+    /// never separated from the combining marks which follow it. Splitting
+    /// at `0` or at the number of graphemes in the `Rope` returns an empty
+    /// `Rope` on one side.
+    ///
+    /// # Panics
+    /// If `n` is greater than the number of grapheme clusters in this `Rope`.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let (left, right) = Rope::from("a̐éö̲").split_at_grapheme(1);
+    /// assert_eq!(&left, "a̐");
+    /// assert_eq!(&right, "éö̲");
+    /// ```
+    pub fn split_at_grapheme(self, n: usize) -> (Rope, Rope) {
+        let Grapheme(len) = self.measure();
+        assert!( n <= len
+               , "Rope::split_at_grapheme: grapheme index {} is out of bounds \
+                  (length {})"
+               , n, len);
+        let at = self.to_byte_index(Grapheme(n))
+                     .expect("grapheme index should be in bounds");
+        self.split(at)
+    }
+
+    /// Returns a new `Rope` with the text in the byte ranges `a` and `b`
+    /// swapped, leaving this `Rope` unchanged.
+    ///
+    /// The ranges may be given in either order, and may be of different
+    /// lengths, but must not overlap. This is useful for editor operations
+    /// such as transposing two lines or two selections.
+    ///
+    /// # Panics
+    /// * If `a` and `b` overlap
+    /// * If the start of either range is greater than its end
+    /// * If the end of either range is greater than the length of this `Rope`
+    /// * If any end of either range does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\nsecond\nthird\n");
+    /// assert_eq!(&rope.swap_ranges(0..6, 13..19), "third\nsecond\nfirst\n");
+    /// assert_eq!(&rope, "first\nsecond\nthird\n");
+    /// ```
+    pub fn swap_ranges(&self, a: ops::Range<usize>, b: ops::Range<usize>)
+                       -> Rope {
+        for range in &[&a, &b] {
+            assert!( range.start <= range.end
+                   , "Rope::swap_ranges: start index {} > end index {}"
+                   , range.start, range.end);
+            assert!( range.end <= self.len()
+                   , "Rope::swap_ranges: byte index {} is out of bounds \
+                      (length {})"
+                   , range.end, self.len());
+            assert!( self.is_char_boundary(range.start)
+                   , "Rope::swap_ranges: byte index {} is not a char boundary"
+                   , range.start);
+            assert!( self.is_char_boundary(range.end)
+                   , "Rope::swap_ranges: byte index {} is not a char boundary"
+                   , range.end);
+        }
+        let (a, b) = if a.start <= b.start { (a, b) } else { (b, a) };
+        assert!( a.end <= b.start
+               , "Rope::swap_ranges: ranges {:?} and {:?} overlap", a, b);
+        // split off the five pieces from the right, so each split index is
+        // still relative to the start of the remaining left piece
+        let (rest, after) = self.root.split(b.end);
+        let (rest, b_text) = rest.split(b.start);
+        let (rest, between) = rest.split(a.end);
+        let (before, a_text) = rest.split(a.start);
+        let pieces = [before, b_text, between, a_text, after];
+        let pieces = pieces.iter()
+                           .filter(|node| !node.is_empty())
+                           .cloned()
+                           .collect::<Vec<_>>();
+        Rope::from(Node::balanced_from(&pieces))
+    }
+
+    /// Returns a new `Rope` with the text in the byte range `from` moved to
+    /// the byte offset `to`, leaving this `Rope` unchanged.
+    ///
+    /// `to` is an offset into this `Rope` _before_ the text is removed, so
+    /// the moved text ends up immediately before whatever followed `to`
+    /// originally. If `to` lies within `from` (including at either end of
+    /// it), the text would not move, and an equal `Rope` is returned.
+    ///
+    /// # Panics
+    /// * If the start of `from` is greater than its end
+    /// * If `to` or the end of `from` is greater than the length of this
+    ///   `Rope`
+    /// * If `to` or either end of `from` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("drag and drop");
+    /// assert_eq!(&rope.move_range(0..5, 13), "and dropdrag ");
+    /// assert_eq!(&rope.move_range(9..13, 0), "dropdrag and ");
+    /// ```
+    pub fn move_range(&self, from: ops::Range<usize>, to: usize) -> Rope {
+        assert!( to <= self.len()
+               , "Rope::move_range: byte index {} is out of bounds \
+                  (length {})"
+               , to, self.len());
+        assert!( self.is_char_boundary(to)
+               , "Rope::move_range: byte index {} is not a char boundary"
+               , to);
+        // moving a range is the same as swapping it with the text between it
+        // and the target
+        if to < from.start {
+            self.swap_ranges(to..from.start, from)
+        } else if to > from.end {
+            let end = from.end;
+            self.swap_ranges(from, end..to)
+        } else {
+            // check `from`'s bounds even though nothing moves
+            let end = from.end;
+            self.swap_ranges(from, end..end)
+        }
+    }
+
+    /// Removes the range `range` from this `Rope`, returning the removed text
+    /// as a new `Rope`.
+    ///
+    /// Unlike [`delete()`], this modifies this `Rope` in place, and rather
+    /// than discarding the removed text, it is returned as a `Rope` sharing
+    /// structure with the original. This is useful for operations such as
+    /// cutting a selection to a clipboard.
+    ///
+    /// [`delete()`]: struct.Rope.html#method.delete
+    ///
+    /// # Panics
+    /// * If the start or end of `range` are indices outside of the `Rope`
+    /// * If the start index of `range` is greater than the end index
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut an_rope = Rope::from("this is not fine");
+    /// let removed = an_rope.remove_range_returning_rope(8..12);
+    /// assert_eq!(&an_rope, "this is fine");
+    /// assert_eq!(&removed, "not ");
+    /// ```
+    pub fn remove_range_returning_rope<M>(&mut self, range: ops::Range<M>)
+                                          -> Rope
+    where M: Metric
+        , Self: Measured<M>
+        , NodeLink: Measured<M>
+        , String: Measured<M>
+        , str: Measured<M>
+        {
+        assert!( range.start <= range.end
+               , "invalid index! start {:?} > end {:?}"
+               , range.start, range.end);
+        assert!( range.end <= self.measure()
+               , "Rope::remove_range_returning_rope: index {:?} was > \
+                  length {:?}"
+               , range.end, self.measure());
+        let bytes = self.byte_range(range);
+        let (left, right) = self.root.split::<usize>(bytes.start);
+        let (removed, right) = right.split::<usize>(bytes.end - bytes.start);
+        self.root = Node::join(&left, &right);
+        Rope::from(removed)
+    }
+
+    /// Replaces the grapheme clusters in `range` with `replacement`, in
+    /// place.
+    ///
+    /// Both ends of `range` are converted to byte indices using the
+    /// [`Grapheme`] metric, so they always fall on grapheme cluster
+    /// boundaries, and no cluster is split by the edit. This is the natural
+    /// way for an editor to replace a selection made of user-visible
+    /// characters.
+    ///
+    /// [`Grapheme`]: metric/struct.Grapheme.html
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the number of grapheme
+    ///   clusters in this `Rope`
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Grapheme;
+    /// let mut an_rope = Rope::from("a̐éö̲");
+    /// an_rope.splice_graphemes(Grapheme(1)..Grapheme(2), "老虎");
+    /// assert_eq!(&an_rope, "a̐老虎ö̲");
+    /// ```
+    pub fn splice_graphemes( &mut self
+                           , range: ops::Range<Grapheme>
+                           , replacement: &str) {
+        assert!( range.start <= range.end
+               , "Rope::splice_graphemes: start index {:?} > end index {:?}"
+               , range.start, range.end);
+        let len: Grapheme = self.measure();
+        assert!( range.end <= len
+               , "Rope::splice_graphemes: index {:?} was > length {:?}"
+               , range.end, len);
+        let to_byte_index = |i: Grapheme| self.root.to_byte_index(i)
+            .expect("Rope::splice_graphemes: grapheme index has no byte \
+                     index. something is broken.");
+        let start = to_byte_index(range.start);
+        let end = to_byte_index(range.end);
+        let spliced = self.delete(start..end);
+        *self = if replacement.is_empty() { spliced }
+                else { spliced.insert_str(start, replacement) };
+    }
+
+    /// Retains only the lines for which the predicate `f` returns `true`,
+    /// removing all other lines from this `Rope` in place.
+    ///
+    /// The predicate is passed each line as a `RopeSlice`, without its line
+    /// ending. As in [`iter_lines_with_endings()`], `"\n"`, `"\r\n"`, and a
+    /// lone `"\r"` are all recognised as line endings. Kept lines retain
+    /// their line endings, so if the last line of the `Rope` is not
+    /// terminated, it won't be after filtering, either. A line ending at
+    /// the very end of the `Rope` does not begin another (empty) line.
+    ///
+    /// [`iter_lines_with_endings()`]: struct.Rope.html#method.iter_lines_with_endings
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut log = Rope::from("INFO: ok\nDEBUG: noise\nINFO: done\n");
+    /// log.retain_lines(|line| !line.to_string().starts_with("DEBUG"));
+    /// assert_eq!(&log, "INFO: ok\nINFO: done\n");
+    ///
+    /// let mut log = Rope::from("INFO: ok\r\nDEBUG: noise\r\nINFO: done");
+    /// log.retain_lines(|line| !line.to_string().starts_with("DEBUG"));
+    /// assert_eq!(&log, "INFO: ok\r\nINFO: done");
+    /// ```
+    pub fn retain_lines<F>(&mut self, mut f: F)
+    where F: FnMut(RopeSlice) -> bool {
+        // the byte ranges of runs of consecutive kept lines
+        let mut kept: Vec<ops::Range<usize>> = Vec::new();
+        let mut start = 0;
+        for (line, ending) in self.iter_lines_with_endings() {
+            let end = start + line.len()
+                    + ending.map_or(0, |ending| ending.as_str().len());
+            if f(line) {
+                match kept.last_mut() {
+                    Some(ref mut run) if run.end == start => run.end = end
+                  , _ => kept.push(start..end)
+                }
+            }
+            start = end;
+        }
+        let nodes = kept.into_iter()
+            .map(|run| self.root.split(run.start).1.split(run.end - run.start).0)
+            .collect::<Vec<_>>();
+        self.root = Rope::from(Node::balanced_from(&nodes)).root;
+    }
+
+    /// Replaces the byte range `range` of this `Rope` with `replacement`,
+    /// returning an [`Edit`] which undoes the replacement.
+    ///
+    /// The returned `Edit` replaces the newly inserted text with the text
+    /// that was removed from `range`, so passing it to [`apply()`] restores
+    /// this `Rope` exactly.
+    ///
+    /// [`Edit`]: edit/struct.Edit.html
+    /// [`apply()`]: struct.Rope.html#method.apply
+    ///
+    /// # Panics
+    /// * If the start index of `range` is greater than the end index
+    /// * If the end of `range` is greater than the length of this `Rope`
+    /// * If either end of `range` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut rope = Rope::from("this is not fine");
+    /// let undo = rope.apply_edit(8..11, Rope::from("totally"));
+    /// assert_eq!(&rope, "this is totally fine");
+    /// assert_eq!(undo.range, 8..15);
+    /// assert_eq!(&undo.replacement, "not");
+    /// ```
+    pub fn apply_edit(&mut self, range: ops::Range<usize>, replacement: Rope)
+                      -> Edit {
+        assert!( range.start <= range.end
+               , "Rope::apply_edit: start index {} > end index {}"
+               , range.start, range.end);
+        assert!( range.end <= self.len()
+               , "Rope::apply_edit: byte index {} is out of bounds (length \
+                  {})"
+               , range.end, self.len());
+        assert!( self.is_char_boundary(range.start)
+               , "Rope::apply_edit: byte index {} is not a char boundary"
+               , range.start);
+        assert!( self.is_char_boundary(range.end)
+               , "Rope::apply_edit: byte index {} is not a char boundary"
+               , range.end);
+        let start = range.start;
+        let removed = self.remove_range_returning_rope(range);
+        let inserted = start..start + replacement.len();
+        self.root = self.insert_rope(start, &replacement).root;
+        Edit::new(inserted, removed)
+    }
+
+    /// Applies `edit` to this `Rope`, returning an [`Edit`] which undoes it.
+    ///
+    /// This is equivalent to calling [`apply_edit()`] with the edit's range
+    /// and replacement.
+    ///
+    /// [`Edit`]: edit/struct.Edit.html
+    /// [`apply_edit()`]: struct.Rope.html#method.apply_edit
+    ///
+    /// # Panics
+    /// Under the same conditions as [`apply_edit()`].
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut rope = Rope::from("this is not fine");
+    /// let undo = rope.apply_edit(8..12, Rope::new());
+    /// assert_eq!(&rope, "this is fine");
+    /// rope.apply(undo);
+    /// assert_eq!(&rope, "this is not fine");
+    /// ```
+    #[inline]
+    pub fn apply(&mut self, edit: Edit) -> Edit {
+        self.apply_edit(edit.range, edit.replacement)
+    }
+
+
+    /// Insert `rope` into `index` in this `Rope`, returning a new `Rope`.
+    ///
+    /// # Returns
+    /// * A new `Rope` with `rope` inserted at `index`
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Panics
+    /// * If `index` is greater than the length of this `Rope`
+    ///
+    /// # Examples
+    ///
+    /// Inserting at index 0 prepends `rope` to this `Rope`:
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("cd");
+    /// let new_rope = an_rope.insert_rope(0, &Rope::from("ab"));
+    /// assert_eq!(new_rope, Rope::from("abcd"));
+    /// assert_eq!(an_rope, Rope::from("cd"));
+    /// ```
+    ///
+    /// Inserting at index `len` prepends `rope` to this `Rope`:
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("ab");
+    /// let new_rope = an_rope.insert_rope(an_rope.len(), &Rope::from("cd"));
+    /// assert_eq!(new_rope, Rope::from("abcd"));
+    /// assert_eq!(an_rope, Rope::from("ab"));
+    /// ```
+    ///
+    /// Inserting at an index in the middle inserts `rope` at that index:
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("ad");
+    /// let new_rope = an_rope.insert_rope(1, &Rope::from("bc"));
+    /// assert_eq!(new_rope, Rope::from("abcd"));
+    /// assert_eq!(an_rope, Rope::from("ad"))
+    /// ```
+    ///
+    /// A `Rope` may be inserted into itself. Since nodes are immutable and
+    /// shared by reference counting, the inserted copy shares structure with
+    /// the original rather than aliasing it, so this is both cheap and safe:
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("abcd");
+    /// let new_rope = an_rope.insert_rope(2, &an_rope);
+    /// assert_eq!(new_rope, Rope::from("ababcdcd"));
+    /// assert_eq!(an_rope, Rope::from("abcd"));
+    /// ```
+    #[inline]
+    pub fn insert_rope<M>(&self, index: M, rope: &Rope) -> Rope
+    where M: Metric
+        , Self: Measured<M>
+        , NodeLink: Measured<M>
+        , String: Measured<M>
+        , str: Measured<M>
+        {
+        if !rope.is_empty() {
+            let len = self.measure();
+            if index.into() == 0 {
+                // if the rope is being inserted at index 0, just prepend it
+                self.prepend(rope)
             } else if index == len {
                 // if the rope is being inserted at index len, append it
                 self.append(rope)
             } else {
-                // split the rope at the given index
-                let (left, right) = self.root.split(index);
-
-                // construct the new root node with `Rope` inserted
-                // rebalance the new rope
-                Rope::from(&left + &rope.root + right)
+                // walk down to the index and insert the rope there, only
+                // rebalancing the nodes on the path to the insertion point
+                Rope::from(self.root.insert_at(index, &rope.root))
             }
         } else {
             self.clone()
@@ -652,23 +1734,181 @@ impl Rope {
     /// assert_eq!(&an_rope, "abcd");
     /// ```
     pub fn append(&self, other: &Rope) -> Rope {
-        if !other.is_empty() {
-            Rope::from(&self.root + &other.root)
-        } else {
+        if other.is_empty() {
             self.clone()
+        } else if self.is_empty() {
+            // don't wrap an empty leaf in a new branch node
+            other.clone()
+        } else {
+            Rope::from(&self.root + &other.root)
         }
     }
 
-    /// Prepends a `Rope` to the end of this `Rope`, returning a new `Rope`
+    /// Appends `other` to the end of this `Rope` in place.
     ///
-    /// # Examples
+    /// Unlike [`append()`], which always concatenates the two trees with a
+    /// new branch node, `push_rope()` merges the last leaf of this `Rope` and
+    /// the first leaf of `other` into a single leaf when they are both small.
+    /// This keeps the tree shallow when a `Rope` is built up incrementally
+    /// from many small pieces.
+    ///
+    /// [`append()`]: struct.Rope.html#method.append
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
     ///
+    /// # Examples
     /// ```
     /// use an_rope::Rope;
-    /// let an_rope = Rope::from("efgh");
-    /// let another_rope = an_rope.prepend(&Rope::from("abcd"));
-    /// assert_eq!(&an_rope, "efgh");
-    /// assert_eq!(&another_rope, "abcdefgh");
+    /// let mut an_rope = Rope::from("abcd");
+    /// an_rope.push_rope(Rope::from("efgh"));
+    /// assert_eq!(&an_rope, "abcdefgh");
+    /// // the two small leaves were merged into one
+    /// assert_eq!(an_rope.strings().count(), 1);
+    /// ```
+    pub fn push_rope(&mut self, other: Rope) {
+        use internals::MERGE_THRESHOLD;
+        if other.is_empty() { return }
+        if self.is_empty() {
+            self.root = other.root;
+            return
+        }
+        let merged = match ( self.root.split_last_leaf()
+                           , other.root.split_first_leaf() ) {
+            (Some((init, last)), Some((first, tail)))
+            // don't merge across a line ending, so that each leaf still
+            // contains at most one line
+            if last.len() + first.len() <= MERGE_THRESHOLD
+                && !last.ends_with('\n') => {
+                let mut s = String::with_capacity(last.len() + first.len());
+                s.push_str(last);
+                s.push_str(first);
+                let mut root = Node::new_leaf(s);
+                if !init.is_empty() { root = init + root; }
+                if !tail.is_empty() { root = root + tail; }
+                Some(root)
+            }
+          , _ => None
+        };
+        self.root = match merged {
+            Some(root) => Rope::from(root).root
+          , None => Rope::from(&self.root + &other.root).root
+        };
+    }
+
+    /// Appends `line` and a `'\n'` to the end of this `Rope` in place.
+    ///
+    /// This is convenient for building up a `Rope` line by line, such as a
+    /// log buffer. If this `Rope` doesn't already end with a line ending,
+    /// `line` continues its last line. Like [`push_rope()`], the new text is
+    /// merged into the last leaf of this `Rope` when both are small and that
+    /// leaf doesn't end a line; otherwise, each line gets its own leaf.
+    ///
+    /// [`push_rope()`]: struct.Rope.html#method.push_rope
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut log = Rope::new();
+    /// log.append_line("starting up");
+    /// log.append_line("ready");
+    /// assert_eq!(&log, "starting up\nready\n");
+    /// assert_eq!(log.lines().count(), 2);
+    /// ```
+    pub fn append_line(&mut self, line: &str) {
+        let mut s = String::with_capacity(line.len() + 1);
+        s.push_str(line);
+        s.push('\n');
+        let line = if line.contains('\n') {
+            // a line containing newlines of its own is split into several
+            // leaves, preserving the one-newline-per-leaf invariant
+            Rope::from(s)
+        } else {
+            // don't leave an empty leaf after the newline
+            Rope::from(Node::new_leaf(s))
+        };
+        self.push_rope(line)
+    }
+
+    /// Appends the grapheme cluster `g` to the end of this `Rope` in place.
+    ///
+    /// `g` must be a single extended grapheme cluster, such as a composed
+    /// sequence produced by an input method. Like [`push_rope()`], it is
+    /// merged into the last leaf of this `Rope` when that leaf is small, so
+    /// typing one grapheme at a time doesn't grow a new leaf per keystroke.
+    ///
+    /// [`push_rope()`]: struct.Rope.html#method.push_rope
+    ///
+    /// # Panics
+    /// In debug builds, if `g` is not exactly one grapheme cluster.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut an_rope = Rope::from("Lo\u{308}");
+    /// an_rope.push_grapheme("w");
+    /// an_rope.push_grapheme("e\u{301}");
+    /// assert_eq!(&an_rope, "Lo\u{308}we\u{301}");
+    /// assert_eq!(an_rope.graphemes().count(), 4);
+    /// assert_eq!(an_rope.strings().count(), 1);
+    /// ```
+    pub fn push_grapheme(&mut self, g: &str) {
+        debug_assert!( {
+                           use unicode_segmentation::UnicodeSegmentation;
+                           g.graphemes(true).count() == 1
+                       }
+                     , "Rope::push_grapheme: {:?} is not a single grapheme \
+                        cluster", g);
+        self.push_rope(Rope::from(g))
+    }
+
+    /// Appends `n` copies of this `Rope` to the end of `buf` in place.
+    ///
+    /// The copies are built by repeated doubling, so only O(log _n_) new
+    /// nodes are created, and every copy shares this `Rope`'s tree rather
+    /// than duplicating its text. The repetitions are then joined onto
+    /// `buf` in a single rebalancing step, which leaves `buf`'s existing
+    /// contents in place.
+    ///
+    /// # Time Complexity
+    /// O(log _n_ + log _m_), where _m_ is the length of `buf`
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut buf = Rope::from("> ");
+    /// Rope::from("ab").repeat_into(&mut buf, 3);
+    /// assert_eq!(&buf, "> ababab");
+    /// ```
+    pub fn repeat_into(&self, buf: &mut Rope, n: usize) {
+        if self.is_empty() { return }
+        let mut repeated = Node::empty();
+        let mut doubled = self.root.clone();
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 { repeated = Node::join(&repeated, &doubled); }
+            n >>= 1;
+            if n > 0 { doubled = &doubled + &doubled; }
+        }
+        buf.root = Node::join(&buf.root, &repeated);
+    }
+
+    /// Prepends a `Rope` to the end of this `Rope`, returning a new `Rope`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("efgh");
+    /// let another_rope = an_rope.prepend(&Rope::from("abcd"));
+    /// assert_eq!(&an_rope, "efgh");
+    /// assert_eq!(&another_rope, "abcdefgh");
     /// ```
     ///
     /// ```
@@ -688,11 +1928,103 @@ impl Rope {
     /// assert_eq!(&another_rope, "abcd");
     /// ```
     pub fn prepend(&self, other: &Rope) -> Rope {
-        if !other.is_empty() {
-            Rope::from(&other.root + &self.root)
-        } else {
+        if other.is_empty() {
             self.clone()
+        } else if self.is_empty() {
+            // don't wrap an empty leaf in a new branch node
+            other.clone()
+        } else {
+            Rope::from(&other.root + &self.root)
+        }
+    }
+
+    /// Appends every `Rope` in `ropes` to the end of this `Rope`, returning
+    /// a new `Rope`.
+    ///
+    /// Rather than [`append()`]ing each `Rope` in turn, which produces a tree
+    /// as deep as the number of appended ropes, this builds a single balanced
+    /// subtree from all of them and concatenates it onto this `Rope` once.
+    ///
+    /// [`append()`]: struct.Rope.html#method.append
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the number of appended ropes
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("abc");
+    /// let appended = rope.append_all(vec![ Rope::from("def")
+    ///                                    , Rope::from("ghi") ]);
+    /// assert_eq!(&appended, "abcdefghi");
+    /// assert_eq!(&rope, "abc");
+    /// ```
+    pub fn append_all<I>(&self, ropes: I) -> Rope
+    where I: IntoIterator<Item=Rope> {
+        let nodes = ropes.into_iter()
+                         .filter(|rope| !rope.is_empty())
+                         .map(|rope| rope.root)
+                         .collect::<Vec<_>>();
+        self.append(&Rope::from(Node::balanced_from(&nodes)))
+    }
+
+    /// Appends the contents of each of `slices` to the end of this `Rope`,
+    /// in order.
+    ///
+    /// The slices' text is not copied wherever possible: every node of the
+    /// sliced `Rope`s that a slice covers entirely is shared with this
+    /// `Rope`, and only the leaves at the edges of each slice are copied.
+    /// This makes it cheap to reassemble a `Rope` from rearranged regions of
+    /// other `Rope`s (or of an earlier version of this one).
+    ///
+    /// # Time Complexity
+    /// O(_k_ log _n_) for _k_ slices
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let source = Rope::from("one\ntwo\nthree\n");
+    /// let mut rope = Rope::from("lines: ");
+    /// rope.extend_from_slices(&[ source.slice_bytes(8..14)
+    ///                          , source.slice_bytes(0..4) ]);
+    /// assert_eq!(&rope, "lines: three\none\n");
+    /// ```
+    pub fn extend_from_slices(&mut self, slices: &[RopeSlice]) {
+        let mut nodes = Vec::with_capacity(slices.len() + 1);
+        if !self.is_empty() { nodes.push(self.root.clone()) }
+        nodes.extend(slices.iter()
+                           .filter(|slice| !slice.is_empty())
+                           .map(RopeSlice::subrope));
+        self.root = Node::balanced_from(&nodes);
+    }
+
+    /// Joins `pieces` together into a single `Rope`, with a copy of `sep`
+    /// between each piece.
+    ///
+    /// Rather than repeatedly [`append()`]ing, which produces a tree as deep
+    /// as the number of pieces, this builds a balanced tree in a single pass.
+    /// Since `Rope`s share structure, neither `sep` nor any of the pieces is
+    /// copied.
+    ///
+    /// [`append()`]: struct.Rope.html#method.append
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the number of pieces
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let lines = [Rope::from("one"), Rope::from("two"), Rope::from("three")];
+    /// let joined = Rope::join_with_rope(&Rope::from("\n"), &lines);
+    /// assert_eq!(&joined, "one\ntwo\nthree");
+    /// ```
+    pub fn join_with_rope(sep: &Rope, pieces: &[Rope]) -> Rope {
+        let mut nodes = Vec::with_capacity(pieces.len() * 2);
+        for (i, piece) in pieces.iter().enumerate() {
+            if i > 0 && !sep.is_empty() { nodes.push(sep.root.clone()); }
+            if !piece.is_empty() { nodes.push(piece.root.clone()); }
         }
+        Rope::from(Node::balanced_from(&nodes))
     }
 
 
@@ -718,187 +2050,2507 @@ impl Rope {
         (Rope::from(l), Rope::from(r))
     }
 
-    /// Rebalances this entire `Rope`, returning a balanced `Rope`.
-    #[inline]
-    #[cfg(any(test, feature = "rebalance"))]
-    fn rebalance(&mut self) {
-        if self.is_balanced() {
-            // the rope is already balanced, do nothing
+    /// Returns `true` if the byte at `index` is the first byte of a UTF-8
+    /// code point sequence, or the end of the `Rope`.
+    ///
+    /// The start and end of the `Rope` are considered to be boundaries.
+    /// Returns `false` if `index` is greater than `self.len()`.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎");
+    /// assert!(rope.is_char_boundary(0));
+    /// assert!(rope.is_char_boundary(6));
+    /// // second byte of `ö`
+    /// assert!(!rope.is_char_boundary(2));
+    /// // third byte of `老`
+    /// assert!(!rope.is_char_boundary(8));
+    /// assert!(rope.is_char_boundary(rope.len()));
+    /// assert!(!rope.is_char_boundary(rope.len() + 1));
+    /// ```
+    pub fn is_char_boundary(&self, index: usize) -> bool {
+        if index == 0 || index == self.len() {
+            true
         } else {
-            // rebalance the rope
-            // self.root = self.root.rebalance();
+            self.root.leaf_at(index)
+                .map(|(s, i)| s.is_char_boundary(i))
+                .unwrap_or(false)
         }
     }
 
-    /// Returns true if this `Rope` is balanced.
+    /// Returns the leaf string containing the byte at `index`, and the offset
+    /// of that byte within the leaf, or `None` if `index` is out of bounds.
     ///
-    /// Balancing invariant:
-    /// the rope length needs to be less than _F_(rope_length) where F is fibonacci
+    /// This is a low-level accessor for performing work local to a single
+    /// leaf, such as running a regular expression over a window of text,
+    /// without copying the text out of the `Rope`. How a `Rope`'s text is
+    /// divided into leaves is an implementation detail, and shouldn't be
+    /// relied upon.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("abc") + Rope::from("Löwe");
+    /// assert_eq!(rope.byte_slice_at(1), Some(("abc", 1)));
+    /// assert_eq!(rope.byte_slice_at(5), Some(("Löwe", 2)));
+    /// assert_eq!(rope.byte_slice_at(rope.len()), None);
+    /// ```
     #[inline]
-    #[cfg(any(test, feature = "rebalance"))]
-    fn is_balanced(&self) -> bool {
-        self.root.is_balanced()
+    pub fn byte_slice_at(&self, index: usize) -> Option<(&str, usize)> {
+        self.root.leaf_at(index)
     }
 
-    unstable_iters! {
-        #[doc="Returns an iterator over all the strings in this `Rope`"]
-        #[inline]
-        pub fn strings<'a>(&'a self) -> impl Iterator<Item=&'a str> + 'a {
-            self.root.strings()
+    /// Returns the byte at byte index `index`, or `None` if `index` is out of
+    /// bounds.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("abc") + Rope::from("Löwe");
+    /// assert_eq!(rope.byte_at(1), Some(b'b'));
+    /// assert_eq!(rope.byte_at(4), Some(0xC3));
+    /// assert_eq!(rope.byte_at(rope.len()), None);
+    /// ```
+    #[inline]
+    pub fn byte_at(&self, index: usize) -> Option<u8> {
+        self.root.leaf_at(index).map(|(s, i)| s.as_bytes()[i])
+    }
+
+    /// Returns the byte at byte index `index`, without checking that `index`
+    /// is in bounds.
+    ///
+    /// This is the unchecked counterpart to [`byte_at()`], for hot loops
+    /// which have already validated their indices.
+    ///
+    /// [`byte_at()`]: struct.Rope.html#method.byte_at
+    ///
+    /// # Safety
+    /// `index` must be less than the length of this `Rope`. Calling this
+    /// method with an out of bounds index is undefined behaviour.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("abc") + Rope::from("def");
+    /// assert_eq!(unsafe { rope.byte_at_unchecked(4) }, b'e');
+    /// ```
+    #[inline]
+    pub unsafe fn byte_at_unchecked(&self, index: usize) -> u8 {
+        debug_assert!( index < self.len()
+                     , "Rope::byte_at_unchecked: byte index {} is out of \
+                        bounds (length {})"
+                     , index, self.len());
+        match self.root.leaf_at(index) {
+            Some((s, i)) => *s.as_bytes().get_unchecked(i)
+          , None => ::std::hint::unreachable_unchecked()
         }
+    }
 
-        #[doc="Returns an iterator over all the lines of text in this `Rope`."]
-        pub fn lines<'a>(&'a self) -> impl Iterator<Item=RopeSlice<'a>> +'a  {
-            {   // create a new block here so the macro will bind the `use` stmt
-                use internals::IsLineEnding;
-                let last_idx = self.len().saturating_sub(1);
-                Box::new(self.char_indices()
-                             .filter_map(move |(i, c)|
-                                if c.is_line_ending() { Some(i) }
-                                // special case: slice to the end of the rope
-                                // even if it doesn't end in a newline character
-                                else if i == last_idx { Some(i + 1) }
-                                else { None })
-                              .scan(0, move |mut l, i|  {
-                                    let last = *l;
-                                    *l = i + 1;
-                                    Some(self.slice(last..i))
-                                }))
+    /// Returns the `char` beginning at byte index `index`, or `None` if
+    /// `index` is out of bounds or does not fall on a `char` boundary.
+    ///
+    /// Unlike [`nth_char()`], `index` is a byte offset, not a count of
+    /// `char`s.
+    ///
+    /// [`nth_char()`]: struct.Rope.html#method.nth_char
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎");
+    /// assert_eq!(rope.char_at(1), Some('ö'));
+    /// assert_eq!(rope.char_at(2), None);
+    /// assert_eq!(rope.char_at(9), Some('虎'));
+    /// assert_eq!(rope.char_at(rope.len()), None);
+    /// ```
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        self.root.leaf_at(index)
+            .and_then(|(s, i)| if s.is_char_boundary(i) { s[i..].chars().next() }
+                               else { None })
+    }
+
+    /// Returns the `char` beginning at byte index `index`, without checking
+    /// that `index` is in bounds or on a `char` boundary.
+    ///
+    /// This is the unchecked counterpart to [`char_at()`], for hot loops
+    /// which have already validated their indices.
+    ///
+    /// [`char_at()`]: struct.Rope.html#method.char_at
+    ///
+    /// # Safety
+    /// `index` must be less than the length of this `Rope`, and must fall on
+    /// a `char` boundary. Calling this method with any other index is
+    /// undefined behaviour.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎");
+    /// assert_eq!(unsafe { rope.char_at_unchecked(6) }, '老');
+    /// ```
+    #[inline]
+    pub unsafe fn char_at_unchecked(&self, index: usize) -> char {
+        debug_assert!( self.char_at(index).is_some()
+                     , "Rope::char_at_unchecked: byte index {} is out of \
+                        bounds or not a char boundary"
+                     , index);
+        let c = self.root.leaf_at(index)
+                    .and_then(|(s, i)| s.get_unchecked(i..).chars().next());
+        match c {
+            Some(c) => c
+          , None => ::std::hint::unreachable_unchecked()
+        }
+    }
+
+    /// Returns the `n`th `char` in this `Rope`, or `None` if this `Rope`
+    /// contains `n` or fewer `char`s.
+    ///
+    /// This is equivalent to `rope.chars().nth(n)`, but rather than iterating
+    /// over every preceding `char`, it walks the tree using the cached
+    /// [`Char`] measure of each node.
+    ///
+    /// [`Char`]: metric/struct.Char.html
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎");
+    /// assert_eq!(rope.nth_char(1), Some('ö'));
+    /// assert_eq!(rope.nth_char(6), Some('虎'));
+    /// assert_eq!(rope.nth_char(7), None);
+    /// ```
+    pub fn nth_char(&self, n: usize) -> Option<char> {
+        if Char(n) >= self.measure() { return None }
+        self.root.to_byte_index(Char(n))
+            .and_then(|i| self.root.leaf_at(i))
+            .and_then(|(s, i)| s[i..].chars().next())
+    }
+
+    /// Binary searches this `Rope`, which is assumed to hold `char`s sorted
+    /// in the order given by `f`, for a `char` for which `f` returns
+    /// `Ordering::Equal`.
+    ///
+    /// This mirrors [`slice::binary_search_by()`]: `f` should return whether
+    /// the `char` it is passed is less than, equal to, or greater than the
+    /// target. If a matching `char` is found, its index is returned in
+    /// `Ok`; if there are several, any one of them may be returned. If
+    /// none is found, the index at which the target could be inserted while
+    /// keeping the `Rope` sorted is returned in `Err`. Both indices count
+    /// `char`s, not bytes, and each probe finds its `char` using
+    /// [`nth_char()`].
+    ///
+    /// [`slice::binary_search_by()`]: https://doc.rust-lang.org/std/primitive.slice.html#method.binary_search_by
+    /// [`nth_char()`]: struct.Rope.html#method.nth_char
+    ///
+    /// # Time Complexity
+    /// O(log² _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("acegiköü");
+    /// assert_eq!(rope.binary_search_by(|c| c.cmp(&'g')), Ok(3));
+    /// assert_eq!(rope.binary_search_by(|c| c.cmp(&'h')), Err(4));
+    /// assert_eq!(rope.binary_search_by(|c| c.cmp(&'ü')), Ok(7));
+    /// assert_eq!(rope.binary_search_by(|c| c.cmp(&'老')), Err(8));
+    /// ```
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where F: FnMut(char) -> cmp::Ordering {
+        let (mut low, mut high) = (0, self.count_chars());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let c = self.nth_char(mid)
+                .expect("Rope::binary_search_by: char index is in bounds, \
+                         but has no char. something is broken.");
+            match f(c) {
+                cmp::Ordering::Less => low = mid + 1
+              , cmp::Ordering::Greater => high = mid
+              , cmp::Ordering::Equal => return Ok(mid)
             }
         }
+        Err(low)
     }
-    //
-    //
-    // /// Returns a move iterator over all the strings in this `Rope`
-    // ///
-    // /// Consumes `self`.
-    // #[cfg(feature = "unstable")]
-    // #[inline]
-    // pub fn into_strings<'a>(self) -> impl Iterator<Item=String> + 'a {
-    //     self.root.into_strings()
-    // }
-    //
-    // /// Returns a move iterator over all the strings in this `Rope`
-    // ///
-    // /// Consumes `self`.
-    // #[cfg(not(feature = "unstable"))]
-    // #[inline]
-    // pub fn into_strings<'a>(self) -> Box<Iterator<Item=String> + 'a> {
-    //     self.root.into_strings()
-    // }
 
+    /// Returns the number of bytes needed to encode the `n`th `char` in this
+    /// `Rope` as UTF-8, or `None` if `n` is out of bounds.
+    ///
+    /// This is useful when advancing a byte offset one `char` at a time.
+    /// The `char` is located using [`nth_char()`].
+    ///
+    /// [`nth_char()`]: struct.Rope.html#method.nth_char
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎 🦁");
+    /// assert_eq!(rope.byte_len_utf8_of_char_at(0), Some(1));
+    /// assert_eq!(rope.byte_len_utf8_of_char_at(1), Some(2));
+    /// assert_eq!(rope.byte_len_utf8_of_char_at(5), Some(3));
+    /// assert_eq!(rope.byte_len_utf8_of_char_at(8), Some(4));
+    /// assert_eq!(rope.byte_len_utf8_of_char_at(9), None);
+    /// ```
+    #[inline]
+    pub fn byte_len_utf8_of_char_at(&self, n: usize) -> Option<usize> {
+        self.nth_char(n).map(char::len_utf8)
+    }
+
+    /// Returns the first `char` in this `Rope`, or `None` if it is empty.
+    ///
+    /// Rather than iterating over the `Rope`'s characters, this walks down
+    /// to the leftmost (non-empty) leaf of the tree.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// assert_eq!(Rope::from("Löwe 老虎").first_char(), Some('L'));
+    /// assert_eq!(Rope::new().first_char(), None);
+    /// ```
+    pub fn first_char(&self) -> Option<char> {
+        self.root.strings()
+            .find(|s| !s.is_empty())
+            .and_then(|s| s.chars().next())
+    }
+
+    /// Returns the last `char` in this `Rope`, or `None` if it is empty.
+    ///
+    /// Rather than iterating over the `Rope`'s characters, this walks down
+    /// to the rightmost (non-empty) leaf of the tree.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// assert_eq!(Rope::from("Löwe 老虎").last_char(), Some('虎'));
+    /// assert_eq!(Rope::new().last_char(), None);
+    /// ```
+    pub fn last_char(&self) -> Option<char> {
+        self.root.strings_rev()
+            .find(|s| !s.is_empty())
+            .and_then(|s| s.chars().next_back())
+    }
+
+    /// Shortens this `Rope` to at most `len` bytes, returning a new `Rope`.
+    ///
+    /// Unlike slicing a `str`, this will never panic: if `len` falls in the
+    /// middle of a multi-byte character, the cut is snapped _down_ to the
+    /// nearest preceding `char` boundary, so that character is removed
+    /// entirely. If `len` is greater than or equal to the `Rope`'s length,
+    /// the returned `Rope` is equal to this one.
+    ///
+    /// If you would rather be told about an index which isn't on a `char`
+    /// boundary, use [`checked_truncate_bytes()`].
+    ///
+    /// [`checked_truncate_bytes()`]: struct.Rope.html#method.checked_truncate_bytes
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("hello world");
+    /// assert_eq!(&an_rope.truncate_bytes(5), "hello");
+    /// assert_eq!(&an_rope.truncate_bytes(42), "hello world");
+    /// assert_eq!(&an_rope, "hello world");
+    /// ```
+    ///
+    /// Truncating in the middle of a character removes that character:
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("ab💖");
+    /// assert_eq!(&an_rope.truncate_bytes(4), "ab");
+    /// ```
+    pub fn truncate_bytes(&self, len: usize) -> Rope {
+        if len >= self.len() {
+            self.clone()
+        } else {
+            // snap down to the nearest char boundary. a UTF-8 code point is
+            // at most four bytes long, so this loops at most three times.
+            let mut len = len;
+            while !self.is_char_boundary(len) { len -= 1; }
+            self.split(len).0
+        }
+    }
+
+    /// Shortens this `Rope` to at most `len` bytes, returning a new `Rope`,
+    /// or an error if `len` does not fall on a `char` boundary.
+    ///
+    /// If `len` is greater than or equal to the `Rope`'s length, the returned
+    /// `Rope` is equal to this one.
+    ///
+    /// # Errors
+    /// Returns a [`CharBoundaryError`] if `len` is less than the length of
+    /// this `Rope` but does not lie on a `char` boundary.
+    ///
+    /// [`CharBoundaryError`]: error/struct.CharBoundaryError.html
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("ab💖");
+    /// assert_eq!(&an_rope.checked_truncate_bytes(2).unwrap(), "ab");
+    /// assert!(an_rope.checked_truncate_bytes(3).is_err());
+    /// ```
+    pub fn checked_truncate_bytes(&self, len: usize)
+                                  -> Result<Rope, CharBoundaryError> {
+        if len >= self.len() {
+            Ok(self.clone())
+        } else if self.is_char_boundary(len) {
+            Ok(self.split(len).0)
+        } else {
+            Err(CharBoundaryError { index: len })
+        }
+    }
+
+    /// Shortens this `Rope` to its first `n` `char`s, returning a new `Rope`.
+    ///
+    /// If this `Rope` contains `n` or fewer `char`s, the returned `Rope` is
+    /// equal to this one.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let an_rope = Rope::from("Löwe 老虎");
+    /// assert_eq!(&an_rope.truncate_chars(6), "Löwe 老");
+    /// assert_eq!(&an_rope.truncate_chars(42), "Löwe 老虎");
+    /// ```
+    pub fn truncate_chars(&self, n: usize) -> Rope {
+        let len = self.chars().take(n).map(char::len_utf8).sum();
+        self.truncate_bytes(len)
+    }
+
+    /// Rebalances this entire `Rope`, returning a balanced `Rope`.
+    #[inline]
+    #[cfg(any(test, feature = "rebalance"))]
+    fn rebalance(&mut self) {
+        if self.is_balanced() {
+            // the rope is already balanced, do nothing
+        } else {
+            // rebalance the rope
+            self.root = self.root.rebuild_coalescing(0);
+        }
+    }
+
+    /// Returns true if this `Rope` is balanced.
+    ///
+    /// Balancing invariant:
+    /// the rope length needs to be less than _F_(rope_length) where F is fibonacci
+    #[inline]
+    #[cfg(any(test, feature = "rebalance"))]
+    fn is_balanced(&self) -> bool {
+        self.root.is_balanced()
+    }
+
+    /// Returns true if `self` and `other` contain the same bytes and have
+    /// equal byte and line measurements, regardless of the shapes of their
+    /// trees.
+    ///
+    /// This is used to check that `Rope`s built through different
+    /// construction paths (a single `from()`, many `append()`s, and so on)
+    /// are indistinguishable.
+    //  N.B. that graphemes are deliberately not compared: a grapheme cluster
+    //  split across two leaves is currently counted once per leaf, so the
+    //  `Grapheme` measure _does_ depend on the tree's shape.
+    #[cfg(test)]
+    pub(crate) fn equal_content_ignoring_structure(&self, other: &Rope)
+                                                   -> bool {
+        self == other
+            && Measured::<usize>::measure(self)
+                == Measured::<usize>::measure(other)
+            && Measured::<Line>::measure(self)
+                == Measured::<Line>::measure(other)
+    }
+
+    /// Returns `true` if the cached length of this `Rope` is equal to the sum
+    /// of the lengths of its leaves.
+    ///
+    /// Every method which builds a new tree must leave the cached length
+    /// metadata consistent with the text the tree actually contains; this
+    /// checks that invariant.
+    #[cfg(test)]
+    pub(crate) fn len_is_consistent(&self) -> bool {
+        self.len() == self.strings().map(str::len).sum::<usize>()
+    }
+
+    unstable_iters! {
+        #[doc="Returns an iterator over all the strings in this `Rope`"]
+        #[inline]
+        pub fn strings<'a>(&'a self) -> impl Iterator<Item=&'a str> + 'a {
+            self.root.strings()
+        }
+
+        #[doc="Returns an iterator over all the lines of text in this `Rope`."]
+        pub fn lines<'a>(&'a self) -> impl Iterator<Item=RopeSlice<'a>> +'a  {
+            {   // create a new block here so the macro will bind the `use` stmt
+                use internals::IsLineEnding;
+                let len = self.len();
+                Box::new(self.char_indices()
+                             .filter_map(move |(i, c)|
+                                if c.is_line_ending() { Some(i) }
+                                // special case: slice to the end of the rope
+                                // even if it doesn't end in a newline character
+                                else if i + c.len_utf8() == len { Some(len) }
+                                else { None })
+                              .scan(0, move |mut l, i|  {
+                                    let last = *l;
+                                    *l = i + 1;
+                                    Some(self.slice(last..i))
+                                }))
+            }
+        }
+    }
+    //
+    //
+    // /// Returns a move iterator over all the strings in this `Rope`
+    // ///
+    // /// Consumes `self`.
+    // #[cfg(feature = "unstable")]
+    // #[inline]
+    // pub fn into_strings<'a>(self) -> impl Iterator<Item=String> + 'a {
+    //     self.root.into_strings()
+    // }
+    //
+    // /// Returns a move iterator over all the strings in this `Rope`
+    // ///
+    // /// Consumes `self`.
+    // #[cfg(not(feature = "unstable"))]
+    // #[inline]
+    // pub fn into_strings<'a>(self) -> Box<Iterator<Item=String> + 'a> {
+    //     self.root.into_strings()
+    // }
+
+    /// Consumes this `Rope`, returning the contents of each of its leaves
+    /// as an owned `String`.
+    ///
+    /// Concatenating the returned `String`s gives the text of the `Rope`,
+    /// and each one is the text of a single leaf, so the `Rope`'s chunking
+    /// can be inspected or persisted. Empty leaves are skipped, so the
+    /// returned vector is empty only if the `Rope` is. [`from_leaves()`]
+    /// rebuilds a `Rope` with the same chunking.
+    ///
+    /// Leaves may be shared with other `Rope`s, so their text is copied.
+    ///
+    /// [`from_leaves()`]: struct.Rope.html#method.from_leaves
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe ") + Rope::from("老虎");
+    /// assert_eq!(rope.into_leaf_strings(), vec!["Löwe ", "老虎"]);
+    /// ```
+    pub fn into_leaf_strings(self) -> Vec<String> {
+        self.strings()
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+
+    str_iters! {
+        #[doc="Returns an iterator over all the bytes in this `Rope`.\n\
+               \nAs a Rope consists of a sequence of bytes, we can iterate \
+               through a rope by byte. This method returns such an iterator."]
+        #[inline]
+        impl bytes<u8> for Rope {}
+        #[doc="Returns an iterator over all the characters in this `Rope`.\n\
+               \nAs a `Rope` consists of valid UTF-8, we can iterate through a \
+               `Rope` by `char`. This method returns such an iterator. \n\
+               \nIt's important to remember that `char` represents a Unicode \
+               Scalar Value, and may not match your idea of what a \
+               'character' is. Iteration over grapheme clusters may be what \
+               you actually want."]
+        #[inline]
+        impl chars<char> for Rope {}
+        #[doc="Returns an iterator over the `char`s of this `Rope` and their \
+               byte offsets.\n\
+               \nLike `str::char_indices()`, the offsets are byte offsets \
+               from the start of the `Rope`, not counts of the preceding \
+               `char`s."]
+        #[inline]
+        impl char_indices<(usize, char)> for Rope {}
+        #[inline]
+        impl split_whitespace<&'a str> for Rope {}
+        // #[inline]
+        // impl lines<&'a str> for Rope {}
+    }
+
+    unicode_seg_iters! {
+        #[doc=
+            "Returns an iterator over the [grapheme clusters][graphemes] of \
+             `self`.\n\
+
+             [graphemes]: \
+             http://www.unicode.org/reports/tr29/#Grapheme_Cluster_Boundaries\
+             \n\
+             The iterator is over the  *extended grapheme clusters*; as \
+             [UAX#29]\
+             (http://www.unicode.org/reports/tr29/#Grapheme_Cluster_Boundaries)\
+             recommends extended grapheme cluster boundaries for general \
+             processing."]
+        #[inline]
+        impl graphemes<&'a str> for Rope {}
+        #[doc=
+            "Returns an iterator over the words of `self`, separated on \
+            [UAX#29 word boundaries]\
+            (http://www.unicode.org/reports/tr29/#Word_Boundaries).\n\n\
+
+            Here, \"words\" are just those substrings which, after splitting on\
+            UAX#29 word boundaries, contain any alphanumeric characters. That \
+            is, the substring must contain at least one character with the \
+            [Alphabetic](http://unicode.org/reports/tr44/#Alphabetic) \
+            property, or with [General_Category=Number]\
+            (http://unicode.org/reports/tr44/#General_Category_Values)."]
+        #[inline]
+        impl unicode_words<&'a str> for Rope {}
+        #[doc=
+            "Returns an iterator over substrings of `self` separated on \
+            [UAX#29 word boundaries]\
+            (http://www.unicode.org/reports/tr29/#Word_Boundaries). \n\n\
+            The concatenation of the substrings returned by this function is \
+            just the original string."]
+        #[inline]
+        impl split_word_bounds<&'a str> for Rope {}
+        // #[inline]
+        // impl grapheme_indices<(usize, &'a str)> for Rope {}
+        // #[inline]
+        // impl split_word_bound_indices<(usize, &'a str)> for Rope {}
+    }
+
+    /// Returns an iterator over the grapheme clusters of `self` and their
+    /// byte offsets. See `graphemes()` for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲\r\n");
+    /// let gr_inds = rope.grapheme_indices()
+    ///                   .collect::<Vec<(usize, &str)>>();
+    /// let b: &[_] = &[(0, "a̐"), (3, "é"), (6, "ö̲"), (11, "\r\n")];
+    ///
+    /// assert_eq!(&gr_inds[..], b);
+    /// ```
+    #[inline]
+    pub fn grapheme_indices(&self) -> internals::GraphemeIndices {
+        self.root.grapheme_indices()
+    }
+
+    /// Returns an iterator over the bytes of this `Rope` in reverse order,
+    /// starting from the last byte.
+    ///
+    /// This walks the leaves from the right, so backward scans (such as a
+    /// reverse substring search) need not collect the `Rope`'s bytes first.
+    /// The iterator is double-ended: calling `next_back()` yields bytes
+    /// from the start of the `Rope`, in forward order.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("abc\ndef");
+    /// let rev = rope.bytes_rev().collect::<Vec<u8>>();
+    /// assert_eq!(&rev[..], b"fed\ncba");
+    ///
+    /// let fwd = rope.bytes_rev().rev().collect::<Vec<u8>>();
+    /// assert_eq!(&fwd[..], b"abc\ndef");
+    /// ```
+    #[inline]
+    pub fn bytes_rev(&self) -> internals::BytesRev<'_> {
+        self.root.bytes_rev()
+    }
+
+    /// Returns an iterator over the `char`s of this `Rope` and their byte
+    /// offsets, starting from the last `char` and ending with the first.
+    ///
+    /// This yields the same pairs as [`char_indices()`], in reverse order,
+    /// and is useful when scanning backwards for a `char` whose offset is
+    /// needed. Like `str::char_indices()`, the offsets are byte offsets from
+    /// the start of the `Rope`.
+    ///
+    /// [`char_indices()`]: struct.Rope.html#method.char_indices
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("aé\n") + Rope::from("老");
+    /// let rev = rope.char_indices_rev().collect::<Vec<_>>();
+    /// assert_eq!(rev, vec![(4, '老'), (3, '\n'), (1, 'é'), (0, 'a')]);
+    /// ```
+    #[inline]
+    pub fn char_indices_rev(&self) -> internals::CharIndicesRev<'_> {
+        self.root.char_indices_rev()
+    }
+
+    /// Returns a [`RopeCursor`] at the start of this `Rope`.
+    ///
+    /// The cursor can seek to a byte offset, move forwards and backwards by
+    /// `char` or by grapheme cluster, and report its line and column. It is
+    /// also an iterator over `(byte_offset, char)` pairs from its current
+    /// position. See the [`cursor`] module for details.
+    ///
+    /// [`RopeCursor`]: cursor/struct.RopeCursor.html
+    /// [`cursor`]: cursor/index.html
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲");
+    /// let mut cursor = rope.iter();
+    /// assert_eq!(cursor.next_grapheme(), Some(0..3));
+    /// assert_eq!(cursor.next(), Some((3, 'é')));
+    /// assert_eq!(cursor.prev_grapheme(), Some(3..5));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> RopeCursor<'_> {
+        RopeCursor::new(self)
+    }
+
+    /// Wraps `iter`, an iterator over this `Rope`, so that it yields each
+    /// item together with its byte offset from the start of the `Rope`.
+    ///
+    /// `iter` may be any of the `Rope`'s iterators over `char`s or `&str`s,
+    /// such as [`chars()`], [`graphemes()`], or [`unicode_words()`]. The
+    /// offsets are found by threading a running offset through the `Rope`'s
+    /// leaves, so no separate `*_indices()` iterator is needed. See the
+    /// [`offsets`] module for details.
+    ///
+    /// [`chars()`]: struct.Rope.html#method.chars
+    /// [`graphemes()`]: struct.Rope.html#method.graphemes
+    /// [`unicode_words()`]: struct.Rope.html#method.unicode_words
+    /// [`offsets`]: offsets/index.html
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲\r\n");
+    /// let graphemes = rope.with_byte_offsets(rope.graphemes())
+    ///                     .collect::<Vec<_>>();
+    /// assert_eq!(graphemes, vec![(0, "a̐"), (3, "é"), (5, "ö̲"), (9, "\r\n")]);
+    /// ```
+    #[inline]
+    pub fn with_byte_offsets<'a, I>(&'a self, iter: I)
+                                   -> WithByteOffsets<'a, I::IntoIter>
+    where I: IntoIterator
+        , I::Item: Segment {
+        WithByteOffsets::new(iter.into_iter(), self.root.strings())
+    }
+
+    /// Returns an iterator over the whitespace-separated tokens of this
+    /// `Rope`, together with the byte range of each one.
+    ///
+    /// This yields the same tokens as `str::split_whitespace()` would on the
+    /// text of the `Rope`, with their ranges found using
+    /// [`with_byte_offsets()`]. A token which continues from one leaf into
+    /// the next is yielded as a single slice spanning both.
+    ///
+    /// [`with_byte_offsets()`]: struct.Rope.html#method.with_byte_offsets
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("  let x\t= 老虎;\n");
+    /// let tokens = rope.split_whitespace_ranges()
+    ///                  .map(|(range, token)| (range, token.to_string()))
+    ///                  .collect::<Vec<_>>();
+    /// assert_eq!(tokens, vec![ (2..5, "let".to_string())
+    ///                        , (6..7, "x".to_string())
+    ///                        , (8..9, "=".to_string())
+    ///                        , (10..17, "老虎;".to_string()) ]);
+    /// ```
+    pub fn split_whitespace_ranges<'a>(&'a self)
+        -> impl Iterator<Item=(ops::Range<usize>, RopeSlice<'a>)> + 'a {
+        let mut pieces = self.with_byte_offsets(self.split_whitespace())
+                             .map(|(i, s)| i..i + s.len())
+                             .peekable();
+        iter::from_fn(move || {
+            let mut range = pieces.next()?;
+            // the leaves split tokens which span them into several pieces;
+            // pieces with no whitespace between them are one token
+            while let Some(next) = pieces.peek().cloned() {
+                if next.start != range.end { break }
+                range.end = next.end;
+                pieces.next();
+            }
+            Some((range.clone(), self.slice_bytes(range)))
+        })
+    }
+
+    /// Returns the byte offsets of every grapheme cluster boundary in this
+    /// `Rope`, including the final offset equal to `len()`.
+    ///
+    /// The offsets are collected in a single pass over the `Rope`, and are
+    /// suitable for building a table to look up graphemes by index without
+    /// walking the whole `Rope`. Grapheme clusters which span two leaves are
+    /// treated as one cluster. An empty `Rope` has a single boundary, at
+    /// offset 0.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲\r\n");
+    /// assert_eq!(rope.collect_grapheme_offsets(), vec![0, 3, 5, 9, 11]);
+    /// assert_eq!(Rope::new().collect_grapheme_offsets(), vec![0]);
+    /// ```
+    pub fn collect_grapheme_offsets(&self) -> Vec<usize> {
+        let mut offsets = self.root.grapheme_ranges(0)
+                              .map(|range| range.start)
+                              .collect::<Vec<_>>();
+        offsets.push(self.len());
+        offsets
+    }
+
+    /// Returns a table mapping each `char` offset in this `Rope` to the
+    /// corresponding byte offset.
+    ///
+    /// Index `i` of the returned `Vec` holds the byte offset of the `i`th
+    /// `char`, and a final entry holding the `Rope`'s length in bytes is
+    /// appended, so the table has one more entry than the `Rope` has `char`s.
+    /// This is useful when many `char` offsets must be converted at once, as
+    /// the whole table is computed in a single pass over the `Rope`.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎");
+    /// assert_eq!(rope.char_to_byte_table(), vec![0, 1, 3, 4, 5, 6, 9, 12]);
+    /// assert_eq!(Rope::new().char_to_byte_table(), vec![0]);
+    /// ```
+    pub fn char_to_byte_table(&self) -> Vec<usize> {
+        let Char(n) = self.measure();
+        let mut table = Vec::with_capacity(n + 1);
+        table.extend(self.char_indices().map(|(i, _)| i));
+        table.push(self.len());
+        table
+    }
+
+    /// Returns an iterator over substrings of `self`, split on UAX#29 word
+    /// boundaries, and their offsets. See `split_word_bounds()` for more
+    /// information.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use an_rope::Rope;
+    /// let rope = Rope::from("Brr, it's 29.3°F!");
+    /// let swi1 = rope.split_word_bound_indices()
+    ///                .collect::<Vec<(usize, &str)>>();
+    /// let b: &[_] = &[ (0, "Brr"), (3, ","), (4, " "), (5, "it's")
+    ///                , (9, " "), (10, "29.3"),  (14, "°"), (16, "F")
+    ///                , (17, "!")];
+    ///
+    /// assert_eq!(&swi1[..], b);
+    /// ```
+    #[inline]
+    pub fn split_word_bound_indices(&self) -> internals::UWordBoundIndices {
+        self.root.split_word_bound_indices()
+    }
+
+    /// Returns an iterator over the bytes of this `Rope`, starting at the
+    /// beginning of line `line` and continuing to the end of the `Rope`.
+    ///
+    /// Lines are numbered from 0. Line _n_ begins immediately after the
+    /// _n_th line ending. Since a `Line` index refers to the end of that
+    /// line, this yields the same bytes as the right half of
+    /// [`split()`]`(Line(n - 1))`.
+    ///
+    /// [`split()`]: struct.Rope.html#method.split
+    ///
+    /// # Panics
+    /// If this `Rope` has fewer than `line` line endings.
+    ///
+    /// # Time Complexity
+    /// O(log _n_) to find the start of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("first\nsecond\nthird");
+    /// let tail = rope.bytes_from_line(Line(1)).collect::<Vec<u8>>();
+    /// assert_eq!(&tail[..], b"second\nthird");
+    /// ```
+    pub fn bytes_from_line<'a>(&'a self, line: Line)
+                               -> impl Iterator<Item=u8> + 'a {
+        let start = self.root.line_start(line)
+            .unwrap_or_else(|| panic!( "Rope::bytes_from_line: {:?} out of \
+                                        bounds", line));
+        self.root.strings_from(start).flat_map(str::bytes)
+    }
+
+    /// Returns an iterator over the `char`s of this `Rope`, starting at the
+    /// beginning of line `line` and continuing to the end of the `Rope`.
+    ///
+    /// This is the `char` counterpart to [`bytes_from_line()`], and is
+    /// useful for rendering from a scroll position. The start of the line
+    /// is found using the [`Line`] metric, and iteration resumes from the
+    /// leaf containing it, without visiting the leaves before it.
+    ///
+    /// [`bytes_from_line()`]: struct.Rope.html#method.bytes_from_line
+    /// [`Line`]: metric/struct.Line.html
+    ///
+    /// # Panics
+    /// If this `Rope` has fewer than `line` line endings.
+    ///
+    /// # Time Complexity
+    /// O(log _n_) to find the start of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("Löwe\n老虎\nLéopard");
+    /// let tail = rope.char_iter_from_line(Line(1)).collect::<String>();
+    /// assert_eq!(tail, "老虎\nLéopard");
+    /// ```
+    pub fn char_iter_from_line<'a>(&'a self, line: Line)
+                                   -> impl Iterator<Item=char> + 'a {
+        let start = self.root.line_start(line)
+            .unwrap_or_else(|| panic!( "Rope::char_iter_from_line: {:?} out \
+                                        of bounds", line));
+        self.root.strings_from(start).flat_map(str::chars)
+    }
+
+    /// Converts the byte index `index` into a `(line, column)` position in
+    /// this `Rope`.
+    ///
+    /// Lines are numbered from 0, and the column is the number of `char`s
+    /// between the start of the line and `index`.
+    ///
+    /// # Panics
+    /// * If `index` is greater than the length of this `Rope`
+    /// * If `index` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_) to find the line, plus O(_m_) in the length of the line
+    /// preceding `index` to count the column.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("first\nsecönd\nthird");
+    /// assert_eq!(rope.byte_index_to_position(0), (Line(0), 0));
+    /// assert_eq!(rope.byte_index_to_position(6), (Line(1), 0));
+    /// assert_eq!(rope.byte_index_to_position(11), (Line(1), 4));
+    /// assert_eq!(rope.byte_index_to_position(rope.len()), (Line(2), 5));
+    /// ```
+    pub fn byte_index_to_position(&self, index: usize) -> (Line, usize) {
+        assert!( index <= self.len()
+               , "Rope::byte_index_to_position: byte index {} is out of \
+                  bounds (length {})"
+               , index, self.len());
+        assert!( self.is_char_boundary(index)
+               , "Rope::byte_index_to_position: byte index {} is not a char \
+                  boundary"
+               , index);
+        let line = self.root.line_of(index);
+        let start = self.root.line_start(line)
+            .expect("Rope::byte_index_to_position: line containing index \
+                     has no start. something is broken.");
+        (line, self.slice_bytes(start..index).chars().count())
+    }
+
+    /// Returns the grapheme column of the byte index `index`: the number of
+    /// grapheme clusters between the start of the line containing `index`
+    /// and `index`.
+    ///
+    /// Unlike the `char` column returned by [`byte_index_to_position()`],
+    /// a base character followed by combining marks counts as a single
+    /// column. If `index` falls inside a grapheme cluster, the column of that
+    /// cluster is returned.
+    ///
+    /// [`byte_index_to_position()`]: struct.Rope.html#method.byte_index_to_position
+    ///
+    /// # Panics
+    /// * If `index` is greater than the length of this `Rope`
+    /// * If `index` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_) to find the line, plus O(_m_) in the length of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\na̐éö̲ line");
+    /// assert_eq!(rope.byte_to_grapheme_column(3), 3);
+    /// assert_eq!(rope.byte_to_grapheme_column(6), 0);
+    /// // "a̐" and "é" are 5 bytes, but only two grapheme clusters
+    /// assert_eq!(rope.byte_to_grapheme_column(11), 2);
+    /// assert_eq!(rope.byte_to_grapheme_column(rope.len()), 8);
+    /// ```
+    pub fn byte_to_grapheme_column(&self, index: usize) -> usize {
+        assert!( index <= self.len()
+               , "Rope::byte_to_grapheme_column: byte index {} is out of \
+                  bounds (length {})"
+               , index, self.len());
+        assert!( self.is_char_boundary(index)
+               , "Rope::byte_to_grapheme_column: byte index {} is not a char \
+                  boundary"
+               , index);
+        let line = self.root.line_of(index);
+        let start = self.root.line_start(line)
+            .expect("Rope::byte_to_grapheme_column: line containing index \
+                     has no start. something is broken.");
+        self.root.grapheme_ranges(start)
+            .take_while(|g| g.end <= index)
+            .count()
+    }
+
+    /// Returns the number of `char`s between the start of the line containing
+    /// byte index `index` and `index`.
+    ///
+    /// This is the column of `index` for editors which count columns in
+    /// `char`s. See [`byte_to_grapheme_column()`] for a version which counts
+    /// grapheme clusters instead.
+    ///
+    /// [`byte_to_grapheme_column()`]: struct.Rope.html#method.byte_to_grapheme_column
+    ///
+    /// # Panics
+    /// * If `index` is greater than the length of this `Rope`
+    /// * If `index` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_) to find the line, plus O(_m_) in the length of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\nLöwe 老虎");
+    /// assert_eq!(rope.byte_to_char_in_line(3), 3);
+    /// assert_eq!(rope.byte_to_char_in_line(6), 0);
+    /// // "Löwe " is 6 bytes, but only 5 `char`s
+    /// assert_eq!(rope.byte_to_char_in_line(12), 5);
+    /// assert_eq!(rope.byte_to_char_in_line(rope.len()), 7);
+    /// ```
+    pub fn byte_to_char_in_line(&self, index: usize) -> usize {
+        assert!( index <= self.len()
+               , "Rope::byte_to_char_in_line: byte index {} is out of bounds \
+                  (length {})"
+               , index, self.len());
+        assert!( self.is_char_boundary(index)
+               , "Rope::byte_to_char_in_line: byte index {} is not a char \
+                  boundary"
+               , index);
+        let start = self.find_line_start(index);
+        self.slice_bytes(start..index).chars().count()
+    }
+
+    /// Returns the number of grapheme clusters in line `line` of this `Rope`,
+    /// not counting its line ending.
+    ///
+    /// This is the length of the line as displayed by an editor, in which a
+    /// base character followed by combining marks occupies a single column.
+    /// Lines are numbered from 0.
+    ///
+    /// # Panics
+    /// If this `Rope` has fewer than `line` line endings.
+    ///
+    /// # Time Complexity
+    /// O(log _n_) to find the line, plus O(_m_) in the length of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("first\na̐éö̲\r\n");
+    /// assert_eq!(rope.grapheme_count_in_line(Line(0)), 5);
+    /// assert_eq!(rope.grapheme_count_in_line(Line(1)), 3);
+    /// assert_eq!(rope.grapheme_count_in_line(Line(2)), 0);
+    /// ```
+    pub fn grapheme_count_in_line(&self, line: Line) -> usize {
+        let start = self.root.line_start(line)
+            .unwrap_or_else(|| panic!( "Rope::grapheme_count_in_line: {:?} \
+                                        out of bounds", line));
+        let end = start + self.bytes_in_line(line, false);
+        self.root.grapheme_ranges(start)
+            .take_while(|g| g.end <= end)
+            .count()
+    }
+
+    /// Returns an iterator over the lines of this `Rope`, together with the
+    /// line ending which terminates each one.
+    ///
+    /// Each line is yielded as a slice not including its line ending, along
+    /// with the [`LineEnding`] which followed it, or `None` for a final line
+    /// which isn't terminated. `"\n"`, `"\r\n"`, and a lone `"\r"` are all
+    /// recognised, so a `Rope` with mixed line endings can be reassembled
+    /// exactly. As with [`lines()`], a line ending at the very end of the
+    /// `Rope` isn't followed by an empty line.
+    ///
+    /// Note that everywhere else in this crate, such as [`lines()`] and the
+    /// `Line` metric, only `"\n"` ends a line, and a lone `"\r"` is part of
+    /// the line's text. This iterator also splits lines at a lone `"\r"`, so
+    /// that classic Mac OS line endings can be found and converted.
+    ///
+    /// [`LineEnding`]: enum.LineEnding.html
+    /// [`lines()`]: struct.Rope.html#method.lines
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::{Rope, LineEnding};
+    /// let rope = Rope::from("unix\nwindows\r\nmac\rnone");
+    /// let lines = rope.iter_lines_with_endings()
+    ///                 .map(|(line, ending)| (line.to_string(), ending))
+    ///                 .collect::<Vec<_>>();
+    /// assert_eq!(lines, vec![ ("unix".to_string(), Some(LineEnding::Lf))
+    ///                       , ("windows".to_string(), Some(LineEnding::CrLf))
+    ///                       , ("mac".to_string(), Some(LineEnding::Cr))
+    ///                       , ("none".to_string(), None) ]);
+    /// ```
+    pub fn iter_lines_with_endings<'a>(&'a self)
+        -> impl Iterator<Item=(RopeSlice<'a>, Option<LineEnding>)> + 'a {
+        self.lines_ending_at(self.line_endings())
+    }
+
+    /// Returns an iterator over the lines of this `Rope` separated by the
+    /// line endings `endings`, together with the line ending which
+    /// terminates each one.
+    fn lines_ending_at<'a, I>(&'a self, mut endings: I)
+        -> impl Iterator<Item=(RopeSlice<'a>, Option<LineEnding>)> + 'a
+    where I: Iterator<Item=(usize, LineEnding)> + 'a {
+        let len = self.len();
+        let mut start = 0;
+        iter::from_fn(move || {
+            if let Some((i, ending)) = endings.next() {
+                let line = self.slice(start..i);
+                start = i + ending.as_str().len();
+                Some((line, Some(ending)))
+            } else if start < len {
+                // the last line, if it isn't terminated
+                let line = self.slice(start..len);
+                start = len;
+                Some((line, None))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns an iterator over the byte index of each line ending in this
+    /// `Rope`, and which kind of line ending it is.
+    ///
+    /// A `\r\n` pair is a single line ending, even if the `\r` and `\n` are
+    /// in different leaves.
+    fn line_endings<'a>(&'a self)
+                        -> impl Iterator<Item=(usize, LineEnding)> + 'a {
+        let mut bytes = self.bytes().enumerate().peekable();
+        iter::from_fn(move || {
+            while let Some((i, b)) = bytes.next() {
+                let ending = match b {
+                    b'\n' => LineEnding::Lf
+                  , b'\r' if bytes.peek().map(|&(_, b)| b) == Some(b'\n') => {
+                        bytes.next();
+                        LineEnding::CrLf
+                    }
+                  , b'\r' => LineEnding::Cr
+                  , _ => continue
+                };
+                return Some((i, ending))
+            }
+            None
+        })
+    }
+
+    /// Returns an iterator over the lines of this `Rope` which intersect the
+    /// byte range `range`, clipped to that range.
+    ///
+    /// Each line is yielded as a slice including its line ending, except
+    /// that the first line starts at the start of `range`, and the last line
+    /// stops at its end. The slices therefore cover exactly the text in
+    /// `range`. This is useful for rendering only the visible part of a
+    /// buffer.
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the length of this `Rope`
+    /// * If either end of `range` does not fall on a `char` boundary
+    ///
+    /// # Time Complexity
+    /// O(log _n_) per line yielded.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\nsecond\nthird\nfourth");
+    /// let lines = rope.byte_slice_lines(2..15)
+    ///                 .map(|line| line.to_string())
+    ///                 .collect::<Vec<_>>();
+    /// assert_eq!(lines, vec!["rst\n", "second\n", "th"]);
+    /// ```
+    pub fn byte_slice_lines<'a>(&'a self, range: ops::Range<usize>)
+                                -> impl Iterator<Item=RopeSlice<'a>> + 'a {
+        assert!( range.start <= range.end
+               , "Rope::byte_slice_lines: start index {} > end index {}"
+               , range.start, range.end);
+        assert!( range.end <= self.len()
+               , "Rope::byte_slice_lines: byte index {} is out of bounds \
+                  (length {})"
+               , range.end, self.len());
+        assert!( self.is_char_boundary(range.start)
+               , "Rope::byte_slice_lines: byte index {} is not a char boundary"
+               , range.start);
+        assert!( self.is_char_boundary(range.end)
+               , "Rope::byte_slice_lines: byte index {} is not a char boundary"
+               , range.end);
+        let ops::Range { mut start, end } = range;
+        iter::from_fn(move || {
+            if start >= end { return None }
+            let line = self.root.line_of(start);
+            let next = self.root.line_start(line + 1)
+                           .map_or(end, |next| cmp::min(next, end));
+            let slice = RopeSlice::new(&self.root, start..next);
+            start = next;
+            Some(slice)
+        })
+    }
+
+    /// Returns the byte index of the start of the line containing byte index
+    /// `index`.
+    ///
+    /// A line starts at the start of the `Rope`, or immediately after a
+    /// `'\n'`. A line ending is considered part of the line it ends.
+    ///
+    /// # Panics
+    /// If `index` is greater than the length of this `Rope`.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\nsecond\r\nthird");
+    /// assert_eq!(rope.find_line_start(3), 0);
+    /// assert_eq!(rope.find_line_start(5), 0);
+    /// assert_eq!(rope.find_line_start(6), 6);
+    /// assert_eq!(rope.find_line_start(15), 14);
+    /// ```
+    pub fn find_line_start(&self, index: usize) -> usize {
+        assert!( index <= self.len()
+               , "Rope::find_line_start: byte index {} is out of bounds \
+                  (length {})"
+               , index, self.len());
+        let line = self.root.line_of(index);
+        self.root.line_start(line)
+            .expect("Rope::find_line_start: line containing index has no \
+                     start. something is broken.")
+    }
+
+    /// Returns `true` if the byte index `index` is at the start of a line.
+    ///
+    /// That is, if `index` is 0, or immediately follows a `'\n'`. The start
+    /// of the line containing `index` is found as in [`find_line_start()`].
+    ///
+    /// [`find_line_start()`]: struct.Rope.html#method.find_line_start
+    ///
+    /// # Panics
+    /// If `index` is greater than the length of this `Rope`.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\nsecond\r\nthird");
+    /// assert!(rope.is_line_boundary(0));
+    /// assert!(!rope.is_line_boundary(5));
+    /// assert!(rope.is_line_boundary(6));
+    /// assert!(!rope.is_line_boundary(13));
+    /// assert!(rope.is_line_boundary(14));
+    /// ```
+    pub fn is_line_boundary(&self, index: usize) -> bool {
+        assert!( index <= self.len()
+               , "Rope::is_line_boundary: byte index {} is out of bounds \
+                  (length {})"
+               , index, self.len());
+        let line = self.root.line_of(index);
+        self.root.line_start(line) == Some(index)
+    }
+
+    /// Returns the byte index of the end of the line containing byte index
+    /// `index`.
+    ///
+    /// This is the index of the line's `"\n"` or `"\r\n"` line ending, or the
+    /// length of the `Rope` if the line is the last one and has no line
+    /// ending. Together with [`find_line_start()`], this gives the range of
+    /// the text of the current line, such as for a "select line" command.
+    ///
+    /// [`find_line_start()`]: struct.Rope.html#method.find_line_start
+    ///
+    /// # Panics
+    /// If `index` is greater than the length of this `Rope`.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\nsecond\r\nthird");
+    /// assert_eq!(rope.find_line_end(0), 5);
+    /// assert_eq!(rope.find_line_end(5), 5);
+    /// assert_eq!(rope.find_line_end(6), 12);
+    /// assert_eq!(rope.find_line_end(15), 19);
+    /// ```
+    pub fn find_line_end(&self, index: usize) -> usize {
+        assert!( index <= self.len()
+               , "Rope::find_line_end: byte index {} is out of bounds \
+                  (length {})"
+               , index, self.len());
+        let line = self.root.line_of(index);
+        match self.root.line_start(line + 1) {
+            // the next line starts just after this line's `\n`
+            Some(next) if next >= 2 && self.byte_at(next - 2) == Some(b'\r') =>
+                next - 2
+          , Some(next) => next - 1
+          , None => self.len()
+        }
+    }
+
+    /// Returns the length in bytes of line `line` of this `Rope`.
+    ///
+    /// If `include_ending` is `true`, the line's `"\n"` or `"\r\n"` line
+    /// ending is counted; otherwise only the text of the line is. Lines are
+    /// numbered from 0. This is useful for computing horizontal scroll
+    /// extents without copying the line out of the `Rope`.
+    ///
+    /// # Panics
+    /// If this `Rope` has fewer than `line` line endings.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("first\n老虎\r\nlast");
+    /// assert_eq!(rope.bytes_in_line(Line(0), false), 5);
+    /// assert_eq!(rope.bytes_in_line(Line(0), true), 6);
+    /// assert_eq!(rope.bytes_in_line(Line(1), false), 6);
+    /// assert_eq!(rope.bytes_in_line(Line(1), true), 8);
+    /// assert_eq!(rope.bytes_in_line(Line(2), true), 4);
+    /// ```
+    pub fn bytes_in_line(&self, line: Line, include_ending: bool) -> usize {
+        let start = self.root.line_start(line)
+            .unwrap_or_else(|| panic!( "Rope::bytes_in_line: {:?} out of \
+                                        bounds", line));
+        let end = self.root.line_start(line + 1).unwrap_or_else(|| self.len());
+        if include_ending || end == start || self.byte_at(end - 1) != Some(b'\n')
+        {
+            return end - start
+        }
+        if end - start >= 2 && self.byte_at(end - 2) == Some(b'\r') {
+            end - start - 2
+        } else {
+            end - start - 1
+        }
+    }
+
+    /// Returns the grapheme cluster at grapheme column `col` of line `line`
+    /// of this `Rope`, or `None` if the line has fewer than `col + 1`
+    /// graphemes, or the `Rope` has fewer than `line` line endings.
+    ///
+    /// Lines and columns are numbered from 0, and the line ending is not
+    /// counted as part of the line. This is the text an editor would
+    /// highlight under a caret placed at that position.
+    ///
+    /// # Time Complexity
+    /// O(log _n_ + _m_), where _m_ is the length of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("first\na̐老ö̲\r\nlast");
+    /// assert_eq!(&rope.graphemes_at_line_col(Line(1), 0).unwrap(), "a̐");
+    /// assert_eq!(&rope.graphemes_at_line_col(Line(1), 1).unwrap(), "老");
+    /// assert_eq!(&rope.graphemes_at_line_col(Line(1), 2).unwrap(), "ö̲");
+    /// assert!(rope.graphemes_at_line_col(Line(1), 3).is_none());
+    /// assert!(rope.graphemes_at_line_col(Line(3), 0).is_none());
+    /// ```
+    pub fn graphemes_at_line_col(&self, line: Line, col: usize)
+                                 -> Option<RopeSlice<'_>> {
+        let start = self.root.line_start(line)?;
+        let end = start + self.bytes_in_line(line, false);
+        self.root.grapheme_ranges(start)
+            .take_while(|g| g.end <= end)
+            .nth(col)
+            .map(|g| self.slice_bytes(g))
+    }
+
+    /// Returns the first line of this `Rope`, without its line ending.
+    ///
+    /// The end of the line is found using the [`Line`] metric, so only the
+    /// first line is examined. If this `Rope` has no line endings, the whole
+    /// `Rope` is returned.
+    ///
+    /// [`Line`]: metric/struct.Line.html
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// assert_eq!(&Rope::from("first\r\nsecond\n").first_line(), "first");
+    /// assert_eq!(&Rope::from("only").first_line(), "only");
+    /// assert_eq!(&Rope::new().first_line(), "");
+    /// ```
+    pub fn first_line(&self) -> RopeSlice<'_> {
+        self.line_without_ending(Line(0))
+    }
+
+    /// Returns the last line of this `Rope`, without its line ending.
+    ///
+    /// As with [`lines()`], a line ending at the very end of this `Rope`
+    /// isn't followed by an empty line, so the last line is the one it
+    /// terminates. The start of the line is found using the [`Line`] metric,
+    /// rather than by iterating over every line.
+    ///
+    /// [`lines()`]: struct.Rope.html#method.lines
+    /// [`Line`]: metric/struct.Line.html
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// assert_eq!(&Rope::from("first\nsecond").last_line(), "second");
+    /// assert_eq!(&Rope::from("first\nsecond\n").last_line(), "second");
+    /// assert_eq!(&Rope::from("first\n\n").last_line(), "");
+    /// assert_eq!(&Rope::new().last_line(), "");
+    /// ```
+    pub fn last_line(&self) -> RopeSlice<'_> {
+        let lines: Line = self.measure();
+        let last = if lines > Line(0) && self.ends_with('\n') {
+            lines - Line(1)
+        } else {
+            lines
+        };
+        self.line_without_ending(last)
+    }
+
+    /// Returns line `line` of this `Rope`, without its line ending.
+    fn line_without_ending(&self, line: Line) -> RopeSlice<'_> {
+        let start = self.root.line_start(line)
+            .expect("Rope: line has no start. something is broken.");
+        let end = start + self.bytes_in_line(line, false);
+        self.slice_bytes(start..end)
+    }
+
+    /// Returns the number of line endings within the byte range `range` of
+    /// this `Rope`.
+    ///
+    /// This walks the tree using the cached [`Line`] measure of each node,
+    /// so only the leaves containing the ends of `range` are scanned.
+    ///
+    /// [`Line`]: metric/struct.Line.html
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the length of this `Rope`
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("one\ntwo\nthree\nfour");
+    /// assert_eq!(rope.count_lines_in_range(0..rope.len()), 3);
+    /// assert_eq!(rope.count_lines_in_range(2..9), 2);
+    /// assert_eq!(rope.count_lines_in_range(4..7), 0);
+    /// ```
+    pub fn count_lines_in_range(&self, range: ops::Range<usize>) -> usize {
+        assert!( range.start <= range.end
+               , "Rope::count_lines_in_range: start index {} > end index {}"
+               , range.start, range.end);
+        assert!( range.end <= self.len()
+               , "Rope::count_lines_in_range: byte index {} is out of bounds \
+                  (length {})"
+               , range.end, self.len());
+        (self.root.line_of(range.end) - self.root.line_of(range.start)).0
+    }
+
+    /// Returns the number of extended grapheme clusters which begin within
+    /// the byte range `range` of this `Rope`.
+    ///
+    /// The text is segmented across leaf boundaries, so a cluster which is
+    /// split between two leaves is only counted once. If `range` starts in
+    /// the middle of a cluster, that cluster isn't counted.
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the length of this `Rope`
+    ///
+    /// # Time Complexity
+    /// O(log _n_). The number of grapheme clusters in each subtree is cached,
+    /// so only the leaves at either end of `range`, and the clusters at the
+    /// seams between subtrees, are segmented.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲\r\n") + Rope::from("Löwe");
+    /// assert_eq!(rope.count_graphemes_in_range(0..rope.len()), 8);
+    /// // "a̐" and "é" are 5 bytes, but 2 grapheme clusters
+    /// assert_eq!(rope.count_graphemes_in_range(0..5), 2);
+    /// assert_eq!(rope.count_graphemes_in_range(5..12), 3);
+    /// ```
+    pub fn count_graphemes_in_range(&self, range: ops::Range<usize>)
+                                    -> usize {
+        assert!( range.start <= range.end
+               , "Rope::count_graphemes_in_range: start index {} > end index \
+                  {}"
+               , range.start, range.end);
+        assert!( range.end <= self.len()
+               , "Rope::count_graphemes_in_range: byte index {} is out of \
+                  bounds (length {})"
+               , range.end, self.len());
+        self.root.grapheme_clusters_before(range.end)
+            - self.root.grapheme_clusters_before(range.start)
+    }
+
+    /// Returns `true` if the `char`s in this `Rope` are equal to the `char`s
+    /// yielded by `other`.
+    ///
+    /// The comparison stops at the first `char` which differs, and the two
+    /// sequences must be of the same length to be equal.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe ") + Rope::from("老虎");
+    /// assert!(rope.chars_eq("Löwe 老虎".chars()));
+    /// assert!(!rope.chars_eq("Löwe".chars()));
+    /// assert!(!rope.chars_eq("Löwe 老虎!".chars()));
+    /// ```
+    #[inline]
+    pub fn chars_eq<I>(&self, other: I) -> bool
+    where I: Iterator<Item=char> {
+        self.chars().eq(other)
+    }
+
+    /// Returns `true` if this `Rope` and `other` consist of the same
+    /// sequence of extended grapheme clusters.
+    ///
+    /// Grapheme segmentation is determined entirely by the text being
+    /// segmented, so two `Rope`s have the same grapheme clusters exactly
+    /// when they have the same bytes. This compares their lengths, and then
+    /// their bytes, without segmenting or copying either of them. It is
+    /// provided so that text-processing code which reasons in terms of
+    /// grapheme clusters can say so explicitly.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲");
+    /// assert!(rope.graphemes_eq(&(Rope::from("a") + Rope::from("̐éö̲"))));
+    /// assert!(!rope.graphemes_eq(&Rope::from("a̐éö")));
+    /// ```
+    #[inline]
+    pub fn graphemes_eq(&self, other: &Rope) -> bool {
+        self.len() == other.len() && self.bytes_eq(other.bytes())
+    }
+
+    /// Returns `true` if this `Rope` and `other` are equal, ignoring case.
+    ///
+    /// Both `Rope`s are compared using Unicode simple case folding: each
+    /// `char` is folded to a single `char`, so the different forms of a
+    /// letter, such as the Greek sigma's `'Σ'`, `'σ'` and `'ς'`, compare
+    /// equal. The `char`s of both `Rope`s are folded as they are compared,
+    /// so neither is copied into a `String`.
+    ///
+    /// Since each `char` folds to exactly one `char`, case distinctions which
+    /// need full case folding are not ignored: `'ß'` doesn't equal `"ss"`,
+    /// and `'İ'` (U+0130) doesn't equal `"i\u{307}"`.
+    ///
+    /// This method is only available when the `case` feature is enabled.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "case")] {
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("ΣΊΣΥΦΟΣ");
+    /// assert!(rope.bytes_eq_ignore_case_unicode(&Rope::from("σίσυφος")));
+    /// assert!(!rope.bytes_eq_ignore_case_unicode(&Rope::from("σίσυφο")));
+    /// # }
+    /// ```
+    #[cfg(feature = "case")]
+    pub fn bytes_eq_ignore_case_unicode(&self, other: &Rope) -> bool {
+        use unicode::case_folding::simple_fold;
+        self.chars().map(simple_fold).eq(other.chars().map(simple_fold))
+    }
+
+    /// Returns true if the bytes in `self` equal the bytes in `other`
+    #[inline]
+    fn bytes_eq<I>(&self, other: I) -> bool
+    where I: Iterator<Item=u8> {
+        self.bytes().zip(other).all(|(a, b)| a == b)
+    }
+
+    /// Returns `true` if the given pattern matches a prefix of this `Rope`.
+    ///
+    /// The pattern may be a `&str`, a `char`, or a closure that determines
+    /// whether a `char` matches. See the [`pattern`] module for details.
+    ///
+    /// [`pattern`]: pattern/index.html
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("bananas");
+    /// assert!(rope.starts_with("bana"));
+    /// assert!(!rope.starts_with("nana"));
+    /// assert!(rope.starts_with('b'));
+    /// assert!(rope.starts_with(|c: char| c.is_lowercase()));
+    /// ```
+    #[inline]
+    pub fn starts_with<P: RopePattern>(&self, mut pat: P) -> bool {
+        pat.is_prefix_of(self)
+    }
+
+    /// Returns `true` if the given pattern matches a suffix of this `Rope`.
+    ///
+    /// The pattern may be a `&str`, a `char`, or a closure that determines
+    /// whether a `char` matches. See the [`pattern`] module for details.
+    ///
+    /// [`pattern`]: pattern/index.html
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("bananas");
+    /// assert!(rope.ends_with("anas"));
+    /// assert!(!rope.ends_with("nan"));
+    /// assert!(rope.ends_with('s'));
+    /// assert!(rope.ends_with(char::is_alphabetic));
+    /// ```
+    #[inline]
+    pub fn ends_with<P: RopePattern>(&self, mut pat: P) -> bool {
+        pat.is_suffix_of(self)
+    }
+
+    /// Returns a slice of this `Rope` with all leading occurrences of `pat`
+    /// removed.
+    ///
+    /// This mirrors [`str::trim_start_matches()`], but only accepts a `&str`
+    /// pattern. An empty pattern matches nothing, so the whole `Rope` is
+    /// returned.
+    ///
+    /// [`str::trim_start_matches()`]: https://doc.rust-lang.org/std/primitive.str.html#method.trim_start_matches
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the length of the trimmed prefix
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("\n\n\nsome text");
+    /// assert_eq!(&rope.trim_start_matches("\n"), "some text");
+    /// let rope = Rope::from("foofoofoo123");
+    /// assert_eq!(&rope.trim_start_matches("foo"), "123");
+    /// assert_eq!(&rope.trim_start_matches("fo"), "ofoofoo123");
+    /// ```
+    pub fn trim_start_matches(&self, pat: &str) -> RopeSlice<'_> {
+        let mut start = 0;
+        if !pat.is_empty() {
+            let mut bytes = self.bytes();
+            'trim: while self.len() - start >= pat.len() {
+                for b in pat.bytes() {
+                    if bytes.next() != Some(b) { break 'trim; }
+                }
+                start += pat.len();
+            }
+        }
+        self.slice_bytes(start..self.len())
+    }
+
+    /// Returns a slice of this `Rope` with all leading and trailing `char`s
+    /// which are in `chars` removed.
+    ///
+    /// This mirrors calling [`str::trim_matches()`] with a slice of `char`s.
+    ///
+    /// [`str::trim_matches()`]: https://doc.rust-lang.org/std/primitive.str.html#method.trim_matches
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the length of the trimmed prefix and suffix, times the
+    /// length of `chars`
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("-_-some-text_-_");
+    /// assert_eq!(&rope.trim_matches_char_set(&['-', '_']), "some-text");
+    /// assert_eq!(&rope.trim_matches_char_set(&[]), "-_-some-text_-_");
+    /// ```
+    pub fn trim_matches_char_set(&self, chars: &[char]) -> RopeSlice<'_> {
+        let start = self.char_indices()
+                        .find(|&(_, c)| !chars.contains(&c))
+                        .map_or(self.len(), |(i, _)| i);
+        let end = self.char_indices_rev()
+                      .take_while(|&(i, _)| i >= start)
+                      .find(|&(_, c)| !chars.contains(&c))
+                      .map_or(start, |(i, c)| i + c.len_utf8());
+        self.slice_bytes(start..end)
+    }
+
+    /// Returns a slice of this `Rope` with all trailing occurrences of `pat`
+    /// removed.
+    ///
+    /// This mirrors [`str::trim_end_matches()`], but only accepts a `&str`
+    /// pattern. An empty pattern matches nothing, so the whole `Rope` is
+    /// returned.
+    ///
+    /// [`str::trim_end_matches()`]: https://doc.rust-lang.org/std/primitive.str.html#method.trim_end_matches
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the length of the trimmed suffix
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("some text\n\n\n");
+    /// assert_eq!(&rope.trim_end_matches("\n"), "some text");
+    /// let rope = Rope::from("123foofoofoo");
+    /// assert_eq!(&rope.trim_end_matches("foo"), "123");
+    /// assert_eq!(&rope.trim_end_matches("oo"), "123foofoof");
+    /// ```
+    pub fn trim_end_matches(&self, pat: &str) -> RopeSlice<'_> {
+        let mut end = self.len();
+        if !pat.is_empty() {
+            let mut rev_bytes = self.root.strings_rev()
+                                    .flat_map(|s| s.bytes().rev());
+            'trim: while end >= pat.len() {
+                for b in pat.bytes().rev() {
+                    if rev_bytes.next() != Some(b) { break 'trim; }
+                }
+                end -= pat.len();
+            }
+        }
+        self.slice_bytes(0..end)
+    }
+
+    /// Splits this `Rope` on the last occurrence of `pat`, returning the
+    /// slices before and after the match, or `None` if `pat` does not occur
+    /// in this `Rope`.
+    ///
+    /// This mirrors `str::rsplit_once()`. The search begins at the end of
+    /// the `Rope`, so this is useful for splitting off a trailing field, such
+    /// as a file extension.
+    ///
+    /// # Time Complexity
+    /// O(_m_) in the length of the text after the match (times the length of
+    /// `pat`, for string patterns).
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("archive.tar.gz");
+    /// let (stem, ext) = rope.rsplit_once('.').unwrap();
+    /// assert_eq!(&stem, "archive.tar");
+    /// assert_eq!(&ext, "gz");
+    /// assert!(rope.rsplit_once("zip").is_none());
+    /// ```
+    pub fn rsplit_once<P>(&self, mut pat: P)
+                          -> Option<(RopeSlice<'_>, RopeSlice<'_>)>
+    where P: RopePattern {
+        pat.rmatch_ranges(self, 1).pop()
+           .map(|m| (self.slice_bytes(0..m.start)
+                    , self.slice_bytes(m.end..self.len())))
+    }
+
+    /// Returns an iterator over at most `n` slices of this `Rope`, separated
+    /// by `pat` and starting from the end of the `Rope`.
+    ///
+    /// This mirrors `str::rsplitn()`: the last slice returned contains the
+    /// remainder of the `Rope` before the `n - 1`th match from the end.
+    ///
+    /// # Time Complexity
+    /// O(_m_) in the length of the text after the `n - 1`th match from the
+    /// end (times the length of `pat`, for string patterns).
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a,b,c,d");
+    /// let fields = rope.rsplitn(3, ',')
+    ///                  .map(|s| s.to_string())
+    ///                  .collect::<Vec<_>>();
+    /// assert_eq!(fields, vec!["d", "c", "a,b"]);
+    /// ```
+    pub fn rsplitn<P>(&self, n: usize, mut pat: P)
+                      -> ::std::vec::IntoIter<RopeSlice<'_>>
+    where P: RopePattern {
+        let mut pieces = Vec::new();
+        if n == 0 { return pieces.into_iter() }
+        let mut end = self.len();
+        for m in pat.rmatch_ranges(self, n - 1) {
+            pieces.push(self.slice_bytes(m.end..end));
+            end = m.start;
+        }
+        pieces.push(self.slice_bytes(0..end));
+        pieces.into_iter()
+    }
+
+    /// Returns a slice of this `Rope` covering up to `before` bytes before
+    /// and `after` bytes after the byte index `index`.
+    ///
+    /// This is intended for displaying the context surrounding a position,
+    /// such as the location of an error. The window is clamped to the bounds
+    /// of the `Rope` (as is `index` itself), and each end of the window is
+    /// widened to the nearest `char` boundary, so that a `char` is never cut
+    /// in half.
+    ///
+    /// # Time Complexity
+    /// O(log _n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("let x = 老虎 + 1;");
+    /// assert_eq!(&rope.byte_windows_around(6, 2, 3), "x = 老");
+    /// // clamped to the start and end of the rope
+    /// assert_eq!(&rope.byte_windows_around(1, 10, 2), "let");
+    /// assert_eq!(&rope.byte_windows_around(20, 3, 10), " 1;");
+    /// ```
+    pub fn byte_windows_around(&self, index: usize, before: usize, after: usize)
+                               -> RopeSlice<'_> {
+        let len = self.len();
+        let index = cmp::min(index, len);
+        let mut start = index.saturating_sub(before);
+        let mut end = cmp::min(index.saturating_add(after), len);
+        while !self.is_char_boundary(start) { start -= 1; }
+        while !self.is_char_boundary(end) { end += 1; }
+        self.slice_bytes(start..end)
+    }
+
+    /// Returns the byte index of the first occurrence of `pat` which lies
+    /// entirely within the byte range `[start, end)` of this `Rope`, or
+    /// `None` if there is no such occurrence.
+    ///
+    /// Only the bytes in `[start, end)` are examined, so this is suitable for
+    /// searching a region such as the part of a document visible in a
+    /// viewport without scanning the whole `Rope`. The returned index is
+    /// relative to the start of the `Rope`, not to `start`. An empty pattern
+    /// matches at `start`.
+    ///
+    /// # Panics
+    /// * If `start` is greater than `end`
+    /// * If `end` is greater than the length of this `Rope`
+    ///
+    /// # Time Complexity
+    /// O(log _n_) to find `start`, plus O(_m_ × _k_) where _m_ is `end -
+    /// start` and _k_ is the length of `pat`.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("one fish, two fish, red fish");
+    /// assert_eq!(rope.byte_search_with_limit("fish", 0, rope.len()), Some(4));
+    /// assert_eq!(rope.byte_search_with_limit("fish", 5, rope.len()), Some(14));
+    /// // the second "fish" ends at byte 18, past the end of the range
+    /// assert_eq!(rope.byte_search_with_limit("fish", 5, 17), None);
+    /// ```
+    pub fn byte_search_with_limit(&self, pat: &str, start: usize, end: usize)
+                                  -> Option<usize> {
+        use std::collections::VecDeque;
+        assert!( start <= end
+               , "Rope::byte_search_with_limit: start index {} > end index {}"
+               , start, end);
+        assert!( end <= self.len()
+               , "Rope::byte_search_with_limit: byte index {} is out of \
+                  bounds (length {})"
+               , end, self.len());
+        let pat = pat.as_bytes();
+        if pat.is_empty() { return Some(start) }
+        if end - start < pat.len() { return None }
+
+        // slide a window the length of the pattern over the bytes in range,
+        // so that matches spanning leaf boundaries are found
+        let mut window = VecDeque::with_capacity(pat.len());
+        let bytes = self.root.strings_from(start)
+                        .flat_map(str::bytes)
+                        .take(end - start);
+        for (i, b) in bytes.enumerate() {
+            if window.len() == pat.len() { window.pop_front(); }
+            window.push_back(b);
+            if window.len() == pat.len() && window.iter().eq(pat.iter()) {
+                return Some(start + i + 1 - pat.len())
+            }
+        }
+        None
+    }
+
+    /// Returns the byte index of the first occurrence of `c` in this `Rope`,
+    /// or `None` if `c` does not occur in it.
+    ///
+    /// Each leaf is searched in turn, and the returned index is relative to
+    /// the start of the `Rope`. When the `memchr` feature is enabled, ASCII
+    /// characters are found using `memchr`, which is considerably faster than
+    /// checking each character in turn.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe\n") + Rope::from("老虎\n");
+    /// assert_eq!(rope.find_char('w'), Some(3));
+    /// assert_eq!(rope.find_char('虎'), Some(9));
+    /// assert_eq!(rope.find_char('\n'), Some(5));
+    /// assert_eq!(rope.find_char('x'), None);
+    /// ```
+    pub fn find_char(&self, c: char) -> Option<usize> {
+        let mut offset = 0;
+        for s in self.strings() {
+            let found = if c.is_ascii() { find_byte(s, c as u8) }
+                        else { s.find(c) };
+            if let Some(i) = found { return Some(offset + i) }
+            offset += s.len();
+        }
+        None
+    }
+
+    /// Returns an iterator over the byte index of every occurrence of `c` in
+    /// this `Rope`, in order.
+    ///
+    /// Each leaf is searched in turn, and the running byte offset of the
+    /// leaf is added to each match, so the indices are relative to the start
+    /// of the `Rope`.
+    ///
+    /// # Time Complexity
+    /// O(_n_) to exhaust the iterator.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a,b,") + Rope::from("老,虎");
+    /// let commas = rope.byte_indices_of_char(',').collect::<Vec<_>>();
+    /// assert_eq!(commas, vec![1, 3, 7]);
+    /// ```
+    pub fn byte_indices_of_char<'a>(&'a self, c: char)
+                                    -> impl Iterator<Item=usize> + 'a {
+        self.strings()
+            .scan(0, |offset, s| {
+                let start = *offset;
+                *offset += s.len();
+                Some((start, s))
+            })
+            .flat_map(move |(start, s)|
+                s.match_indices(c).map(move |(i, _)| start + i))
+    }
+
+    /// Returns a new `Rope` with every line ending converted to `to`.
+    ///
+    /// Any of `\n`, `\r\n`, or a lone `\r` is recognised as a line ending,
+    /// so a `Rope` containing a mix of styles is converted correctly: a
+    /// `\r\n` pair is always treated as a single line ending, even if the
+    /// `\r` and `\n` are in different leaves.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::{Rope, LineEnding};
+    /// let rope = Rope::from("one\r\ntwo\rthree\nfour");
+    /// assert_eq!( &rope.normalize_newlines(LineEnding::Lf)
+    ///           , "one\ntwo\nthree\nfour");
+    /// assert_eq!( &rope.normalize_newlines(LineEnding::CrLf)
+    ///           , "one\r\ntwo\r\nthree\r\nfour");
+    /// ```
+    pub fn normalize_newlines(&self, to: LineEnding) -> Rope {
+        let to = Rope::from(to.as_str());
+        self.iter_lines_with_endings()
+            .flat_map(|(line, ending)| {
+                let ending = ending.map(|_| to.clone());
+                iter::once(Rope::from(line.subrope())).chain(ending)
+            })
+            .collect()
+    }
+
+    /// Returns a new `Rope` with the lines of this `Rope` joined into one,
+    /// with `separator` in place of each line ending.
+    ///
+    /// The lines are split where [`lines()`] splits them, at each `\n`. A
+    /// `\r\n` pair is replaced by a single `separator`, even if the `\r` and
+    /// `\n` are in different leaves, but a lone `\r` doesn't end a line, and
+    /// is left as it is. As with [`lines()`], a line ending at the very end
+    /// of this `Rope` doesn't separate it from an empty line, so it is
+    /// removed rather than replaced. This is useful for unwrapping
+    /// hard-wrapped text.
+    ///
+    /// [`lines()`]: struct.Rope.html#method.lines
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("an rope for\r\nlarge text\ndocuments\n");
+    /// assert_eq!(&rope.join_lines(" "), "an rope for large text documents");
+    /// assert_eq!(&Rope::from("a\n\nb").join_lines(", "), "a, , b");
+    /// assert_eq!(&Rope::from("a\rb\nc").join_lines(" "), "a\rb c");
+    /// ```
+    pub fn join_lines(&self, separator: &str) -> Rope {
+        let separator = Rope::from(separator);
+        let endings = self.line_endings()
+                          .filter(|&(_, ending)| ending != LineEnding::Cr);
+        self.lines_ending_at(endings)
+            .enumerate()
+            .flat_map(|(i, (line, _))| {
+                // every line but the first is separated from the one before
+                let separator = if i > 0 { Some(separator.clone()) }
+                                else { None };
+                separator.into_iter()
+                         .chain(iter::once(Rope::from(line.subrope())))
+            })
+            .collect()
+    }
+
+    /// Returns a slice of this `Rope` containing at most its first `width`
+    /// grapheme clusters.
+    ///
+    /// This is intended for clipping long lines to the width of a viewport.
+    /// The slice always ends on a grapheme cluster boundary, so combining
+    /// sequences are never cut in half, even when they are split between
+    /// leaves. If this `Rope` is `width` grapheme clusters long or shorter,
+    /// the whole `Rope` is returned.
+    ///
+    /// # Time Complexity
+    /// O(_w_), where _w_ is the number of bytes in the returned slice.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲ tail");
+    /// assert_eq!(&rope.truncate_to_grapheme_width(2), "a̐é");
+    /// assert_eq!(&rope.truncate_to_grapheme_width(20), "a̐éö̲ tail");
+    /// ```
+    pub fn truncate_to_grapheme_width(&self, width: usize) -> RopeSlice<'_> {
+        let end = self.root.grapheme_ranges(0)
+                      .nth(width)
+                      .map_or(self.len(), |g| g.start);
+        self.slice(0..end)
+    }
+
+    /// Returns the width of this `Rope` in columns, when displayed in a
+    /// terminal.
+    ///
+    /// Unlike counting grapheme clusters, this accounts for East Asian wide
+    /// characters, which take up two columns, and combining marks and other
+    /// zero-width characters, which take up none. The width of each
+    /// grapheme cluster is found using the [`unicode-width`] crate, and the
+    /// widths of all of them are summed. A grapheme cluster which spans two
+    /// leaves is measured as a whole.
+    ///
+    /// This method is only available when the `width` feature is enabled.
+    ///
+    /// [`unicode-width`]: https://crates.io/crates/unicode-width
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "width")] {
+    /// use an_rope::Rope;
+    /// assert_eq!(Rope::from("a̐éö̲").total_grapheme_width(), 3);
+    /// assert_eq!(Rope::from("Löwe 老虎").total_grapheme_width(), 9);
+    /// # }
+    /// ```
+    #[cfg(feature = "width")]
+    pub fn total_grapheme_width(&self) -> usize {
+        use unicode_width::UnicodeWidthStr;
+        self.root.grapheme_ranges(0)
+            .map(|g| self.root.text_in(g).width())
+            .sum()
+    }
+
+    /// Returns the width in columns of line `line` of this `Rope`, when
+    /// displayed in a terminal.
+    ///
+    /// Each tab advances to the next multiple of `tab_width` columns, East
+    /// Asian wide characters take up two columns, and zero-width characters
+    /// take up none, as in [`total_grapheme_width()`]. The line ending is not
+    /// counted. Lines are numbered from 0.
+    ///
+    /// This method is only available when the `width` feature is enabled.
+    ///
+    /// [`total_grapheme_width()`]: struct.Rope.html#method.total_grapheme_width
+    ///
+    /// # Panics
+    /// If this `Rope` has fewer than `line` line endings.
+    ///
+    /// # Time Complexity
+    /// O(log _n_ + _m_), where _m_ is the length of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "width")] {
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("fn main() {\n\t老虎();\n}");
+    /// assert_eq!(rope.line_width_columns(Line(0), 4), 11);
+    /// assert_eq!(rope.line_width_columns(Line(1), 4), 11);
+    /// assert_eq!(rope.line_width_columns(Line(1), 8), 15);
+    /// # }
+    /// ```
+    #[cfg(feature = "width")]
+    pub fn line_width_columns(&self, line: Line, tab_width: usize) -> usize {
+        let line = self.line_range(line);
+        self.root.grapheme_ranges(line.start)
+            .take_while(|g| g.end <= line.end)
+            .fold(0, |column, g|
+                column + column_width(&self.root.text_in(g), column, tab_width))
+    }
+
+    /// Returns the byte index of the grapheme cluster displayed at column
+    /// `column` of line `line` of this `Rope`.
+    ///
+    /// This is the inverse of [`line_width_columns()`], and maps a position
+    /// on screen, such as a mouse click, to a position in the `Rope`. Columns
+    /// are measured in the same way, with tabs advancing to the next multiple
+    /// of `tab_width`. If `column` falls inside a tab or a wide character,
+    /// the index of that tab or character is returned. If `column` is past
+    /// the end of the line, the index of the end of the line (before its
+    /// line ending) is returned.
+    ///
+    /// This method is only available when the `width` feature is enabled.
+    ///
+    /// [`line_width_columns()`]: struct.Rope.html#method.line_width_columns
+    ///
+    /// # Panics
+    /// If this `Rope` has fewer than `line` line endings.
+    ///
+    /// # Time Complexity
+    /// O(log _n_ + _m_), where _m_ is the length of the line.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "width")] {
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("first\n\t老虎();\nlast");
+    /// assert_eq!(rope.byte_position_of_column(Line(1), 0, 4), 6);
+    /// assert_eq!(rope.byte_position_of_column(Line(1), 3, 4), 6);
+    /// assert_eq!(rope.byte_position_of_column(Line(1), 4, 4), 7);
+    /// assert_eq!(rope.byte_position_of_column(Line(1), 7, 4), 10);
+    /// assert_eq!(rope.byte_position_of_column(Line(1), 20, 4), 16);
+    /// # }
+    /// ```
+    #[cfg(feature = "width")]
+    pub fn byte_position_of_column( &self, line: Line, column: usize
+                                  , tab_width: usize) -> usize {
+        let line = self.line_range(line);
+        let mut current = 0;
+        for g in self.root.grapheme_ranges(line.start)
+                          .take_while(|g| g.end <= line.end) {
+            current += column_width( &self.root.text_in(g.clone()), current
+                                   , tab_width);
+            if current > column { return g.start }
+        }
+        line.end
+    }
+
+    /// Returns the byte range of line `line` of this `Rope`, without its
+    /// line ending.
+    #[cfg(feature = "width")]
+    fn line_range(&self, line: Line) -> ops::Range<usize> {
+        let start = self.root.line_start(line)
+            .unwrap_or_else(|| panic!( "Rope::line_range: {:?} out of bounds"
+                                     , line));
+        start..start + self.bytes_in_line(line, false)
+    }
+
+    /// Returns a new `Rope` padded at the start with copies of `fill` until
+    /// it is at least `width` grapheme clusters long.
+    ///
+    /// Width is measured in grapheme clusters rather than bytes or `char`s,
+    /// so that multi-byte text and combining sequences line up when the
+    /// result is used for column alignment. A `Rope` which is already at
+    /// least `width` grapheme clusters long is returned unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// assert_eq!(&Rope::from("42").pad_start(5, ' '), "   42");
+    /// assert_eq!(&Rope::from("老虎").pad_start(3, '*'), "*老虎");
+    /// assert_eq!(&Rope::from("toolong").pad_start(3, ' '), "toolong");
+    /// ```
+    pub fn pad_start(&self, width: usize, fill: char) -> Rope {
+        match self.padding(width, fill) {
+            Some(padding) => padding.append(self)
+          , None => self.clone()
+        }
+    }
+
+    /// Returns a new `Rope` padded at the end with copies of `fill` until it
+    /// is at least `width` grapheme clusters long.
+    ///
+    /// Width is measured in grapheme clusters rather than bytes or `char`s,
+    /// so that multi-byte text and combining sequences line up when the
+    /// result is used for column alignment. A `Rope` which is already at
+    /// least `width` grapheme clusters long is returned unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// assert_eq!(&Rope::from("name").pad_end(6, '.'), "name..");
+    /// assert_eq!(&Rope::from("a̐é").pad_end(3, '-'), "a̐é-");
+    /// assert_eq!(&Rope::from("toolong").pad_end(3, ' '), "toolong");
+    /// ```
+    pub fn pad_end(&self, width: usize, fill: char) -> Rope {
+        match self.padding(width, fill) {
+            Some(padding) => self.append(&padding)
+          , None => self.clone()
+        }
+    }
 
-    str_iters! {
-        #[doc="Returns an iterator over all the bytes in this `Rope`.\n\
-               \nAs a Rope consists of a sequence of bytes, we can iterate \
-               through a rope by byte. This method returns such an iterator."]
-        #[inline]
-        impl bytes<u8> for Rope {}
-        #[doc="Returns an iterator over all the characters in this `Rope`.\n\
-               \nAs a `Rope` consists of valid UTF-8, we can iterate through a \
-               `Rope` by `char`. This method returns such an iterator. \n\
-               \nIt's important to remember that `char` represents a Unicode \
-               Scalar Value, and may not match your idea of what a \
-               'character' is. Iteration over grapheme clusters may be what \
-               you actually want."]
-        #[inline]
-        impl chars<char> for Rope {}
-        #[inline]
-        impl char_indices<(usize, char)> for Rope {}
-        #[inline]
-        impl split_whitespace<&'a str> for Rope {}
-        // #[inline]
-        // impl lines<&'a str> for Rope {}
+    /// Returns the padding needed to make this `Rope` `width` grapheme
+    /// clusters wide, or `None` if it is already that wide.
+    fn padding(&self, width: usize, fill: char) -> Option<Rope> {
+        let len = self.root.grapheme_ranges(0).count();
+        if len >= width { return None }
+        let mut padding = String::with_capacity((width - len) * fill.len_utf8());
+        for _ in len..width { padding.push(fill) }
+        Some(Rope::from(padding))
     }
 
-    unicode_seg_iters! {
-        #[doc=
-            "Returns an iterator over the [grapheme clusters][graphemes] of \
-             `self`.\n\
+    /// Replaces every occurrence of the `char` `from` in this `Rope` with
+    /// `to`, in place.
+    ///
+    /// Only the leaves which contain `from` are rebuilt; the rest of the tree
+    /// is left as it is, so any nodes this `Rope` shares with other `Rope`s
+    /// stay shared. When `from` and `to` have the same UTF-8 length, leaves
+    /// which aren't shared are overwritten in place, without allocating.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let mut rope = Rope::from("a-b-c");
+    /// rope.replace_all_chars('-', '→');
+    /// assert_eq!(&rope, "a→b→c");
+    /// rope.replace_all_chars('→', '\n');
+    /// assert_eq!(&rope, "a\nb\nc");
+    /// ```
+    pub fn replace_all_chars(&mut self, from: char, to: char) {
+        if from != to { self.root.replace_chars(from, to); }
+    }
 
-             [graphemes]: \
-             http://www.unicode.org/reports/tr29/#Grapheme_Cluster_Boundaries\
-             \n\
-             The iterator is over the  *extended grapheme clusters*; as \
-             [UAX#29]\
-             (http://www.unicode.org/reports/tr29/#Grapheme_Cluster_Boundaries)\
-             recommends extended grapheme cluster boundaries for general \
-             processing."]
-        #[inline]
-        impl graphemes<&'a str> for Rope {}
-        #[doc=
-            "Returns an iterator over the words of `self`, separated on \
-            [UAX#29 word boundaries]\
-            (http://www.unicode.org/reports/tr29/#Word_Boundaries).\n\n\
+    /// Returns a new `Rope` with the indentation common to all of its
+    /// non-blank lines removed from every line.
+    ///
+    /// This mirrors Kotlin's `trimIndent()`. The indentation of a line is the
+    /// number of whitespace `char`s it begins with, and the smallest
+    /// indentation of any line which contains non-whitespace text is
+    /// stripped from the start of every line. Blank lines are stripped of up
+    /// to that much whitespace, but aren't considered when measuring it.
+    /// Unlike `trimIndent()`, leading and trailing blank lines are kept.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("    fn main() {\n        run();\n    }\n");
+    /// assert_eq!(&rope.trim_indent(), "fn main() {\n    run();\n}\n");
+    /// ```
+    pub fn trim_indent(&self) -> Rope {
+        fn indent(line: &RopeSlice) -> usize {
+            line.chars().take_while(|c| c.is_whitespace()).count()
+        }
+        fn is_blank(line: &RopeSlice) -> bool {
+            line.chars().all(char::is_whitespace)
+        }
+        let common = self.lines()
+                         .filter(|line| !is_blank(line))
+                         .map(|line| indent(&line))
+                         .min()
+                         .unwrap_or(0);
+        let lines = self.lines().map(|line| {
+            let strip = cmp::min(common, indent(&line));
+            Rope::from(line.chars().skip(strip).collect::<String>())
+        }).collect::<Vec<_>>();
+        let trimmed = Rope::join_with_rope(&Rope::from("\n"), &lines);
+        if self.ends_with('\n') { trimmed.append(&Rope::from("\n")) }
+        else { trimmed }
+    }
 
-            Here, \"words\" are just those substrings which, after splitting on\
-            UAX#29 word boundaries, contain any alphanumeric characters. That \
-            is, the substring must contain at least one character with the \
-            [Alphabetic](http://unicode.org/reports/tr44/#Alphabetic) \
-            property, or with [General_Category=Number]\
-            (http://unicode.org/reports/tr44/#General_Category_Values)."]
-        #[inline]
-        impl unicode_words<&'a str> for Rope {}
-        #[doc=
-            "Returns an iterator over substrings of `self` separated on \
-            [UAX#29 word boundaries]\
-            (http://www.unicode.org/reports/tr29/#Word_Boundaries). \n\n\
-            The concatenation of the substrings returned by this function is \
-            just the original string."]
-        #[inline]
-        impl split_word_bounds<&'a str> for Rope {}
-        // #[inline]
-        // impl grapheme_indices<(usize, &'a str)> for Rope {}
-        // #[inline]
-        // impl split_word_bound_indices<(usize, &'a str)> for Rope {}
+    /// Returns a new `Rope` built by applying `f` to each line of this
+    /// `Rope`.
+    ///
+    /// `f` is passed each line without its line ending, and the line ending
+    /// is added back after the `Rope` it returns, so `"\r\n"` line endings
+    /// are preserved. As with [`lines()`], a line ending at the very end of
+    /// this `Rope` isn't followed by an empty line, so `f` isn't called for
+    /// one. The results are joined into a balanced tree.
+    ///
+    /// [`lines()`]: struct.Rope.html#method.lines
+    ///
+    /// # Time Complexity
+    /// O(_n_), plus the time taken by `f`
+    ///
+    /// # Examples
+    ///
+    /// Quoting every line of a `Rope`:
+    ///
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("first\nsecond\n");
+    /// let quoted = rope.map_lines(|line| Rope::from(format!("> {}", line)));
+    /// assert_eq!(&quoted, "> first\n> second\n");
+    /// ```
+    pub fn map_lines<F>(&self, mut f: F) -> Rope
+    where F: FnMut(RopeSlice) -> Rope {
+        let mut pieces = Vec::new();
+        for (line, ending) in self.iter_lines_with_endings() {
+            let line = f(line);
+            if !line.is_empty() { pieces.push(line.root); }
+            if let Some(ending) = ending {
+                pieces.push(Rope::from(ending.as_str()).root);
+            }
+        }
+        Rope::from(Node::balanced_from(&pieces))
     }
 
-    /// Returns an iterator over the grapheme clusters of `self` and their
-    /// byte offsets. See `graphemes()` for more information.
+    /// Returns a new `Rope` with `prefix` added to the start of every line.
+    ///
+    /// The final line is indented even if it isn't terminated by a line
+    /// ending, but a line ending at the very end of this `Rope` isn't
+    /// followed by an indented empty line. [`dedent()`] reverses this.
+    ///
+    /// [`dedent()`]: struct.Rope.html#method.dedent
+    ///
+    /// # Time Complexity
+    /// O(_n_)
     ///
     /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("fn main() {\n    run();\n}");
+    /// assert_eq!( &rope.indent("    ")
+    ///           , "    fn main() {\n        run();\n    }");
+    /// ```
+    pub fn indent(&self, prefix: &str) -> Rope {
+        self.map_lines(|line| Rope::from(format!("{}{}", prefix, line)))
+    }
+
+    /// Returns a new `Rope` with `prefix` removed from the start of every
+    /// line which starts with it.
+    ///
+    /// Lines which don't start with `prefix` are left as they are, and
+    /// `prefix` is removed at most once from each line. This reverses
+    /// [`indent()`].
+    ///
+    /// [`indent()`]: struct.Rope.html#method.indent
+    ///
+    /// # Time Complexity
+    /// O(_n_)
     ///
+    /// # Examples
     /// ```
-    /// # use an_rope::Rope;
-    /// let rope = Rope::from("a̐éö̲\r\n");
-    /// let gr_inds = rope.grapheme_indices()
-    ///                   .collect::<Vec<(usize, &str)>>();
-    /// let b: &[_] = &[(0, "a̐"), (3, "é"), (6, "ö̲"), (11, "\r\n")];
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("> > quoted\n> reply\nsignature\n");
+    /// assert_eq!(&rope.dedent("> "), "> quoted\nreply\nsignature\n");
+    /// ```
+    pub fn dedent(&self, prefix: &str) -> Rope {
+        self.map_lines(|line| {
+            let line = line.to_string();
+            match line.strip_prefix(prefix) {
+                Some(rest) => Rope::from(rest)
+              , None => Rope::from(line.as_str())
+            }
+        })
+    }
+
+    /// Returns a new `Rope` with each run of consecutive, identical `char`s
+    /// in this `Rope` collapsed into a single `char`.
     ///
-    /// assert_eq!(&gr_inds[..], b);
+    /// This mirrors `Vec::dedup()`. The result is built leaf by leaf, and
+    /// runs which continue from one leaf into the next are collapsed too.
+    ///
+    /// # Time Complexity
+    /// O(_n_)
+    ///
+    /// # Examples
     /// ```
-    #[inline]
-    pub fn grapheme_indices(&self) -> internals::GraphemeIndices {
-        self.root.grapheme_indices()
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("aaabccddd  ö");
+    /// assert_eq!(&rope.dedup(), "abcd ö");
+    /// let rope = Rope::from("lo") + Rope::from("ooong\n\n") + Rope::from("g");
+    /// assert_eq!(&rope.dedup(), "long\ng");
+    /// ```
+    pub fn dedup(&self) -> Rope {
+        // the last character kept, which may be in a previous leaf
+        let mut last = None;
+        self.strings().map(|s| {
+            let mut out = String::with_capacity(s.len());
+            for c in s.chars() {
+                if last != Some(c) {
+                    out.push(c);
+                    last = Some(c);
+                }
+            }
+            Rope::from(out)
+        }).collect()
     }
 
-    /// Returns an iterator over substrings of `self`, split on UAX#29 word
-    /// boundaries, and their offsets. See `split_word_bounds()` for more
-    /// information.
+    /// Returns the number of leading bytes that this `Rope` shares with
+    /// `other`.
     ///
-    /// # Example
+    /// Both ropes are walked in lockstep until they diverge or one of them
+    /// ends, so this does not allocate, and the leaf structure of the two
+    /// ropes need not be the same.
     ///
+    /// # Time Complexity
+    /// O(_n_) in the length of the common prefix
+    ///
+    /// # Examples
     /// ```
-    /// # use an_rope::Rope;
-    /// let rope = Rope::from("Brr, it's 29.3°F!");
-    /// let swi1 = rope.split_word_bound_indices()
-    ///                .collect::<Vec<(usize, &str)>>();
-    /// let b: &[_] = &[ (0, "Brr"), (3, ","), (4, " "), (5, "it's")
-    ///                , (9, " "), (10, "29.3"),  (14, "°"), (16, "F")
-    ///                , (17, "!")];
+    /// use an_rope::Rope;
+    /// let a = Rope::from("hello world");
+    /// let b = Rope::from("hello") + Rope::from(" there");
+    /// assert_eq!(a.common_prefix_len(&b), 6);
+    /// assert_eq!(a.common_prefix_len(&a), a.len());
+    /// ```
+    pub fn common_prefix_len(&self, other: &Rope) -> usize {
+        self.bytes().zip(other.bytes())
+            .take_while(|&(a, b)| a == b)
+            .count()
+    }
+
+    /// Returns the number of trailing bytes that this `Rope` shares with
+    /// `other`.
     ///
-    /// assert_eq!(&swi1[..], b);
+    /// This is the counterpart to [`common_prefix_len()`], walking both ropes
+    /// from the back.
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the length of the common suffix
+    ///
+    /// # Examples
     /// ```
-    #[inline]
-    pub fn split_word_bound_indices(&self) -> internals::UWordBoundIndices {
-        self.root.split_word_bound_indices()
+    /// use an_rope::Rope;
+    /// let a = Rope::from("hello world");
+    /// let b = Rope::from("goodbye") + Rope::from(" world");
+    /// assert_eq!(a.common_suffix_len(&b), 6);
+    /// ```
+    ///
+    /// [`common_prefix_len()`]: struct.Rope.html#method.common_prefix_len
+    pub fn common_suffix_len(&self, other: &Rope) -> usize {
+        let other_bytes = other.root.strings_rev()
+                               .flat_map(|s| s.bytes().rev());
+        self.root.strings_rev()
+            .flat_map(|s| s.bytes().rev())
+            .zip(other_bytes)
+            .take_while(|&(a, b)| a == b)
+            .count()
     }
 
-    /// Returns true if the bytes in `self` equal the bytes in `other`
-    #[inline]
-    fn bytes_eq<I>(&self, other: I) -> bool
-    where I: Iterator<Item=u8> {
-        self.bytes().zip(other).all(|(a, b)| a == b)
+    /// Returns the region in which this `Rope` differs from `other`.
+    ///
+    /// The result is a tuple `(prefix_len, self_mid_len, other_mid_len)`:
+    /// both ropes share their first `prefix_len` bytes, and after that, the
+    /// next `self_mid_len` bytes of this rope were replaced by the next
+    /// `other_mid_len` bytes of `other`. Whatever follows the middle region
+    /// is shared by both ropes.
+    ///
+    /// This is not a full diff algorithm; rather, it finds the smallest
+    /// single edit that turns this `Rope` into `other`, which is enough to
+    /// describe a single insertion, deletion, or replacement. All of the
+    /// returned offsets fall on `char` boundaries.
+    ///
+    /// # Time Complexity
+    /// O(_n_) in the length of the common prefix and suffix
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let a = Rope::from("hello world");
+    /// let b = Rope::from("hello, cruel world");
+    /// assert_eq!(a.diff(&b), (5, 0, 7));
+    /// assert_eq!(b.diff(&a), (5, 7, 0));
+    /// assert_eq!(a.diff(&a), (11, 0, 0));
+    /// ```
+    pub fn diff(&self, other: &Rope) -> (usize, usize, usize) {
+        let mut prefix = self.common_prefix_len(other);
+        // the shared bytes are identical in both ropes, so a char boundary
+        // in one is a char boundary in the other
+        while !self.is_char_boundary(prefix) { prefix -= 1; }
+
+        // the suffix may not overlap the prefix in either rope
+        let max_suffix = cmp::min(self.len(), other.len()) - prefix;
+        let mut suffix = cmp::min(self.common_suffix_len(other), max_suffix);
+        while !self.is_char_boundary(self.len() - suffix) { suffix -= 1; }
+
+        ( prefix
+        , self.len() - prefix - suffix
+        , other.len() - prefix - suffix )
     }
 
     /// Returns an immutable slice of this `Rope` between the given indices.
@@ -941,6 +4593,202 @@ impl Rope {
         RopeSlice::new(&self.root, range)
     }
 
+    /// Returns an immutable slice of this `Rope` between the given _byte_
+    /// indices.
+    ///
+    /// Unlike the metric-generic slicing methods, the range passed to
+    /// `slice_bytes()` is always interpreted as a range of byte offsets.
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the length of this `Rope`
+    /// * If either end of `range` does not fall on a `char` boundary
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎 Léopard");
+    /// assert_eq!(&rope.slice_bytes(6..12), "老虎");
+    /// ```
+    ///
+    /// Slicing in the middle of a multi-byte character panics:
+    ///
+    /// ```should_panic
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎 Léopard");
+    /// rope.slice_bytes(7..12);
+    /// ```
+    pub fn slice_bytes(&self, range: ops::Range<usize>) -> RopeSlice<'_> {
+        assert!( range.start <= range.end
+               , "Rope::slice_bytes: start index {} > end index {}"
+               , range.start, range.end);
+        assert!( range.end <= self.len()
+               , "Rope::slice_bytes: byte index {} is out of bounds (length \
+                  {})"
+               , range.end, self.len());
+        assert!( self.is_char_boundary(range.start)
+               , "Rope::slice_bytes: byte index {} is not a char boundary"
+               , range.start);
+        assert!( self.is_char_boundary(range.end)
+               , "Rope::slice_bytes: byte index {} is not a char boundary"
+               , range.end);
+        RopeSlice::new(&self.root, range)
+    }
+
+    /// Returns an immutable slice of this `Rope` over the _byte_ range
+    /// `range`.
+    ///
+    /// This is the same as [`slice_bytes()`], but is named to pair with
+    /// [`grapheme_range_to_slice()`], so that code which slices by both
+    /// units says which one it means at every call site.
+    ///
+    /// [`slice_bytes()`]: struct.Rope.html#method.slice_bytes
+    /// [`grapheme_range_to_slice()`]: struct.Rope.html#method.grapheme_range_to_slice
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the length of this `Rope`
+    /// * If either end of `range` does not fall on a `char` boundary
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a̐éö̲ tail");
+    /// assert_eq!(&rope.byte_range_to_slice(0..3), "a̐");
+    /// ```
+    pub fn byte_range_to_slice(&self, range: ops::Range<usize>)
+                               -> RopeSlice<'_> {
+        self.try_slice(range)
+            .unwrap_or_else(|e| panic!("Rope::byte_range_to_slice: {}", e))
+    }
+
+    /// Returns an immutable slice of this `Rope` over the range `range` of
+    /// grapheme clusters.
+    ///
+    /// Both ends of `range` are converted to byte indices using the
+    /// [`Grapheme`] metric, so the slice never cuts a grapheme cluster in
+    /// half.
+    ///
+    /// [`Grapheme`]: metric/struct.Grapheme.html
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the number of grapheme
+    ///   clusters in this `Rope`
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::metric::Grapheme;
+    /// let rope = Rope::from("a̐éö̲ tail");
+    /// let slice = rope.grapheme_range_to_slice(Grapheme(0)..Grapheme(3));
+    /// assert_eq!(&slice, "a̐éö̲");
+    /// ```
+    pub fn grapheme_range_to_slice(&self, range: ops::Range<Grapheme>)
+                                   -> RopeSlice<'_> {
+        self.try_slice(range)
+            .unwrap_or_else(|e| panic!("Rope::grapheme_range_to_slice: {}", e))
+    }
+
+    /// Returns an immutable slice of this `Rope` over the range `range`,
+    /// measured by the metric `M`, or an error if the range is invalid.
+    ///
+    /// This is the non-panicking counterpart to [`slice()`] and
+    /// [`slice_bytes()`], for callers which need to report exactly why a
+    /// range could not be sliced. As with [`delete()`], a range of `Line`s
+    /// includes its last line.
+    ///
+    /// [`slice()`]: struct.Rope.html#method.slice
+    /// [`slice_bytes()`]: struct.Rope.html#method.slice_bytes
+    /// [`delete()`]: struct.Rope.html#method.delete
+    ///
+    /// # Errors
+    /// * [`SliceError::StartAfterEnd`] if the start of `range` is greater
+    ///   than its end
+    /// * [`SliceError::OutOfBounds`] if the end of `range` is greater than
+    ///   the length of this `Rope` as measured by `M`
+    /// * [`SliceError::NotOnBoundary`] if either end of `range` falls inside
+    ///   a multi-byte `char`
+    ///
+    /// [`SliceError::StartAfterEnd`]: error/enum.SliceError.html
+    /// [`SliceError::OutOfBounds`]: error/enum.SliceError.html
+    /// [`SliceError::NotOnBoundary`]: error/enum.SliceError.html
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// use an_rope::error::SliceError;
+    /// use an_rope::metric::Char;
+    ///
+    /// let rope = Rope::from("Löwe 老虎");
+    /// assert_eq!(&rope.try_slice(Char(5)..Char(7)).unwrap(), "老虎");
+    /// assert_eq!(&rope.try_slice(6..9).unwrap(), "老");
+    /// assert_eq!( rope.try_slice(0..2).unwrap_err()
+    ///           , SliceError::NotOnBoundary { index: 2 });
+    ///
+    /// use an_rope::metric::Line;
+    /// let rope = Rope::from("one\ntwo\nthree");
+    /// assert_eq!(&rope.try_slice(Line(1)..Line(1)).unwrap(), "two\n");
+    /// assert_eq!(&rope.try_slice(Line(1)..Line(2)).unwrap(), "two\nthree");
+    /// ```
+    pub fn try_slice<M>(&self, range: ops::Range<M>)
+                       -> Result<RopeSlice<'_>, SliceError>
+    where M: Metric
+        , Self: Measured<M> {
+        let len = self.measure();
+        if range.start > range.end {
+            return Err(SliceError::StartAfterEnd { start: range.start.into()
+                                                 , end: range.end.into() })
+        }
+        if range.end > len {
+            return Err(SliceError::OutOfBounds { index: range.end.into()
+                                               , len: len.into() })
+        }
+        let check = |i: M, byte_index: Option<usize>| {
+            match byte_index {
+                None => Err(SliceError::OutOfBounds { index: i.into()
+                                                    , len: len.into() })
+              , Some(b) if !self.is_char_boundary(b) =>
+                    Err(SliceError::NotOnBoundary { index: b })
+              , Some(b) => Ok(b)
+            }
+        };
+        let start = check(range.start, range.start.range_start(self))?;
+        let end = if range.end == len { Some(self.len()) }
+                  else { self.to_byte_index(range.end) };
+        let end = check(range.end, end)?;
+        Ok(RopeSlice::new(&self.root, start..end))
+    }
+
+}
+
+/// Returns the index of the first occurrence of the byte `b` in `s`.
+#[cfg(feature = "memchr")]
+#[inline]
+fn find_byte(s: &str, b: u8) -> Option<usize> {
+    memchr::memchr(b, s.as_bytes())
+}
+
+/// Returns the index of the first occurrence of the byte `b` in `s`.
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn find_byte(s: &str, b: u8) -> Option<usize> {
+    s.bytes().position(|x| x == b)
+}
+
+/// Returns the number of columns taken up by the grapheme cluster `g` when
+/// it is displayed starting at column `column`.
+///
+/// A tab advances to the next multiple of `tab_width`.
+#[cfg(feature = "width")]
+#[inline]
+fn column_width(g: &str, column: usize, tab_width: usize) -> usize {
+    use unicode_width::UnicodeWidthStr;
+    match g {
+        "\t" if tab_width == 0 => 0
+      , "\t" => tab_width - column % tab_width
+      , _ => UnicodeWidthStr::width(g)
+    }
 }
 
 impl convert::Into<Vec<u8>> for Rope {
@@ -1031,7 +4879,6 @@ impl cmp::PartialEq<String> for Rope {
     }
 }
 
-
 //-- concatenation --------------------------------------------------
 impl<'a> ops::Add for &'a Rope {
     type Output = Rope;
@@ -1216,6 +5063,33 @@ impl iter::FromIterator<char> for Rope {
 
 }
 
+/// Collects a stream of `(index, char)` pairs, such as that produced by
+/// [`Rope::char_indices()`], into a `Rope`.
+///
+/// The indices are ignored, so that a stream which has been filtered or
+/// otherwise edited can be collected back into a `Rope`.
+///
+/// # Examples
+/// ```
+/// use an_rope::Rope;
+/// let rope = Rope::from("Löwe 老虎");
+/// let rebuilt: Rope = rope.char_indices()
+///                         .filter(|&(_, c)| !c.is_whitespace())
+///                         .collect();
+/// assert_eq!(&rebuilt, "Löwe老虎");
+/// ```
+///
+/// [`Rope::char_indices()`]: struct.Rope.html#method.char_indices
+impl iter::FromIterator<(usize, char)> for Rope {
+
+    fn from_iter<I>(iter: I) -> Rope
+    where I: IntoIterator<Item=(usize, char)> {
+        let s: String = iter.into_iter().map(|(_, c)| c).collect();
+        Rope::from(s)
+    }
+
+}
+
 impl iter::FromIterator<String> for Rope {
 
     fn from_iter<I>(iter: I) -> Rope
@@ -1227,9 +5101,15 @@ impl iter::FromIterator<String> for Rope {
 
 impl iter::FromIterator<Rope> for Rope {
 
+    /// Concatenates the `Rope`s yielded by `iter` into a balanced tree,
+    /// sharing the tree of each one.
     fn from_iter<I>(iter: I) -> Rope
     where I: IntoIterator<Item=Rope> {
-        iter.into_iter().fold(Rope::new(), |acc, x| acc + x)
+        let nodes = iter.into_iter()
+                        .filter(|rope| !rope.is_empty())
+                        .map(|rope| rope.root)
+                        .collect::<Vec<_>>();
+        Rope::from(Node::balanced_from(&nodes))
     }
 
 }
@@ -31,13 +31,13 @@
 //! assert_eq!(&r, "this is a rope, 🆗!");
 //! ```
 //!
-//! Or, suppose my `Rope` spanned multiple lines:
+//! Or, suppose my `Rope` spanned multiple lines. A `Line` index refers to
+//! the whole of that line, including its line ending: splitting or
+//! inserting at `Line(n)` happens just after line _n_, and a range of
+//! `Line`s covers every line from its first to its last. To delete the
+//! third and fourth lines, I could say:
 //!
-//  FIXME: this test is ignored until
-//         https://github.com/an-cabal/an-rope/issues/66
-//         is fixed. i feel bad about this but it's not my fault.
-//          – eliza, 1/9/2017
-//! ```ignore
+//! ```
 //! # use an_rope::Rope;
 //! use an_rope::metric::Line;
 //! let r = Rope::from("this is\n\
@@ -56,6 +56,7 @@
 //! [`delete`]: ../struct.Rope.html#method.delete
 
 use std::convert;
+use std::iter;
 use std::ops::{Add, Sub};
 use std::default::Default;
 use std::fmt;
@@ -154,6 +155,18 @@ pub trait Metric: Monoid + Eq + Add<usize, Output=Self>
 
     /// Returns true if index `i` in `node` is a boundary along this `Metric`
     fn is_boundary<M: Measured<Self>>(node: &M, i: usize) -> bool;
+
+    /// Returns the byte index in `node` at which a range beginning with
+    /// this index starts.
+    ///
+    /// This is usually the same as `node.to_byte_index(self)`. However, a
+    /// `Line` index refers to the end of that line, while a range of
+    /// `Line`s includes its first line, so it starts at the end of the
+    /// line before.
+    #[inline]
+    fn range_start<M: Measured<Self>>(self, node: &M) -> Option<usize> {
+        node.to_byte_index(self)
+    }
 }
 
 macro_attr! {
@@ -180,6 +193,10 @@ impl fmt::Debug for Grapheme {
 
 macro_attr! {
     /// A metric for calculating indices in `Rope`s based on line numbering.
+    ///
+    /// `Line(n)` refers to the whole of line _n_, including its line ending.
+    /// As a single index it is the end of that line, while a range of
+    /// `Line`s covers both its first and last lines.
     #[derive( Clone, Copy, PartialOrd, Ord, PartialEq, Eq
             , NewtypeFrom!
             , NewtypeAdd!(*), NewtypeAdd!(&self, usize), NewtypeAdd!(usize)
@@ -201,6 +218,79 @@ impl fmt::Debug for Line {
    }
 }
 
+macro_attr! {
+    /// A metric for calculating indices in `Rope`s based on Unicode scalar
+    /// values (`char`s).
+    #[derive( Clone, Copy, PartialOrd, Ord, PartialEq, Eq
+            , NewtypeFrom!
+            , NewtypeAdd!(*), NewtypeAdd!(&self, usize), NewtypeAdd!(usize)
+            , NewtypeSub!(*), NewtypeSub!(&self, usize), NewtypeSub!(usize)
+            , NewtypeMul!(*), NewtypeMul!(&self, usize), NewtypeMul!(usize) )]
+    pub struct Char(pub usize);
+}
+
+impl Default for Char {
+    #[inline] fn default() -> Self { Char(0) }
+}
+
+impl Monoid for Char { }
+
+impl fmt::Debug for Char {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+       write!(f, "char {}", self.0)
+   }
+}
+
+impl Metric for Char {
+
+    #[inline] fn is_splittable() -> bool { true }
+
+    /// Returns true if index `i` in `node` is a boundary along this `Metric`
+    #[inline] fn is_boundary<M: Measured<Self>>(_node: &M, _i: usize) -> bool {
+        true
+    }
+}
+
+impl Measured<Char> for str {
+    /// Convert the `Metric` into a byte index into the given `Node`
+    ///
+    /// # Returns
+    /// - `Some` with the byte index of the beginning of the `n`th `char`
+    ///   in `node`, if there is an `n`th `char`
+    /// - `Some` with the length of the string, if `n` is the number of
+    ///   `char`s in the string (i.e., the index is the end of the string)
+    /// - `None` if `n` is greater than the number of `char`s
+    fn to_byte_index(&self, index: Char) -> Option<usize>  {
+        self.char_indices()
+            .map(|(offset, _)| offset)
+            .chain(Some(self.len()))
+            .nth(index.into())
+    }
+
+    #[inline]
+    fn measure(&self) -> Char {
+        Char(self.chars().count())
+    }
+
+    #[inline]
+    fn measure_weight(&self) -> Char {
+        Char(self.chars().count())
+    }
+}
+
+impl Measured<Char> for String {
+    #[inline]
+    fn to_byte_index(&self, index: Char) -> Option<usize>  {
+        self.as_str().to_byte_index(index)
+    }
+
+    #[inline]
+    fn measure(&self) -> Char { self.as_str().measure() }
+
+    #[inline]
+    fn measure_weight(&self) -> Char { self.as_str().measure_weight() }
+}
+
 impl Metric for Grapheme {
 
     #[inline] fn is_splittable() -> bool { false }
@@ -215,12 +305,19 @@ impl Measured<Grapheme> for str {
     /// Convert the `Metric` into a byte index into the given `Node`
     ///
     /// # Returns
-    /// - `Some` with the byte index of the beginning of the `n`th  element
-    ///    in `node` measured by this `Metric`, if there is an `n`th element
-    /// - `None` if there is no `n`th element in `node`
+    /// - `Some` with the byte index of the beginning of the `n`th grapheme
+    ///   cluster in `node`, if there is an `n`th grapheme cluster
+    /// - `Some` with the length of the string, if `n` is the number of
+    ///   grapheme clusters in the string (i.e., the index is the end of the
+    ///   string)
+    /// - `None` if `n` is greater than the number of grapheme clusters
+    ///
+    /// The returned index is always on a grapheme cluster boundary, so
+    /// splitting at it never separates a combining sequence from its base.
     fn to_byte_index(&self, index: Grapheme) -> Option<usize>  {
         self.grapheme_indices(true)
             .map(|(offset, _)| offset)
+            .chain(Some(self.len()))
             .nth(index.into())
     }
 
@@ -236,10 +333,9 @@ impl Measured<Grapheme> for str {
 }
 
 impl Measured<Grapheme> for String {
+    #[inline]
     fn to_byte_index(&self, index: Grapheme) -> Option<usize>  {
-        self.grapheme_indices(true)
-            .map(|(offset, _)| offset)
-            .nth(index.into())
+        self.as_str().to_byte_index(index)
     }
 
     #[inline]
@@ -263,40 +359,43 @@ impl Metric for Line {
     fn is_boundary<M: Measured<Self>>(_node: &M, _i: usize) -> bool {
         unimplemented!()
     }
+
+    #[inline]
+    fn range_start<M: Measured<Self>>(self, node: &M) -> Option<usize> {
+        match self {
+            Line(0) => Some(0)
+          , _ => node.to_byte_index(self - 1)
+        }
+    }
 }
 
 impl Measured<Line> for str {
-    // This can only handle line endings at the end of a string.
+    /// Returns the byte index of the end of line `index`: just after its
+    /// line ending, or the end of the string if it is the final line.
     fn to_byte_index(&self, index: Line) -> Option<usize>  {
-        match index.into() {
-            0 => Some(self.len())
-          , _ => None
-        }
+        self.char_indices()
+            .filter(|&(_, c)| c.is_line_ending())
+            .map(|(i, c)| i + c.len_utf8())
+            .chain(iter::once(self.len()))
+            .nth(index.into())
     }
 
     #[inline]
     fn measure(&self) -> Line {
-        let len = self.len();
-        Line(if self[len.saturating_sub(1)..len].is_line_ending() { 1 } else { 0 })
+        Line(self.chars().filter(IsLineEnding::is_line_ending).count())
     }
 
     #[inline] fn measure_weight(&self) -> Line { self.measure() }
 }
 
 impl Measured<Line> for String {
-    // This can only handle line endings at the end of a string.
+    #[inline]
     fn to_byte_index(&self, index: Line) -> Option<usize>  {
-        match index.into() {
-            0 => Some(self.len())
-          , _ => None
-        }
+        self.as_str().to_byte_index(index)
     }
 
     #[inline]
-    fn measure(&self) -> Line {
-        let len = self.len();
-        Line(if self[len.saturating_sub(1)..len].is_line_ending() { 1 } else { 0 })
-    }
+    fn measure(&self) -> Line { self.as_str().measure() }
 
     #[inline] fn measure_weight(&self) -> Line { self.measure() }
 }
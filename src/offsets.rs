@@ -0,0 +1,135 @@
+//! Pairing the items of a `Rope`'s iterators with their byte offsets.
+//!
+//! [`Rope::with_byte_offsets()`] wraps an iterator over the `char`s,
+//! grapheme clusters, or words of a `Rope`, and yields each item together
+//! with its byte offset from the start of the `Rope`. This gives every
+//! segmentation iterator offsets, without needing a separate `*_indices()`
+//! method for each one.
+//!
+//! # Examples
+//!
+//! ```
+//! use an_rope::Rope;
+//! let rope = Rope::from("Löwe 老虎");
+//! let words = rope.with_byte_offsets(rope.unicode_words())
+//!                 .collect::<Vec<_>>();
+//! assert_eq!(words, vec![(0, "Löwe"), (6, "老"), (9, "虎")]);
+//! ```
+//!
+//! [`Rope::with_byte_offsets()`]: ../struct.Rope.html#method.with_byte_offsets
+
+use std::collections::VecDeque;
+
+mod private {
+    /// Prevents `Segment` from being implemented outside this crate.
+    pub trait Sealed {}
+
+    impl Sealed for char {}
+    impl Sealed for &str {}
+}
+
+/// An item yielded by one of a `Rope`'s iterators, whose byte offset can be
+/// found by [`WithByteOffsets`].
+///
+/// This trait is sealed, and is implemented for `char` and `&str`.
+///
+/// [`WithByteOffsets`]: struct.WithByteOffsets.html
+pub trait Segment: private::Sealed {
+    /// Returns the length of this item in bytes.
+    fn byte_len(&self) -> usize;
+
+    /// Returns a pointer to the first byte of this item, if it borrows the
+    /// text of the `Rope` it came from.
+    fn text_ptr(&self) -> Option<*const u8> { None }
+}
+
+impl Segment for char {
+    #[inline] fn byte_len(&self) -> usize { self.len_utf8() }
+}
+
+impl Segment for &str {
+    #[inline] fn byte_len(&self) -> usize { self.len() }
+    #[inline] fn text_ptr(&self) -> Option<*const u8> { Some(self.as_ptr()) }
+}
+
+/// An iterator pairing the items of another iterator over a `Rope` with
+/// their byte offsets from the start of the `Rope`.
+///
+/// Items which borrow the `Rope`'s text (such as grapheme clusters and
+/// words) are located within the `Rope`'s leaves, so iterators which skip
+/// some of the text, such as [`Rope::unicode_words()`], still get the
+/// correct offsets. Items which don't (such as `char`s, or strings from
+/// somewhere else) are assumed to follow on directly from the previous item.
+///
+/// This `struct` is created by [`Rope::with_byte_offsets()`].
+///
+/// [`Rope::unicode_words()`]: ../struct.Rope.html#method.unicode_words
+/// [`Rope::with_byte_offsets()`]: ../struct.Rope.html#method.with_byte_offsets
+pub struct WithByteOffsets<'a, I> {
+    iter: I
+  , strings: Box<dyn Iterator<Item=&'a str> + 'a>
+  , /// the leaves read from `strings` so far, starting with the one
+    /// containing the last item located, paired with their byte offsets
+    leaves: VecDeque<(usize, &'a str)>
+  , /// the byte offset of the next leaf to be read from `strings`
+    strings_offset: usize
+  , /// the byte offset of the end of the last item
+    next_offset: usize
+}
+
+impl<'a, I> WithByteOffsets<'a, I> {
+    #[inline]
+    pub(crate) fn new<S>(iter: I, strings: S) -> Self
+    where S: Iterator<Item=&'a str> + 'a {
+        WithByteOffsets { iter
+                        , strings: Box::new(strings)
+                        , leaves: VecDeque::new()
+                        , strings_offset: 0
+                        , next_offset: 0 }
+    }
+
+    /// Returns the offset of the text at `ptr`, searching forward through
+    /// the leaves for the one containing it.
+    ///
+    /// The leaves before the one found are discarded, but if `ptr` isn't in
+    /// any of them, they're all kept, so that an item which doesn't borrow
+    /// the `Rope`'s text doesn't prevent later items from being located.
+    fn locate(&mut self, ptr: *const u8) -> Option<usize> {
+        let ptr = ptr as usize;
+        let mut i = 0;
+        loop {
+            if i == self.leaves.len() {
+                let next = self.strings.next()?;
+                self.leaves.push_back((self.strings_offset, next));
+                self.strings_offset += next.len();
+            }
+            let (offset, leaf) = self.leaves[i];
+            let start = leaf.as_ptr() as usize;
+            if start <= ptr && ptr < start + leaf.len() {
+                self.leaves.drain(..i);
+                return Some(offset + (ptr - start))
+            }
+            i += 1;
+        }
+    }
+}
+
+impl<'a, I> Iterator for WithByteOffsets<'a, I>
+where I: Iterator
+    , I::Item: Segment {
+    type Item = (usize, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        let offset = item.text_ptr()
+                         .and_then(|ptr| self.locate(ptr))
+                         .unwrap_or(self.next_offset);
+        self.next_offset = offset + item.byte_len();
+        Some((offset, item))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
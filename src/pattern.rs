@@ -0,0 +1,168 @@
+//! Patterns for matching against the contents of a `Rope`.
+//!
+//! Like the standard library's [`Pattern`] API for `str`, methods such as
+//! [`Rope::starts_with()`] accept several kinds of pattern:
+//!
+//! + a `&str`, which matches that exact string,
+//! + a `char`, which matches that exact character,
+//! + a closure `FnMut(char) -> bool`, which matches any character for which
+//!   the closure returns `true`.
+//!
+//! The [`RopePattern`] trait is sealed, so it can't be implemented outside of
+//! this crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use an_rope::Rope;
+//! let rope = Rope::from(" hello world!");
+//! assert!(rope.starts_with(" hello"));
+//! assert!(rope.starts_with(' '));
+//! assert!(rope.starts_with(char::is_whitespace));
+//! assert!(rope.ends_with('!'));
+//! ```
+//!
+//! [`Pattern`]: https://doc.rust-lang.org/std/str/pattern/trait.Pattern.html
+//! [`Rope::starts_with()`]: ../struct.Rope.html#method.starts_with
+//! [`RopePattern`]: trait.RopePattern.html
+
+use Rope;
+
+use std::ops::Range;
+
+mod private {
+    /// Prevents `RopePattern` from being implemented outside this crate.
+    pub trait Sealed {}
+
+    impl Sealed for &str {}
+    impl Sealed for char {}
+    impl<F> Sealed for F where F: FnMut(char) -> bool {}
+}
+
+/// A pattern which can be matched against the contents of a [`Rope`].
+///
+/// This trait is sealed, and is implemented for `&str`, `char`, and closures
+/// of the form `FnMut(char) -> bool`.
+///
+/// [`Rope`]: ../struct.Rope.html
+pub trait RopePattern: private::Sealed {
+    /// Returns `true` if this pattern matches at the start of `rope`.
+    fn is_prefix_of(&mut self, rope: &Rope) -> bool;
+
+    /// Returns `true` if this pattern matches at the end of `rope`.
+    fn is_suffix_of(&mut self, rope: &Rope) -> bool;
+
+    /// Returns the byte ranges of up to `limit` non-overlapping matches of
+    /// this pattern in `rope`, searching backward from the end of `rope`.
+    ///
+    /// The ranges are returned in the order they were found, from the last
+    /// match in `rope` to the first.
+    fn rmatch_ranges(&mut self, rope: &Rope, limit: usize)
+                     -> Vec<Range<usize>>;
+}
+
+/// Returns an iterator over the `char`s of `rope` and their byte offsets,
+/// starting from the last `char`.
+fn rev_char_indices<'a>(rope: &'a Rope)
+                        -> impl Iterator<Item=(usize, char)> + 'a {
+    let mut end = rope.len();
+    rope.root.strings_rev().flat_map(move |s| {
+        end -= s.len();
+        let start = end;
+        s.char_indices().rev().map(move |(i, c)| (start + i, c))
+    })
+}
+
+impl RopePattern for &str {
+    #[inline]
+    fn is_prefix_of(&mut self, rope: &Rope) -> bool {
+        rope.len() >= self.len() &&
+            rope.bytes().zip(self.bytes()).all(|(a, b)| a == b)
+    }
+
+    #[inline]
+    fn is_suffix_of(&mut self, rope: &Rope) -> bool {
+        rope.len() >= self.len() &&
+            rope.root.strings_rev()
+                .flat_map(|s| s.bytes().rev())
+                .zip(self.bytes().rev())
+                .all(|(a, b)| a == b)
+    }
+
+    fn rmatch_ranges(&mut self, rope: &Rope, limit: usize)
+                     -> Vec<Range<usize>> {
+        use std::collections::VecDeque;
+        let len = rope.len();
+        if self.is_empty() {
+            // like `str`, the empty string matches at every char boundary
+            return Some(len).into_iter()
+                .chain(rev_char_indices(rope).map(|(i, _)| i))
+                .take(limit)
+                .map(|i| i..i)
+                .collect()
+        }
+        let pat = self.as_bytes();
+        let mut matches = Vec::new();
+        if limit == 0 { return matches }
+        // slide a window the length of the pattern backward over the bytes
+        // of the rope, so that matches spanning leaf boundaries are found
+        let mut window = VecDeque::with_capacity(pat.len());
+        for (i, b) in rope.bytes_rev().enumerate() {
+            if window.len() == pat.len() { window.pop_back(); }
+            window.push_front(b);
+            if window.len() == pat.len() && window.iter().eq(pat.iter()) {
+                let start = len - i - 1;
+                matches.push(start..start + pat.len());
+                if matches.len() == limit { break }
+                // matches may not overlap
+                window.clear();
+            }
+        }
+        matches
+    }
+}
+
+impl RopePattern for char {
+    #[inline]
+    fn is_prefix_of(&mut self, rope: &Rope) -> bool {
+        rope.chars().next() == Some(*self)
+    }
+
+    #[inline]
+    fn is_suffix_of(&mut self, rope: &Rope) -> bool {
+        rope.root.strings_rev().next()
+            .and_then(|s| s.chars().next_back()) == Some(*self)
+    }
+
+    fn rmatch_ranges(&mut self, rope: &Rope, limit: usize)
+                     -> Vec<Range<usize>> {
+        rev_char_indices(rope).filter(|&(_, c)| c == *self)
+                              .take(limit)
+                              .map(|(i, c)| i..i + c.len_utf8())
+                              .collect()
+    }
+}
+
+impl<F> RopePattern for F
+where F: FnMut(char) -> bool {
+    #[inline]
+    fn is_prefix_of(&mut self, rope: &Rope) -> bool {
+        rope.chars().next().map(self).unwrap_or(false)
+    }
+
+    #[inline]
+    fn is_suffix_of(&mut self, rope: &Rope) -> bool {
+        rope.root.strings_rev().next()
+            .and_then(|s| s.chars().next_back())
+            .map(self)
+            .unwrap_or(false)
+    }
+
+    fn rmatch_ranges(&mut self, rope: &Rope, limit: usize)
+                     -> Vec<Range<usize>> {
+        rev_char_indices(rope).filter(|&(_, c)| self(c))
+                              .take(limit)
+                              .map(|(i, c)| i..i + c.len_utf8())
+                              .collect()
+    }
+}
@@ -19,12 +19,13 @@ use collections::range::RangeArgument;
 use std::ops::Range;
 
 use super::Rope;
-use super::internals::Node;
+use super::internals::{Node, NodeLink};
 
 /// An immutable borrowed slice of a `Rope`.
 ///
 /// A `RopeSlice` represents an immutable borrowed slice of some or all the
 /// characters in a `Rope`.
+#[derive(Clone, Copy)]
 pub struct RopeSlice<'a> { node: &'a Node
                          , offset: usize
                          , len: usize
@@ -408,17 +409,44 @@ impl<'a> fmt::Debug for RopeSlice<'a> {
 
 impl<'a> RopeSlice<'a> {
     unstable_iters! {
+        #[doc="Returns an iterator over the strings in this `RopeSlice`."]
+        #[inline]
+        pub fn strings(&'a self) -> impl Iterator<Item=&'a str> + 'a  {
+            // walk to the leaf containing the start of the slice, and then
+            // take strings until we've taken `len` bytes
+            self.node.strings_from(self.offset)
+                .scan(self.len, |remaining, s| {
+                    if *remaining == 0 {
+                        None
+                    } else if s.len() < *remaining {
+                        *remaining -= s.len();
+                        Some(s)
+                    } else {
+                        let r = *remaining;
+                        *remaining = 0;
+                        Some(&s[..r])
+                    }
+                })
+        }
         #[inline]
         pub fn chars(&'a self) -> impl Iterator<Item=char> + 'a  {
-            self.slice_char_iter(self.node.chars())
+            self.strings().flat_map(str::chars)
         }
+        #[doc="Returns an iterator over the `char`s in this `RopeSlice` and \
+               their byte offsets from the start of the slice."]
         #[inline]
         pub fn char_indices(&'a self) -> impl Iterator<Item=(usize, char)> + 'a {
-            self.chars().enumerate()
+            self.strings()
+                .scan(0, |offset, s| {
+                    let start = *offset;
+                    *offset += s.len();
+                    Some(s.char_indices().map(move |(i, c)| (start + i, c)))
+                })
+                .flatten()
         }
         #[inline]
         pub fn bytes(&'a self) -> impl Iterator<Item=u8> + 'a  {
-            self.slice_char_iter(self.node.bytes())
+            self.strings().flat_map(str::bytes)
         }
         #[inline]
         pub fn split_whitespace(&'a self) -> impl Iterator<Item=&'a str> + 'a  {
@@ -477,15 +505,6 @@ impl<'a> RopeSlice<'a> {
     }
 
 
-    #[cfg(feature = "unstable")]
-    #[inline]
-    fn slice_char_iter<I, T>(&'a self, i: I) -> impl Iterator<Item=T> + 'a
-    where I: Iterator<Item=T>
-        , I: 'a
-        , T: Copy {
-            i.skip(self.offset).take(self.len)
-    }
-
     #[cfg(feature = "unstable")]
     fn slice_strings_iter<I>(&'a self, i: I) -> impl Iterator<Item=&'a str> + 'a
     where I: Iterator<Item=&'a str>
@@ -515,16 +534,6 @@ impl<'a> RopeSlice<'a> {
         })
          .skip_while(|&s| s == "")
     }
-    #[cfg(not(feature = "unstable"))]
-    #[inline]
-    fn slice_char_iter<I, T>(&'a self, i: I) -> Box<Iterator<Item=T> + 'a>
-    where I: Iterator<Item=T>
-        , I: 'a
-        , T: Copy {
-            Box::new(i.skip(self.offset)
-                      .take(self.len))
-    }
-
     #[cfg(not(feature = "unstable"))]
     fn slice_strings_iter<I>(&'a self, i: I) -> Box<Iterator<Item=&'a str> + 'a>
     where I: Iterator<Item=&'a str>
@@ -597,6 +606,102 @@ impl<'a> RopeSlice<'a> {
     /// assert!(!an_rope.slice(0..5).is_empty());
     /// ```
     #[inline] pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Returns `true` if the byte at `index` in this `RopeSlice` is the first
+    /// byte of a `char`, or `index` is the length of the slice.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("Löwe 老虎");
+    /// let slice = rope.slice_bytes(1..9);
+    /// assert!(slice.is_char_boundary(0));
+    /// assert!(!slice.is_char_boundary(1));
+    /// assert!(slice.is_char_boundary(slice.len()));
+    /// ```
+    pub fn is_char_boundary(&self, index: usize) -> bool {
+        if index > self.len {
+            false
+        } else {
+            let i = self.offset + index;
+            i == self.node.len() ||
+                self.node.leaf_at(i)
+                    .map(|(s, i)| s.is_char_boundary(i))
+                    .unwrap_or(false)
+        }
+    }
+
+    /// Returns a sub-slice of this `RopeSlice` between the given byte
+    /// indices, or `None` if the range is out of bounds or does not fall on
+    /// `char` boundaries.
+    ///
+    /// The indices are relative to the start of this slice, rather than to
+    /// the start of the `Rope` it was sliced from.
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("the Löwe is a lion");
+    /// let slice = rope.slice_bytes(4..18);
+    /// assert_eq!(&slice.get(0..5).unwrap(), "Löwe");
+    /// assert!(slice.get(0..2).is_none());
+    /// assert!(slice.get(0..100).is_none());
+    /// ```
+    pub fn get(&self, range: Range<usize>) -> Option<RopeSlice<'a>> {
+        if range.start <= range.end
+            && self.is_char_boundary(range.start)
+            && self.is_char_boundary(range.end) {
+            Some(RopeSlice::new( self.node
+                               , self.offset + range.start ..
+                                 self.offset + range.end ))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a sub-slice of this `RopeSlice` between the given byte
+    /// indices.
+    ///
+    /// The indices are relative to the start of this slice, rather than to
+    /// the start of the `Rope` it was sliced from.
+    ///
+    /// # Panics
+    /// * If the start of `range` is greater than its end
+    /// * If the end of `range` is greater than the length of this slice
+    /// * If either end of `range` does not fall on a `char` boundary
+    ///
+    /// # Examples
+    /// ```
+    /// use an_rope::Rope;
+    /// let rope = Rope::from("a line of words\nanother line");
+    /// let line = rope.slice_bytes(0..15);
+    /// assert_eq!(&line.slice(2..6), "line");
+    /// assert_eq!(&line.slice(10..15), "words");
+    /// ```
+    pub fn slice(&self, range: Range<usize>) -> RopeSlice<'a> {
+        assert!( range.start <= range.end
+               , "RopeSlice::slice: start index {} > end index {}"
+               , range.start, range.end);
+        assert!( range.end <= self.len
+               , "RopeSlice::slice: byte index {} is out of bounds (length \
+                  {})"
+               , range.end, self.len);
+        assert!( self.is_char_boundary(range.start)
+               , "RopeSlice::slice: byte index {} is not a char boundary"
+               , range.start);
+        assert!( self.is_char_boundary(range.end)
+               , "RopeSlice::slice: byte index {} is not a char boundary"
+               , range.end);
+        RopeSlice::new( self.node
+                      , self.offset + range.start .. self.offset + range.end)
+    }
+
+    /// Returns a new tree containing the text of this slice, sharing every
+    /// node of the sliced `Rope` that the slice covers entirely.
+    #[inline]
+    pub(crate) fn subrope(&self) -> NodeLink {
+        self.node.subrope(self.offset .. self.offset + self.len)
+    }
 }
 
 //-- comparisons ----------------------------------------------------
@@ -732,6 +837,82 @@ mod tests {
         assert_eq!(&rope_slice, string_slice)
     }
 
+    #[test]
+    fn slice_bytes_multibyte() {
+        let string = "héllo\nwörld\n老虎 💖 done";
+        let rope = Rope::from("héllo\nwö") + Rope::from("rld\n老虎 💖 done");
+        let ranges = [(0, 3), (1, 3), (3, 14), (10, 21), (14, 25), (0, 30)];
+        for &(start, end) in &ranges {
+            let slice = rope.slice_bytes(start..end);
+            assert_eq!(&slice, &string[start..end]);
+            assert_eq!( slice.chars().collect::<String>()
+                      , &string[start..end]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "byte index 2 is not a char boundary")]
+    fn slice_bytes_mid_codepoint() {
+        let rope = Rope::from("héllo");
+        rope.slice_bytes(2..4);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn slice_bytes_out_of_bounds() {
+        let rope = Rope::from("héllo");
+        rope.slice_bytes(2..42);
+    }
+
+    #[test]
+    fn sub_slices_match_str_slicing() {
+        let string = "héllo wörld\n老虎 💖 done\nlast line";
+        let rope = Rope::from("héllo wö") + Rope::from("rld\n老虎 💖") +
+                   Rope::from(" done\nlast line");
+        let (start, end) = (3, 30);
+        let slice = rope.slice_bytes(start..end);
+        let str_slice = &string[start..end];
+        for &(a, b) in &[(0, 0), (0, 3), (4, 9), (8, 17), (5, 27), (0, 27)] {
+            let sub = slice.slice(a..b);
+            assert_eq!(&sub, &str_slice[a..b]);
+            assert_eq!(sub.chars().collect::<String>(), &str_slice[a..b]);
+            assert_eq!(slice.get(a..b), Some(sub));
+        }
+        // sub-slicing a sub-slice
+        let sub = slice.slice(5..27).slice(4..9);
+        assert_eq!(&sub, &str_slice[5..27][4..9]);
+    }
+
+    #[test]
+    fn get_rejects_invalid_ranges() {
+        let rope = Rope::from("héllo") + Rope::from(" wörld");
+        let slice = rope.slice_bytes(1..10);
+        assert!(slice.get(0..1).is_none());
+        assert!(slice.get(0..10).is_none());
+        let (start, end) = (3, 2);
+        assert!(slice.get(start..end).is_none());
+        assert!(slice.get(2..9).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn sub_slice_out_of_bounds() {
+        let rope = Rope::from("hello world");
+        rope.slice_bytes(0..5).slice(0..6);
+    }
+
+    #[test]
+    fn copied_slices_are_independent_handles() {
+        let rope = Rope::from("hello\n") + Rope::from("wörld");
+        let slice = rope.slice_bytes(3..9);
+        let copy = slice;
+        assert_eq!(&slice, "lo\nwö");
+        assert_eq!(&copy, "lo\nwö");
+        assert_eq!( slice.chars().collect::<String>()
+                  , copy.chars().collect::<String>());
+        assert_eq!(slice.bytes().count(), copy.bytes().count());
+    }
+
     // #[test]
     // fn between() {
     //     let string = "aaaaabbbbbbccccccccccccdefgdefgaabababab";
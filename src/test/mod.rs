@@ -22,15 +22,15 @@ impl Arbitrary for Rope {
 }
 
 
-#[ignore]
+#[test]
 fn line_delete_test_1() {
     use metric::Line;
-    let mut rope = Rope::from("this is\n\
-                               a\n\
-                               multi\n\
-                               line\n\
-                               rope");
-    rope.delete(Line(2)..Line(3));
+    let rope = Rope::from("this is\n\
+                           a\n\
+                           multi\n\
+                           line\n\
+                           rope");
+    let rope = rope.delete(Line(2)..Line(3));
     assert_eq!( &rope
               , "this is\n\
                  a\n\
@@ -38,15 +38,15 @@ fn line_delete_test_1() {
               )
 }
 
-#[ignore]
+#[test]
 fn line_delete_test_2() {
     use metric::Line;
-    let mut rope = Rope::from("this is\n\
-                               a\n\
-                               multi\n\
-                               line\n\
-                               rope");
-    rope.delete(Line(0)..Line(0));
+    let rope = Rope::from("this is\n\
+                           a\n\
+                           multi\n\
+                           line\n\
+                           rope");
+    let rope = rope.delete(Line(0)..Line(0));
     assert_eq!( &rope
               , "a\n\
                  multi\n\
@@ -432,6 +432,99 @@ mod properties {
             rope == string
         }
 
+        fn construction_path_is_irrelevant(s: String, cuts: Vec<usize>)
+                                           -> bool {
+            // chop the string into chunks on char boundaries, using the
+            // randomly generated cut points
+            let mut chunks = Vec::new();
+            let mut rest = &s[..];
+            for cut in cuts {
+                if rest.is_empty() { break }
+                let mut i = cut % (rest.len() + 1);
+                while !rest.is_char_boundary(i) { i -= 1; }
+                let (chunk, tail) = rest.split_at(i);
+                chunks.push(chunk);
+                rest = tail;
+            }
+            chunks.push(rest);
+
+            let from = Rope::from(s.clone());
+            let appended = chunks.iter()
+                .fold(Rope::new(), |r, c| r.append(&Rope::from(*c)));
+            let prepended = chunks.iter().rev()
+                .fold(Rope::new(), |r, c| r.prepend(&Rope::from(*c)));
+            let collected: Rope = chunks.iter().cloned().collect();
+
+            from.equal_content_ignoring_structure(&appended)
+                && from.equal_content_ignoring_structure(&prepended)
+                && from.equal_content_ignoring_structure(&collected)
+        }
+
+        fn cached_len_matches_leaf_lens( s: String
+                                       , edits: Vec<(u8, usize, usize, String)>)
+                                       -> bool {
+            // snap a random index to a char boundary in `s`
+            fn snap(s: &str, i: usize) -> usize {
+                let mut i = i % (s.len() + 1);
+                while !s.is_char_boundary(i) { i -= 1; }
+                i
+            }
+            let mut rope = Rope::from(s.clone());
+            let mut string = s;
+            if !rope.len_is_consistent() { return false }
+            for (op, a, b, text) in edits {
+                let (a, b) = (snap(&string, a), snap(&string, b));
+                let (start, end) = (a.min(b), a.max(b));
+                match op % 8 {
+                    0 => {
+                        rope = rope.insert_str(start, &text);
+                        string.insert_str(start, &text);
+                    }
+                  , 1 => {
+                        rope = rope.delete(start..end);
+                        string.drain(start..end);
+                    }
+                  , 2 => {
+                        rope = rope.append(&Rope::from(text.clone()));
+                        string.push_str(&text);
+                    }
+                  , 3 => {
+                        rope = rope.prepend(&Rope::from(text.clone()));
+                        string.insert_str(0, &text);
+                    }
+                  , 4 => {
+                        let (left, right) = rope.split(start);
+                        if !left.len_is_consistent()
+                            || !right.len_is_consistent() {
+                            return false
+                        }
+                        rope = right.append(&left);
+                        let tail = string.split_off(start);
+                        string.insert_str(0, &tail);
+                    }
+                  , 5 => {
+                        rope.apply_edit(start..end, Rope::from(text.clone()));
+                        string.drain(start..end);
+                        string.insert_str(start, &text);
+                    }
+                  , 6 => {
+                        rope.push_rope(Rope::from(text.clone()));
+                        string.push_str(&text);
+                    }
+                  , _ => {
+                        let removed =
+                            rope.remove_range_returning_rope(start..end);
+                        if !removed.len_is_consistent() { return false }
+                        string.drain(start..end);
+                    }
+                }
+                if !rope.len_is_consistent() || rope != string {
+                    return false
+                }
+            }
+            true
+        }
+
         // fn rope_add_assign_is_string_push_str(a: String, b: String) -> bool {
         //     let mut rope = Rope::from(a.clone());
         //     rope = rope + &b;
@@ -710,3 +803,3737 @@ mod iterator {
     }
 
 }
+
+#[test]
+fn truncate_bytes_snaps_to_char_boundary() {
+    // "é" is two bytes, "老" is three, and "💖" is four
+    let rope = Rope::from("é老💖");
+    assert_eq!(&rope.truncate_bytes(1), "");
+    assert_eq!(&rope.truncate_bytes(2), "é");
+    assert_eq!(&rope.truncate_bytes(3), "é");
+    assert_eq!(&rope.truncate_bytes(4), "é");
+    assert_eq!(&rope.truncate_bytes(5), "é老");
+    assert_eq!(&rope.truncate_bytes(8), "é老");
+    assert_eq!(&rope.truncate_bytes(9), "é老💖");
+}
+
+#[test]
+fn truncate_bytes_across_leaves() {
+    let rope = Rope::from("ab\n") + Rope::from("c💖d");
+    assert_eq!(&rope.truncate_bytes(3), "ab\n");
+    assert_eq!(&rope.truncate_bytes(4), "ab\nc");
+    assert_eq!(&rope.truncate_bytes(6), "ab\nc");
+    assert_eq!(&rope.truncate_bytes(8), "ab\nc💖");
+}
+
+#[test]
+fn checked_truncate_bytes_mid_codepoint() {
+    use error::CharBoundaryError;
+    let rope = Rope::from("é老💖");
+    for &i in &[1, 3, 4, 6, 7, 8] {
+        assert_eq!( rope.checked_truncate_bytes(i)
+                  , Err(CharBoundaryError { index: i }));
+    }
+    assert_eq!(&rope.checked_truncate_bytes(5).unwrap(), "é老");
+    assert_eq!(&rope.checked_truncate_bytes(42).unwrap(), "é老💖");
+}
+
+mod pattern {
+    use ::Rope;
+
+    fn multi_leaf() -> Rope {
+        Rope::from("ab") + Rope::from("cd\n") + Rope::from("ef💖")
+    }
+
+    #[test]
+    fn str_prefix_across_leaves() {
+        let rope = multi_leaf();
+        assert!(rope.starts_with(""));
+        assert!(rope.starts_with("abc"));
+        assert!(rope.starts_with("abcd\ne"));
+        assert!(!rope.starts_with("abd"));
+        assert!(!rope.starts_with("abcd\nef💖!"));
+    }
+
+    #[test]
+    fn str_suffix_across_leaves() {
+        let rope = multi_leaf();
+        assert!(rope.ends_with(""));
+        assert!(rope.ends_with("f💖"));
+        assert!(rope.ends_with("d\nef💖"));
+        assert!(!rope.ends_with("e💖"));
+        assert!(!rope.ends_with("!abcd\nef💖"));
+    }
+
+    #[test]
+    fn char_prefix_and_suffix() {
+        let rope = multi_leaf();
+        assert!(rope.starts_with('a'));
+        assert!(!rope.starts_with('b'));
+        assert!(rope.ends_with('💖'));
+        assert!(!rope.ends_with('f'));
+        assert!(!Rope::new().starts_with('a'));
+        assert!(!Rope::new().ends_with('a'));
+    }
+
+    #[test]
+    fn closure_prefix_and_suffix() {
+        let rope = Rope::from("  indented\n") + Rope::from("text ");
+        assert!(rope.starts_with(char::is_whitespace));
+        assert!(rope.ends_with(char::is_whitespace));
+        assert!(!rope.starts_with(|c: char| c.is_alphabetic()));
+        assert!(!Rope::new().starts_with(|_| true));
+    }
+}
+
+#[test]
+fn bytes_from_line_matches_slice() {
+    let s = "line a\n\
+             line bb\n\
+             lïne ç\n\
+             line d";
+    let r = Rope::from("line a\nli") + Rope::from("ne bb\nlïne ç\n") +
+            Rope::from("line d");
+    let mut start = 0;
+    for (n, line) in s.split('\n').enumerate() {
+        let from_line = r.bytes_from_line(Line(n)).collect::<Vec<u8>>();
+        assert_eq!(from_line, s[start..].bytes().collect::<Vec<u8>>());
+        assert_eq!( from_line
+                  , r.slice(start..r.len()).bytes().collect::<Vec<u8>>());
+        start += line.len() + 1;
+    }
+}
+
+#[test]
+fn bytes_from_line_after_trailing_newline() {
+    let r = Rope::from("a\nb\n");
+    assert_eq!(r.bytes_from_line(Line(2)).count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn bytes_from_line_out_of_bounds() {
+    let r = Rope::from("a\nb");
+    let _ = r.bytes_from_line(Line(2));
+}
+
+#[test]
+fn remove_range_returning_rope_multibyte() {
+    let mut rope = Rope::from("héllo\n") + Rope::from("wörld\n") +
+                   Rope::from("💖!");
+    let removed = rope.remove_range_returning_rope(3..14);
+    assert_eq!(&removed, "llo\nwörld\n");
+    assert_eq!(&rope, "hé💖!");
+    assert_eq!(rope.len(), "hé💖!".len());
+}
+
+#[test]
+fn remove_range_returning_rope_everything() {
+    let mut rope = Rope::from("abc\ndef");
+    let len = rope.len();
+    let removed = rope.remove_range_returning_rope(0..len);
+    assert_eq!(&removed, "abc\ndef");
+    assert!(rope.is_empty());
+}
+
+#[test]
+fn remove_range_returning_rope_empty_range() {
+    let mut rope = Rope::from("abc\ndef");
+    let removed = rope.remove_range_returning_rope(2..2);
+    assert!(removed.is_empty());
+    assert_eq!(&rope, "abc\ndef");
+}
+
+#[test]
+fn remove_range_returning_rope_lines() {
+    let text = "aa\nbb\ncc\ndd";
+    let mut rope = Rope::from(text);
+    let removed = rope.remove_range_returning_rope(Line(1)..Line(3));
+    assert_eq!(&removed, "bb\ncc\ndd");
+    assert_eq!(&rope, "aa\n");
+    assert_eq!(rope, Rope::from(text).delete(Line(1)..Line(3)));
+
+    let mut rope = Rope::from("aa\nb") + Rope::from("b\ncc\ndd");
+    let removed = rope.remove_range_returning_rope(Line(1)..Line(1));
+    assert_eq!(&removed, "bb\n");
+    assert_eq!(&rope, "aa\ncc\ndd");
+}
+
+#[test]
+fn remove_range_returning_rope_keeps_depth() {
+    let mut rope = (0..64).map(|i| Rope::from(format!("{:02}", i)))
+                          .collect::<Rope>();
+    let depth = rope.shrink_leaf_fragmentation_report().depth;
+    for _ in 0..40 {
+        let removed = rope.remove_range_returning_rope(1..3);
+        assert_eq!(removed.len(), 2);
+    }
+    assert_eq!(rope.len(), 128 - 80);
+    assert!(rope.len_is_consistent());
+    assert!(rope.shrink_leaf_fragmentation_report().depth <= depth + 1);
+}
+
+#[test]
+fn nth_char_is_chars_nth() {
+    let rope = Rope::from("aé") + Rope::from("老\n") + Rope::from("💖b") +
+               Rope::from("ö̲");
+    let n_chars = rope.chars().count();
+    for n in 0..n_chars + 2 {
+        assert_eq!(rope.nth_char(n), rope.chars().nth(n));
+    }
+    assert_eq!(rope.nth_char(n_chars), None);
+    assert_eq!(Rope::new().nth_char(0), None);
+}
+
+#[test]
+fn char_boundaries_are_char_metric_boundaries() {
+    use metric::{Char, Metric};
+    let rope = Rope::from("aé") + Rope::from("老\n");
+    for i in (0..rope.len() + 1).filter(|&i| rope.is_char_boundary(i)) {
+        assert!(Char::is_boundary(&rope, i));
+    }
+}
+
+#[test]
+fn push_rope_merges_small_leaves() {
+    let mut pushed = Rope::new();
+    let mut appended = Rope::new();
+    for _ in 0..100 {
+        pushed.push_rope(Rope::from("ab"));
+        appended = appended.append(&Rope::from("ab"));
+    }
+    let expected = "ab".repeat(100);
+    assert_eq!(pushed, expected);
+    assert_eq!(appended, expected);
+    // leaves are merged up to the threshold, rather than one leaf per push
+    assert_eq!(pushed.strings().count(), 200 / 64 + 1);
+    assert_eq!(appended.strings().count(), 100);
+}
+
+#[test]
+fn push_rope_does_not_merge_across_lines() {
+    let mut rope = Rope::new();
+    for _ in 0..10 {
+        rope.push_rope(Rope::from("ab\n"));
+    }
+    assert_eq!(rope.strings().count(), 10);
+    assert!(rope.strings().all(|s| s == "ab\n"));
+    assert_eq!(rope.lines().count(), 10);
+}
+
+#[test]
+fn push_rope_empty() {
+    let mut rope = Rope::from("abc");
+    rope.push_rope(Rope::new());
+    assert_eq!(&rope, "abc");
+    let mut rope = Rope::new();
+    rope.push_rope(Rope::from("abc"));
+    assert_eq!(&rope, "abc");
+}
+
+#[test]
+fn is_valid_utf8_detects_unchecked_corruption() {
+    // a lone continuation byte in the middle of the second line
+    let bytes = vec![b'o', b'k', b'\n', b'b', 0x80, b'd'];
+    let corrupt = unsafe { Rope::from_utf8_unchecked(bytes) };
+    assert!(!corrupt.is_valid_utf8());
+    let valid = unsafe { Rope::from_utf8_unchecked(b"ok\nbad".to_vec()) };
+    assert!(valid.is_valid_utf8());
+    assert!((valid + Rope::from("💖")).is_valid_utf8());
+}
+
+#[test]
+fn common_prefix_len_across_leaf_structures() {
+    let shared = "the quick brown fox jumps over the lazy dog\n".repeat(10);
+    let a = Rope::from(format!("{}ünique to a", shared));
+    let b = Rope::from(&shared[..7]) + Rope::from(&shared[7..]) +
+            Rope::from("üniversally b");
+    // "üni" is shared, so the prefix extends past the end of `shared`
+    let expected = shared.len() + "üni".len();
+    assert_eq!(a.common_prefix_len(&b), expected);
+    assert_eq!(b.common_prefix_len(&a), expected);
+    assert_eq!(a.common_prefix_len(&Rope::new()), 0);
+    assert_eq!(a.common_prefix_len(&a.clone()), a.len());
+}
+
+#[test]
+fn common_suffix_len_across_leaf_structures() {
+    let shared = "the quick brown fox jumps over the lazy dog\n".repeat(10);
+    let a = Rope::from(format!("prefix a{}", shared));
+    let b = Rope::from("a different prefix b") + Rope::from(&shared[..13]) +
+            Rope::from(&shared[13..]);
+    assert_eq!(a.common_suffix_len(&b), shared.len());
+    assert_eq!(b.common_suffix_len(&a), shared.len());
+    assert_eq!(a.common_suffix_len(&Rope::new()), 0);
+    assert_eq!(a.common_suffix_len(&a.clone()), a.len());
+}
+
+mod diff {
+    use Rope;
+
+    #[test]
+    fn insertion() {
+        let a = Rope::from("the fox\njumps");
+        let b = Rope::from("the quick ") + Rope::from("fox\njumps");
+        assert_eq!(a.diff(&b), (4, 0, 6));
+    }
+
+    #[test]
+    fn deletion() {
+        let a = Rope::from("the quick ") + Rope::from("fox\njumps");
+        let b = Rope::from("the fox\njumps");
+        assert_eq!(a.diff(&b), (4, 6, 0));
+    }
+
+    #[test]
+    fn replacement() {
+        let a = Rope::from("the quick fox\n") + Rope::from("jumps");
+        let b = Rope::from("the slow fox\njumps");
+        assert_eq!(a.diff(&b), (4, 5, 4));
+    }
+
+    #[test]
+    fn repeated_text_does_not_overlap() {
+        let a = Rope::from("aaa");
+        let b = Rope::from("aaaaa");
+        assert_eq!(a.diff(&b), (3, 0, 2));
+        assert_eq!(b.diff(&a), (3, 2, 0));
+    }
+
+    #[test]
+    fn multibyte_replacement_is_char_aligned() {
+        // "é" and "è" share their first byte, so the changed region must be
+        // widened to cover whole characters
+        let a = Rope::from("caf") + Rope::from("é au lait");
+        let b = Rope::from("café au lait".replace('é', "è"));
+        let (prefix, a_mid, b_mid) = a.diff(&b);
+        assert_eq!((prefix, a_mid, b_mid), (3, 2, 2));
+        assert!(a.is_char_boundary(prefix + a_mid));
+        assert!(b.is_char_boundary(prefix + b_mid));
+    }
+
+    #[test]
+    fn identical_and_empty() {
+        let a = Rope::from("same\nsame");
+        assert_eq!(a.diff(&a.clone()), (a.len(), 0, 0));
+        assert_eq!(a.diff(&Rope::new()), (0, a.len(), 0));
+        assert_eq!(Rope::new().diff(&a), (0, 0, a.len()));
+    }
+}
+
+mod apply_edit {
+    use Rope;
+
+    #[test]
+    fn inverse_restores_multibyte_rope() {
+        let original = Rope::from("Löwe 老虎\n") + Rope::from("Léopard 💖\n") +
+                       Rope::from("tiger");
+        let mut rope = original.clone();
+        // replace "老虎\nLé" with something of a different length
+        let undo = rope.apply_edit(6..16, Rope::from("🐯\nÉ"));
+        assert_eq!(&rope, "Löwe 🐯\nÉopard 💖\ntiger");
+        assert_eq!(undo.range, 6..6 + "🐯\nÉ".len());
+        assert_eq!(&undo.replacement, "老虎\nLé");
+
+        let redo = rope.apply(undo);
+        assert_eq!(rope, original);
+        assert_eq!(rope.lines().count(), original.lines().count());
+
+        rope.apply(redo);
+        assert_eq!(&rope, "Löwe 🐯\nÉopard 💖\ntiger");
+    }
+
+    #[test]
+    fn insertion_and_deletion_round_trip() {
+        let original = Rope::from("abc\ndéf");
+        let mut rope = original.clone();
+        let undo = rope.apply_edit(4..4, Rope::from("ü\n"));
+        assert_eq!(&rope, "abc\nü\ndéf");
+        rope.apply(undo);
+        assert_eq!(rope, original);
+
+        let undo = rope.apply_edit(0..rope.len(), Rope::new());
+        assert!(rope.is_empty());
+        assert_eq!(undo.range, 0..0);
+        rope.apply(undo);
+        assert_eq!(rope, original);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn mid_codepoint_panics() {
+        let mut rope = Rope::from("déf");
+        rope.apply_edit(2..3, Rope::new());
+    }
+}
+
+#[test]
+fn chars_len_utf16_is_encode_utf16_len() {
+    let bmp = "Löwe 老虎 Léopard\n";
+    let astral = "💖🐯\n𝕬𝖓 𝖗𝖔𝖕𝖊";
+    let rope = Rope::from(bmp) + Rope::from(astral) + Rope::from(bmp);
+    let string = format!("{}{}{}", bmp, astral, bmp);
+    assert_eq!(rope.chars_len_utf16(), string.encode_utf16().count());
+    assert_eq!(Rope::from(bmp).chars_len_utf16(), bmp.chars().count());
+    assert_eq!( Rope::from(astral).chars_len_utf16()
+              , astral.chars().map(char::len_utf16).sum::<usize>());
+    assert_eq!(Rope::from("💖").chars_len_utf16(), 2);
+}
+
+mod trim_end_matches {
+    use Rope;
+
+    #[test]
+    fn repeated_multi_char_suffix() {
+        let rope = Rope::from("abc💖ab💖ab💖ab");
+        assert_eq!(&rope.trim_end_matches("💖ab"), "abc");
+        assert_eq!(&rope.trim_end_matches("b💖a"), "abc💖ab💖ab💖ab");
+        assert_eq!(&rope.trim_end_matches("ab"), "abc💖ab💖ab💖");
+    }
+
+    #[test]
+    fn suffix_spanning_leaves() {
+        let rope = Rope::from("text\n") + Rope::from("\r\n\r") +
+                   Rope::from("\n") + Rope::from("\r\n");
+        assert_eq!(&rope.trim_end_matches("\r\n"), "text\n");
+        assert_eq!(&rope.trim_end_matches("\n"), "text\n\r\n\r\n\r");
+        let rope = Rope::from("xyz") + Rope::from("xy") + Rope::from("zx") +
+                   Rope::from("yz");
+        assert_eq!(&rope.trim_end_matches("xyz"), "");
+    }
+
+    #[test]
+    fn no_match_or_empty() {
+        let rope = Rope::from("hello\nworld");
+        assert_eq!(&rope.trim_end_matches("xyz"), "hello\nworld");
+        assert_eq!(&rope.trim_end_matches(""), "hello\nworld");
+        assert_eq!(&rope.trim_end_matches("a longer pattern than the rope")
+                  , "hello\nworld");
+        assert_eq!(&Rope::new().trim_end_matches("a"), "");
+    }
+}
+
+#[test]
+fn first_and_last_char() {
+    assert_eq!(Rope::new().first_char(), None);
+    assert_eq!(Rope::new().last_char(), None);
+    assert_eq!(Rope::from("").first_char(), None);
+
+    let single = Rope::from("💖");
+    assert_eq!(single.first_char(), Some('💖'));
+    assert_eq!(single.last_char(), Some('💖'));
+
+    let rope = Rope::from("老虎\n") + Rope::from("Léopard\n") +
+               Rope::from("Löwe é");
+    assert_eq!(rope.first_char(), Some('老'));
+    assert_eq!(rope.last_char(), Some('é'));
+
+    // empty leaves at either end are skipped over
+    let rope = Rope::new() + Rope::from("ü") + Rope::from("ö") + Rope::new();
+    assert_eq!(rope.first_char(), Some('ü'));
+    assert_eq!(rope.last_char(), Some('ö'));
+}
+
+#[test]
+fn insert_rope_into_itself() {
+    let rope = Rope::from("Löwe\n") + Rope::from("老虎\n") +
+               Rope::from("Léopard");
+    let string = rope.to_string();
+    let mid = "Löwe\n老".len();
+    let doubled = rope.insert_rope(mid, &rope.clone());
+    let expected = format!("{}{}{}", &string[..mid], string, &string[mid..]);
+    assert_eq!(doubled, expected);
+    assert!(doubled.len_is_consistent());
+    // the original, which now shares nodes with `doubled`, is unchanged
+    assert_eq!(rope, string);
+
+    // inserting the result into itself again keeps both correct
+    let quadrupled = doubled.insert_rope(doubled.len() / 2, &doubled);
+    assert_eq!(quadrupled.len(), 4 * string.len());
+    assert_eq!(doubled, expected);
+    assert_eq!(rope, string);
+    let (first, second) = quadrupled.split(doubled.len() / 2);
+    assert_eq!(first.append(&second.split(doubled.len()).1), doubled);
+    assert!(quadrupled.len_is_consistent());
+}
+
+#[test]
+fn as_str_single_leaf() {
+    assert_eq!(Rope::new().as_str(), Some(""));
+    assert_eq!(Rope::from("Löwe 老虎").as_str(), Some("Löwe 老虎"));
+    assert_eq!(Rope::from(String::from("no line break")).as_str()
+              , Some("no line break"));
+}
+
+#[test]
+fn as_str_multi_leaf() {
+    assert_eq!((Rope::from("Löwe ") + Rope::from("老虎")).as_str(), None);
+    assert_eq!(Rope::from("two\nlines").as_str(), None);
+    // a trailing line break is followed by an empty leaf
+    assert_eq!(Rope::from("one line\n").as_str(), None);
+    assert_eq!(Rope::from("abc").insert(1, 'ö').as_str(), None);
+}
+
+#[test]
+fn collect_grapheme_offsets_matches_grapheme_indices() {
+    let rope = Rope::from("a̐éö̲\r\n") + Rope::from("Löwe 老虎 💖\n") +
+               Rope::from("Léopard");
+    let offsets = rope.collect_grapheme_offsets();
+    let mut expected = rope.grapheme_indices()
+                           .map(|(i, _)| i)
+                           .collect::<Vec<_>>();
+    expected.push(rope.len());
+    assert_eq!(offsets, expected);
+    assert_eq!(offsets.last(), Some(&rope.len()));
+    assert!(offsets.iter().all(|&i| rope.is_char_boundary(i)));
+    // consecutive offsets delimit each grapheme
+    for (w, (_, g)) in offsets.windows(2).zip(rope.grapheme_indices()) {
+        assert_eq!(w[1] - w[0], g.len());
+    }
+}
+
+#[test]
+fn collect_grapheme_offsets_with_clusters_spanning_leaves() {
+    // "e\u{301}" is one grapheme cluster, split across the two leaves
+    let rope = Rope::from("ae") + Rope::from("\u{301}x");
+    let offsets = rope.collect_grapheme_offsets();
+    assert_eq!(offsets, vec![0, 1, 4, 5]);
+    assert_eq!( offsets.len() - 1
+              , rope.count_graphemes_in_range(0..rope.len()));
+}
+
+#[test]
+fn byte_index_to_position_round_trips() {
+    let string = "Löwe 老虎\n\nLéopard 💖\r\ntiger";
+    let rope = Rope::from("Löwe ") + Rope::from("老虎\n\nLéo") +
+               Rope::from("pard 💖\r\ntiger");
+    for (i, _) in string.char_indices().chain(Some((string.len(), ' '))) {
+        let (line, column) = rope.byte_index_to_position(i);
+        let line_start = string[..i].rfind('\n').map(|j| j + 1).unwrap_or(0);
+        assert_eq!(line, Line(string[..i].matches('\n').count()));
+        assert_eq!(column, string[line_start..i].chars().count());
+        // and back again
+        let back = rope.root.line_start(line).unwrap() +
+                   string[line_start..].chars().take(column)
+                                       .map(char::len_utf8).sum::<usize>();
+        assert_eq!(back, i);
+    }
+}
+
+#[test]
+#[should_panic(expected = "not a char boundary")]
+fn byte_index_to_position_mid_codepoint() {
+    Rope::from("Löwe").byte_index_to_position(2);
+}
+
+#[test]
+fn fragmentation_report_reflects_tiny_leaves() {
+    let whole = Rope::from("a".repeat(100));
+    let report = whole.shrink_leaf_fragmentation_report();
+    assert_eq!(report.leaf_count, 1);
+    assert_eq!(report.min_leaf_len, 100);
+    assert_eq!(report.max_leaf_len, 100);
+    assert_eq!(report.depth, 0);
+
+    // the same text, built one character at a time
+    let fragmented = (0..100).fold(Rope::new(), |r, _| r + Rope::from("a"));
+    assert_eq!(fragmented, whole);
+    let report = fragmented.shrink_leaf_fragmentation_report();
+    assert_eq!(report.leaf_count, 100);
+    assert_eq!(report.min_leaf_len, 1);
+    assert_eq!(report.max_leaf_len, 1);
+    assert_eq!(report.mean_leaf_len, 1.0);
+    assert!(report.depth >= 7);
+
+    let report = Rope::new().shrink_leaf_fragmentation_report();
+    assert_eq!(report.leaf_count, 0);
+    assert_eq!(report.min_leaf_len, 0);
+    assert_eq!(report.mean_leaf_len, 0.0);
+}
+
+mod with_delete {
+    use Rope;
+    use metric::Char;
+
+    #[test]
+    fn leaves_self_unchanged() {
+        let rope = Rope::from("Löwe 老虎\n") + Rope::from("Léopard\n") +
+                   Rope::from("tiger");
+        let before = rope.to_string();
+        let deleted = rope.with_delete(3..6);
+        assert_eq!(&deleted, "Lö老虎\nLéopard\ntiger");
+        assert_eq!(rope, before);
+        assert!(deleted.len_is_consistent());
+    }
+
+    #[test]
+    fn by_char() {
+        let rope = Rope::from("Löwe\n老虎");
+        let deleted = rope.with_delete(Char(1)..Char(6));
+        assert_eq!(&deleted, "L虎");
+        assert_eq!(&rope, "Löwe\n老虎");
+    }
+
+    #[test]
+    fn whole_and_empty_ranges() {
+        let rope = Rope::from("abc\ndef");
+        assert_eq!(&rope.with_delete(0..rope.len()), "");
+        assert_eq!(&rope.with_delete(2..2), "abc\ndef");
+        assert_eq!(&rope, "abc\ndef");
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        Rope::from("abc").with_delete(1..10);
+    }
+}
+
+mod persistent_edits {
+    use Rope;
+
+    fn original() -> Rope {
+        Rope::from("Löwe 老虎\n") + Rope::from("Léopard\n") + Rope::from("tiger")
+    }
+
+    #[test]
+    fn with_replace_range_leaves_self_unchanged() {
+        let rope = original();
+        let replaced = rope.with_replace_range(6..12, "🐯");
+        assert_eq!(&replaced, "Löwe 🐯\nLéopard\ntiger");
+        assert_eq!(rope, original());
+        let inserted = rope.with_replace_range(0..0, "ä ");
+        assert_eq!(&inserted, "ä Löwe 老虎\nLéopard\ntiger");
+        assert_eq!(rope, original());
+    }
+
+    #[test]
+    fn with_truncate_leaves_self_unchanged() {
+        let rope = original();
+        assert_eq!(&rope.with_truncate(9), "Löwe 老");
+        assert_eq!(&rope.with_truncate(0), "");
+        assert_eq!(rope.with_truncate(rope.len() + 1), original());
+        assert_eq!(rope, original());
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn with_truncate_mid_codepoint() {
+        original().with_truncate(7);
+    }
+
+    #[test]
+    fn with_split_off_leaves_self_unchanged() {
+        let rope = original();
+        let (left, right) = rope.with_split_off(13);
+        assert_eq!(&left, "Löwe 老虎\n");
+        assert_eq!(&right, "Léopard\ntiger");
+        assert_eq!(rope, original());
+        let (left, right) = rope.with_split_off(rope.len());
+        assert_eq!(left, original());
+        assert!(right.is_empty());
+    }
+}
+
+mod join_with_rope {
+    use Rope;
+
+    fn naive_join(sep: &Rope, pieces: &[Rope]) -> Rope {
+        pieces.iter().enumerate().fold(Rope::new(), |acc, (i, piece)|
+            if i == 0 { acc.append(piece) }
+            else { acc.append(sep).append(piece) })
+    }
+
+    #[test]
+    fn zero_pieces() {
+        let joined = Rope::join_with_rope(&Rope::from(", "), &[]);
+        assert!(joined.is_empty());
+    }
+
+    #[test]
+    fn one_piece() {
+        let pieces = [Rope::from("Löwe")];
+        assert_eq!(&Rope::join_with_rope(&Rope::from(", "), &pieces), "Löwe");
+    }
+
+    #[test]
+    fn many_pieces_match_naive_fold() {
+        let sep = Rope::from("\n");
+        let pieces = (0..100).map(|i| Rope::from(format!("line {} 老虎", i)))
+                             .collect::<Vec<_>>();
+        let joined = Rope::join_with_rope(&sep, &pieces);
+        let naive = naive_join(&sep, &pieces);
+        assert_eq!(joined, naive);
+        assert!(joined.equal_content_ignoring_structure(&naive));
+        assert!(joined.len_is_consistent());
+        // 199 subtrees (of depth at most 1) joined into a balanced tree,
+        // rather than a 199-deep one
+        let report = joined.shrink_leaf_fragmentation_report();
+        assert!(report.depth <= 8 + 1, "depth was {}", report.depth);
+        if cfg!(not(feature = "rebalance")) {
+            assert!(naive.shrink_leaf_fragmentation_report().depth > 100);
+        }
+    }
+
+    #[test]
+    fn empty_separator_and_pieces() {
+        let pieces = [Rope::from("a"), Rope::new(), Rope::from("b")];
+        assert_eq!(&Rope::join_with_rope(&Rope::new(), &pieces), "ab");
+        assert_eq!(&Rope::join_with_rope(&Rope::from("-"), &pieces), "a--b");
+    }
+}
+
+mod byte_search_with_limit {
+    use Rope;
+
+    fn rope() -> Rope {
+        Rope::from("Löwe 老虎\n") + Rope::from("tiger Löwe\n") +
+        Rope::from("Lö") + Rope::from("we tiger")
+    }
+
+    #[test]
+    fn excludes_match_outside_range() {
+        let rope = rope();
+        let string = rope.to_string();
+        let first = string.find("tiger").unwrap();
+        let second = string.rfind("tiger").unwrap();
+        assert_eq!( rope.byte_search_with_limit("tiger", 0, rope.len())
+                  , Some(first));
+        // start the range after the first match
+        assert_eq!( rope.byte_search_with_limit("tiger", first + 1, rope.len())
+                  , Some(second));
+        // end the range before the first match is complete
+        assert_eq!( rope.byte_search_with_limit("tiger", 0, first + 4)
+                  , None);
+    }
+
+    #[test]
+    fn match_spanning_leaves() {
+        let rope = rope();
+        let string = rope.to_string();
+        let last = string.rfind("Löwe").unwrap();
+        assert_eq!( rope.byte_search_with_limit("Löwe", 14, rope.len())
+                  , string[14..].find("Löwe").map(|i| i + 14));
+        assert_eq!( rope.byte_search_with_limit("Löwe", last, last + 5)
+                  , Some(last));
+        assert_eq!( rope.byte_search_with_limit("Löwe", last, last + 4)
+                  , None);
+    }
+
+    #[test]
+    fn empty_pattern_and_range() {
+        let rope = rope();
+        assert_eq!(rope.byte_search_with_limit("", 3, 3), Some(3));
+        assert_eq!(rope.byte_search_with_limit("L", 3, 3), None);
+        assert_eq!(rope.byte_search_with_limit("xyz", 0, rope.len()), None);
+    }
+}
+
+#[test]
+fn char_indices_round_trip() {
+    let rope = Rope::from("Löwe 老虎\n") + Rope::from("Léopard 💖\n") +
+               Rope::from("tiger");
+    let rebuilt = rope.char_indices().collect::<Rope>();
+    assert_eq!(rebuilt, rope);
+    assert!(rebuilt.equal_content_ignoring_structure(&rope));
+    assert!(Rope::new().char_indices().collect::<Rope>().is_empty());
+}
+
+#[test]
+fn find_char_matches_naive_scan() {
+    let rope = Rope::from("Löwe 老虎\n") + Rope::from("Léopard 💖\n") +
+               Rope::from("tiger, lion");
+    let string = rope.to_string();
+    for c in "Löwe 老虎\nLéopard💖tign,xyzé".chars() {
+        let naive = string.char_indices()
+                          .find(|&(_, x)| x == c)
+                          .map(|(i, _)| i);
+        assert_eq!(rope.find_char(c), naive, "searching for {:?}", c);
+    }
+    // matches in later leaves are offset by the lengths of earlier leaves
+    assert_eq!(rope.find_char(','), Some(string.find(',').unwrap()));
+    assert_eq!(Rope::new().find_char('a'), None);
+}
+
+mod normalize_newlines {
+    use Rope;
+    use LineEnding;
+
+    fn mixed() -> Rope {
+        // the second `\r\n` is split across two leaves
+        Rope::from("one\r\ntwo\r") + Rope::from("\nthree\rfour\n") +
+        Rope::from("\r\rfive\r")
+    }
+
+    #[test]
+    fn to_lf() {
+        let normalized = mixed().normalize_newlines(LineEnding::Lf);
+        assert_eq!(&normalized, "one\ntwo\nthree\nfour\n\n\nfive\n");
+        assert!(!normalized.chars().any(|c| c == '\r'));
+        assert_eq!(normalized.lines().count(), 7);
+    }
+
+    #[test]
+    fn to_crlf() {
+        let normalized = mixed().normalize_newlines(LineEnding::CrLf);
+        assert_eq!( &normalized
+                  , "one\r\ntwo\r\nthree\r\nfour\r\n\r\n\r\nfive\r\n");
+    }
+
+    #[test]
+    fn to_cr() {
+        let normalized = mixed().normalize_newlines(LineEnding::Cr);
+        assert_eq!(&normalized, "one\rtwo\rthree\rfour\r\r\rfive\r");
+        assert!(!normalized.chars().any(|c| c == '\n'));
+    }
+
+    #[test]
+    fn round_trips() {
+        let lf = mixed().normalize_newlines(LineEnding::Lf);
+        for &to in &[LineEnding::Lf, LineEnding::CrLf, LineEnding::Cr] {
+            let converted = lf.normalize_newlines(to);
+            assert_eq!(converted.normalize_newlines(LineEnding::Lf), lf);
+            // normalizing is idempotent
+            assert_eq!(converted.normalize_newlines(to), converted);
+        }
+    }
+
+    #[test]
+    fn no_line_endings() {
+        let rope = Rope::from("Löwe 老虎");
+        assert_eq!(rope.normalize_newlines(LineEnding::CrLf), rope);
+        assert!(Rope::new().normalize_newlines(LineEnding::Cr).is_empty());
+    }
+
+    #[test]
+    fn many_lines_stay_balanced() {
+        let rope = Rope::from("line\r\n".repeat(1000));
+        let normalized = rope.normalize_newlines(LineEnding::Lf);
+        assert_eq!(normalized, Rope::from("line\n".repeat(1000)));
+        // one piece for each line and each line ending
+        assert!(normalized.shrink_leaf_fragmentation_report().depth <= 12);
+    }
+}
+
+#[test]
+fn count_lines_in_range_matches_str() {
+    let string = "Löwe\n老虎\n\nLéopard 💖\ntiger\n\nlion";
+    let rope = Rope::from("Löwe\n老") + Rope::from("虎\n\nLéopard 💖") +
+               Rope::from("\ntiger\n\nlion");
+    let boundaries = string.char_indices()
+                           .map(|(i, _)| i)
+                           .chain(Some(string.len()))
+                           .collect::<Vec<_>>();
+    for &start in &boundaries {
+        for &end in boundaries.iter().filter(|&&end| end >= start) {
+            assert_eq!( rope.count_lines_in_range(start..end)
+                      , string[start..end].matches('\n').count()
+                      , "range {}..{}", start, end);
+        }
+    }
+}
+
+#[test]
+fn byte_slice_at_returns_containing_leaf() {
+    let rope = Rope::from("Löwe ") + Rope::from("老虎\n") + Rope::from("tiger");
+    let leaves = ["Löwe ", "老虎\n", "tiger"];
+    let mut offset = 0;
+    for leaf in &leaves {
+        for i in 0..leaf.len() {
+            assert_eq!(rope.byte_slice_at(offset + i), Some((*leaf, i)));
+        }
+        offset += leaf.len();
+    }
+    assert_eq!(rope.byte_slice_at(rope.len()), None);
+    assert_eq!(rope.byte_slice_at(rope.len() + 10), None);
+    assert_eq!(Rope::new().byte_slice_at(0), None);
+}
+
+#[test]
+fn append_all_matches_sequential_append() {
+    let rope = Rope::from("Löwe 老虎\n");
+    let ropes = (0..200).map(|i| Rope::from(format!("line {}\n", i)))
+                        .collect::<Vec<_>>();
+    let sequential = ropes.iter().fold(rope.clone(), |r, other| r.append(other));
+    let all = rope.append_all(ropes.iter().cloned());
+    assert_eq!(all, sequential);
+    assert!(all.equal_content_ignoring_structure(&sequential));
+    assert!(all.len_is_consistent());
+    // with the `rebalance` feature, appending already keeps trees balanced
+    if cfg!(not(feature = "rebalance")) {
+        assert!( all.shrink_leaf_fragmentation_report().depth <
+                 sequential.shrink_leaf_fragmentation_report().depth / 10 );
+    }
+    assert_eq!(&rope, "Löwe 老虎\n");
+
+    assert_eq!(rope.append_all(Vec::new()), rope);
+    assert_eq!(rope.append_all(vec![Rope::new(), Rope::new()]), rope);
+}
+
+mod retain_lines {
+    use Rope;
+
+    #[test]
+    fn filters_lines_matching_substring() {
+        let mut rope = Rope::from("keep: Löwe\ndrop: 老虎\n") +
+                       Rope::from("keep: Léopard\ndrop: tiger\nkeep: lion");
+        rope.retain_lines(|line| !line.to_string().contains("drop"));
+        assert_eq!(&rope, "keep: Löwe\nkeep: Léopard\nkeep: lion");
+        assert!(rope.len_is_consistent());
+    }
+
+    #[test]
+    fn trailing_line_keeps_its_ending() {
+        let mut rope = Rope::from("a\nb\nc\n");
+        rope.retain_lines(|line| line != "b");
+        assert_eq!(&rope, "a\nc\n");
+
+        // the last line has no ending, so it has none after filtering
+        let mut rope = Rope::from("a\nb\nc");
+        rope.retain_lines(|line| line != "a");
+        assert_eq!(&rope, "b\nc");
+        rope.retain_lines(|line| line != "c");
+        assert_eq!(&rope, "b\n");
+    }
+
+    #[test]
+    fn predicate_sees_each_line_once() {
+        let mut rope = Rope::from("one\n\nthree\n");
+        let mut seen = Vec::new();
+        rope.retain_lines(|line| { seen.push(line.to_string()); true });
+        assert_eq!(seen, vec!["one", "", "three"]);
+        assert_eq!(&rope, "one\n\nthree\n");
+    }
+
+    #[test]
+    fn remove_everything() {
+        let mut rope = Rope::from("one\ntwo");
+        rope.retain_lines(|_| false);
+        assert!(rope.is_empty());
+        let mut rope = Rope::new();
+        rope.retain_lines(|_| panic!("an empty rope has no lines"));
+        assert!(rope.is_empty());
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let mut rope = Rope::from("keep\r\ndrop\r\nkeep\r\n");
+        let mut seen = Vec::new();
+        rope.retain_lines(|line| {
+            seen.push(line.to_string());
+            line != "drop"
+        });
+        assert_eq!(seen, vec!["keep", "drop", "keep"]);
+        assert_eq!(&rope, "keep\r\nkeep\r\n");
+
+        // a `\r\n` split across leaves is still a single line ending
+        let mut rope = Rope::from("keep\r") + Rope::from("\ndrop\r\nkeep");
+        rope.retain_lines(|line| line != "drop");
+        assert_eq!(&rope, "keep\r\nkeep");
+        assert!(rope.len_is_consistent());
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        let mut rope = Rope::from("keep\rdrop\nkeep\r\ndrop\rkeep");
+        rope.retain_lines(|line| line != "drop");
+        assert_eq!(&rope, "keep\rkeep\r\nkeep");
+    }
+}
+
+#[test]
+fn char_indices_are_byte_offsets_across_leaves() {
+    let string = "Löwe 老虎\nLéopard 💖\ntiger";
+    let rope = Rope::from("Löwe ") + Rope::from("老虎\nLé") +
+               Rope::from("opard 💖\n") + Rope::from("tiger");
+    assert_eq!( rope.char_indices().collect::<Vec<_>>()
+              , string.char_indices().collect::<Vec<_>>());
+    assert_eq!(Rope::new().char_indices().count(), 0);
+
+    let slice = rope.slice_bytes(3..20);
+    assert_eq!( slice.char_indices().collect::<Vec<_>>()
+              , string[3..20].char_indices().collect::<Vec<_>>());
+}
+
+#[test]
+fn lines_with_multibyte_text() {
+    let string = "Löwe 老虎\nLéopard 💖\ntiger 🐯";
+    let rope = Rope::from("Löwe ") + Rope::from("老虎\nLé") +
+               Rope::from("opard 💖\n") + Rope::from("tiger 🐯");
+    assert_eq!( rope.lines().map(|l| l.to_string()).collect::<Vec<_>>()
+              , string.lines().collect::<Vec<_>>());
+}
+
+mod insert_at_grapheme {
+    use Rope;
+    use metric::Grapheme;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    fn check(rope: &Rope) {
+        let string = rope.to_string();
+        let graphemes = string.graphemes(true).collect::<Vec<_>>();
+        for i in 0..graphemes.len() + 1 {
+            let inserted = rope.insert(Grapheme(i), 'x');
+            let mut expected = graphemes.clone();
+            expected.insert(i, "x");
+            assert_eq!(inserted, expected.concat(), "inserting at {}", i);
+            // every original cluster is still intact
+            assert_eq!( inserted.graphemes().collect::<Vec<_>>(), expected
+                      , "inserting at {}", i);
+            let inserted = rope.insert_str(Grapheme(i), "yz");
+            let mut expected = graphemes.clone();
+            expected.insert(i, "yz");
+            assert_eq!(inserted, expected.concat(), "inserting at {}", i);
+        }
+    }
+
+    #[test]
+    fn single_leaf() {
+        check(&Rope::from("a̐éö̲"));
+    }
+
+    #[test]
+    fn multiple_leaves() {
+        check(&(Rope::from("a̐é") + Rope::from("ö̲") + Rope::from("a̐éö̲")));
+        check(&(Rope::from("a̐éö̲\r\n") + Rope::from("a̐éö̲")));
+    }
+}
+
+mod bytes_rev {
+    use Rope;
+
+    fn ropes() -> Vec<Rope> {
+        vec![ Rope::new()
+            , Rope::from("a")
+            , Rope::from("a̐éö̲\r\n")
+            , Rope::from("one\ntwo\n\nthree\n")
+            , Rope::from("left\n") + Rope::from("middle") + Rope::from("右")
+            ]
+    }
+
+    #[test]
+    fn matches_reversed_forward_bytes() {
+        for rope in ropes() {
+            let mut expected = rope.bytes().collect::<Vec<u8>>();
+            expected.reverse();
+            assert_eq!(rope.bytes_rev().collect::<Vec<u8>>(), expected);
+        }
+    }
+
+    #[test]
+    fn rev_of_bytes_rev_is_forward() {
+        for rope in ropes() {
+            assert_eq!( rope.bytes_rev().rev().collect::<Vec<u8>>()
+                      , rope.bytes().collect::<Vec<u8>>());
+        }
+    }
+
+    #[test]
+    fn both_ends_meet_in_the_middle() {
+        let rope = Rope::from("ab\ncd") + Rope::from("ef\ngh");
+        let mut iter = rope.bytes_rev();
+        assert_eq!(iter.len(), 10);
+        let mut back = Vec::new();
+        let mut front = Vec::new();
+        loop {
+            match (iter.next(), iter.next_back()) {
+                (None, None) => break
+              , (b, f) => { back.extend(b); front.extend(f) }
+            }
+        }
+        assert_eq!(&front[..], b"ab\ncd");
+        assert_eq!(&back[..], b"hg\nfe");
+        assert_eq!(iter.len(), 0);
+    }
+
+    quickcheck! {
+        fn prop_matches_reversed_forward_bytes(s: String) -> bool {
+            let rope = Rope::from(s.clone());
+            rope.bytes_rev().collect::<Vec<u8>>() ==
+                s.bytes().rev().collect::<Vec<u8>>()
+        }
+    }
+}
+
+mod try_slice {
+    use Rope;
+    use error::SliceError;
+    use metric::{Char, Grapheme};
+
+    #[test]
+    fn valid_ranges() {
+        let rope = Rope::from("Löwe ") + Rope::from("老虎 Léopard");
+        assert_eq!(&rope.try_slice(0..3).unwrap(), "Lö");
+        assert_eq!(&rope.try_slice(6..12).unwrap(), "老虎");
+        assert_eq!(&rope.try_slice(Char(5)..Char(7)).unwrap(), "老虎");
+        assert_eq!(&rope.try_slice(Grapheme(8)..Grapheme(15)).unwrap()
+                  , "Léopard");
+        assert_eq!(&rope.try_slice(Char(5)..Char(5)).unwrap(), "");
+        let len = rope.len();
+        assert_eq!(&rope.try_slice(0..len).unwrap(), &rope.to_string()[..]);
+    }
+
+    #[test]
+    fn start_after_end() {
+        let rope = Rope::from("Löwe 老虎");
+        let (start, end) = (3, 2);
+        assert_eq!( rope.try_slice(start..end).unwrap_err()
+                  , SliceError::StartAfterEnd { start: 3, end: 2 });
+        let (start, end) = (Char(4), Char(1));
+        assert_eq!( rope.try_slice(start..end).unwrap_err()
+                  , SliceError::StartAfterEnd { start: 4, end: 1 });
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let rope = Rope::from("Löwe 老虎");
+        assert_eq!( rope.try_slice(0..13).unwrap_err()
+                  , SliceError::OutOfBounds { index: 13, len: 12 });
+        assert_eq!( rope.try_slice(Char(2)..Char(8)).unwrap_err()
+                  , SliceError::OutOfBounds { index: 8, len: 7 });
+        assert!(Rope::new().try_slice(0..1).is_err());
+    }
+
+    #[test]
+    fn not_on_boundary() {
+        let rope = Rope::from("Löwe 老虎");
+        assert_eq!( rope.try_slice(2..6).unwrap_err()
+                  , SliceError::NotOnBoundary { index: 2 });
+        assert_eq!( rope.try_slice(6..10).unwrap_err()
+                  , SliceError::NotOnBoundary { index: 10 });
+    }
+}
+
+mod byte_to_grapheme_column {
+    use Rope;
+
+    #[test]
+    fn counts_clusters_not_chars() {
+        // "a̐" is 3 bytes and 2 chars, "o\u{301}\u{332}" is 5 bytes and 3
+        // chars, but each is a single grapheme cluster
+        let rope = Rope::from("x\na̐éo\u{301}\u{332}z\nlast");
+        // offsets of each cluster on line 1: 2, 5, 7, 12, 13
+        assert_eq!(rope.byte_to_grapheme_column(2), 0);
+        assert_eq!(rope.byte_to_grapheme_column(5), 1);
+        assert_eq!(rope.byte_to_grapheme_column(7), 2);
+        assert_eq!(rope.byte_to_grapheme_column(12), 3);
+        assert_eq!(rope.byte_to_grapheme_column(13), 4);
+        assert_eq!(rope.byte_to_grapheme_column(14), 0);
+        assert_eq!(rope.byte_to_grapheme_column(rope.len()), 4);
+    }
+
+    #[test]
+    fn inside_a_cluster_is_that_clusters_column() {
+        let rope = Rope::from("x\na̐éo\u{301}\u{332}z\nlast");
+        // the combining marks of the third cluster start at bytes 8 and 10
+        assert_eq!(rope.byte_to_grapheme_column(8), 2);
+        assert_eq!(rope.byte_to_grapheme_column(10), 2);
+    }
+
+    #[test]
+    fn cluster_spanning_leaves() {
+        let rope = Rope::from("ab") + Rope::from("o") + Rope::from("\u{332}c");
+        assert_eq!(rope.byte_to_grapheme_column(2), 2);
+        assert_eq!(rope.byte_to_grapheme_column(5), 3);
+        assert_eq!(rope.byte_to_grapheme_column(rope.len()), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        Rope::from("abc").byte_to_grapheme_column(4);
+    }
+}
+
+mod extend_from_slices {
+    use Rope;
+
+    fn leaf_ptrs(rope: &Rope) -> Vec<*const u8> {
+        rope.strings().map(str::as_ptr).collect()
+    }
+
+    #[test]
+    fn appends_in_order() {
+        let source = Rope::from("alpha\nbeta\ngamma\ndelta\n");
+        let mut rope = Rope::from("> ");
+        rope.extend_from_slices(&[ source.slice_bytes(11..17)
+                                 , source.slice_bytes(2..8)
+                                 , source.slice_bytes(0..0)
+                                 , source.slice_bytes(17..source.len()) ]);
+        assert_eq!(&rope, "> gamma\npha\nbedelta\n");
+        assert!(rope.len_is_consistent());
+    }
+
+    #[test]
+    fn extend_empty_rope() {
+        let source = Rope::from("héllo\nwörld");
+        let mut rope = Rope::new();
+        rope.extend_from_slices(&[ source.slice_bytes(7..13)
+                                 , source.slice_bytes(0..7) ]);
+        assert_eq!(&rope, "wörldhéllo\n");
+        let mut rope = Rope::new();
+        rope.extend_from_slices(&[]);
+        assert!(rope.is_empty());
+    }
+
+    #[test]
+    fn whole_leaves_are_shared() {
+        let source = Rope::from("first\nsecond\nthird\nfourth\n");
+        let source_ptrs = leaf_ptrs(&source);
+        let mut rope = Rope::new();
+        // "second\nthird\n" covers two whole leaves
+        rope.extend_from_slices(&[source.slice_bytes(6..19)]);
+        assert_eq!(&rope, "second\nthird\n");
+        assert_eq!(leaf_ptrs(&rope), &source_ptrs[1..3]);
+    }
+
+    #[test]
+    fn only_partial_leaves_are_copied() {
+        let source = Rope::from("first\nsecond\nthird\nfourth\n");
+        let source_ptrs = leaf_ptrs(&source);
+        let mut rope = Rope::new();
+        // starts inside "first\n" and ends inside "fourth\n"
+        rope.extend_from_slices(&[source.slice_bytes(3..22)]);
+        assert_eq!(&rope, "st\nsecond\nthird\nfou");
+        let ptrs = leaf_ptrs(&rope);
+        assert_eq!(ptrs.len(), 4);
+        assert!(!source_ptrs.contains(&ptrs[0]));
+        assert_eq!(&ptrs[1..3], &source_ptrs[1..3]);
+        assert!(!source_ptrs.contains(&ptrs[3]));
+    }
+
+    #[test]
+    fn reorder_own_lines() {
+        let mut rope = Rope::from("one\ntwo\nthree\n");
+        let old = rope.clone();
+        rope = Rope::new();
+        rope.extend_from_slices(&[ old.slice_bytes(8..14)
+                                 , old.slice_bytes(4..8)
+                                 , old.slice_bytes(0..4) ]);
+        assert_eq!(&rope, "three\ntwo\none\n");
+        assert_eq!(&old, "one\ntwo\nthree\n");
+    }
+}
+
+mod swap_ranges {
+    use Rope;
+
+    #[test]
+    fn swap_two_lines() {
+        let rope = Rope::from("one\ntwo\nthree\nfour\n");
+        assert_eq!(&rope.swap_ranges(4..8, 8..14), "one\nthree\ntwo\nfour\n");
+        assert_eq!(&rope.swap_ranges(14..19, 0..4), "four\ntwo\nthree\none\n");
+        assert_eq!(&rope, "one\ntwo\nthree\nfour\n");
+    }
+
+    #[test]
+    fn swap_arbitrary_ranges() {
+        let text = "the quick brown fox jumps over the lazy dog";
+        let rope = Rope::from(text);
+        let expected = [ &text[..4], &text[35..39], &text[9..35]
+                       , &text[4..9], &text[39..] ].concat();
+        assert_eq!(&rope.swap_ranges(4..9, 35..39), &expected[..]);
+        assert!(rope.swap_ranges(4..9, 35..39).len_is_consistent());
+    }
+
+    #[test]
+    fn swap_multibyte_and_empty_ranges() {
+        let rope = Rope::from("Löwe 老虎 Léopard");
+        assert_eq!(&rope.swap_ranges(0..6, 6..12), "老虎Löwe  Léopard");
+        assert_eq!(&rope.swap_ranges(6..6, 13..21), "Löwe Léopard老虎 ");
+        assert_eq!(&rope.swap_ranges(0..0, 20..20), "Löwe 老虎 Léopard");
+    }
+
+    #[test]
+    #[should_panic(expected = "overlap")]
+    fn overlapping_ranges() {
+        Rope::from("abcdefgh").swap_ranges(1..4, 3..6);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn not_on_char_boundary() {
+        Rope::from("Löwe 老虎").swap_ranges(0..2, 6..9);
+    }
+}
+
+mod move_range {
+    use Rope;
+
+    #[test]
+    fn move_word_before() {
+        let rope = Rope::from("one two three four");
+        assert_eq!(&rope.move_range(8..14, 0), "three one two four");
+        assert_eq!(&rope.move_range(8..14, 4), "one three two four");
+        assert_eq!(&rope, "one two three four");
+    }
+
+    #[test]
+    fn move_word_after() {
+        let rope = Rope::from("one two three four");
+        assert_eq!(&rope.move_range(0..4, 8), "two one three four");
+        assert_eq!(&rope.move_range(0..4, rope.len()), "two three fourone ");
+        assert!(rope.move_range(0..4, 8).len_is_consistent());
+    }
+
+    #[test]
+    fn move_line_across_leaves() {
+        let rope = Rope::from("a\nb\nc\n") + Rope::from("d\n");
+        assert_eq!(&rope.move_range(0..2, 6), "b\nc\na\nd\n");
+        assert_eq!(&rope.move_range(6..8, 2), "a\nd\nb\nc\n");
+    }
+
+    #[test]
+    fn target_inside_range_is_noop() {
+        let rope = Rope::from("Löwe 老虎 Léopard");
+        assert_eq!(&rope.move_range(6..12, 6), "Löwe 老虎 Léopard");
+        assert_eq!(&rope.move_range(6..12, 9), "Löwe 老虎 Léopard");
+        assert_eq!(&rope.move_range(6..12, 12), "Löwe 老虎 Léopard");
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn target_not_on_char_boundary() {
+        Rope::from("Löwe 老虎").move_range(0..1, 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn noop_move_still_checks_range() {
+        Rope::from("abc").move_range(1..5, 2);
+    }
+}
+
+mod hash_content {
+    use Rope;
+
+    #[test]
+    fn independent_of_structure() {
+        let text = "first line\nsecond lïne\nthird 线\n";
+        let flat = Rope::from(text);
+        let pieces = Rope::from("first ") + Rope::from("line\nsecond l")
+                   + Rope::from("ïne\nthird 线") + Rope::from("\n");
+        let built = Rope::new().insert_str(0, "third 线\n")
+                               .insert_str(0, "first line\nsecond lïne\n");
+        assert_eq!(flat.hash_content(), pieces.hash_content());
+        assert_eq!(flat.hash_content(), built.hash_content());
+        assert_eq!(flat.hash_content(), Rope::from(text).hash_content());
+    }
+
+    #[test]
+    fn known_values() {
+        // reference values for 64-bit FNV-1a
+        assert_eq!(Rope::new().hash_content(), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(Rope::from("a").hash_content(), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!( (Rope::from("foo") + Rope::from("bar")).hash_content()
+                  , 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn differs_on_different_content() {
+        let a = Rope::from("abc\ndef");
+        assert!(a.hash_content() != Rope::from("abc\ndeg").hash_content());
+        assert!(a.hash_content() != Rope::from("abc\nde").hash_content());
+    }
+}
+
+mod unchecked_access {
+    use Rope;
+
+    fn ropes() -> Vec<Rope> {
+        vec![ Rope::from("a")
+            , Rope::from("Löwe 老虎 Léopard\n💖 heart")
+            , Rope::from("abc") + Rope::from("Löwe\n") + Rope::from("老虎")
+            ]
+    }
+
+    // safe wrappers which only pass the unchecked accessors indices that
+    // the checked accessors accept
+    fn byte_at(rope: &Rope, i: usize) -> Option<u8> {
+        if i < rope.len() { Some(unsafe { rope.byte_at_unchecked(i) }) }
+        else { None }
+    }
+
+    fn char_at(rope: &Rope, i: usize) -> Option<char> {
+        if i < rope.len() && rope.is_char_boundary(i) {
+            Some(unsafe { rope.char_at_unchecked(i) })
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn byte_at_agrees_with_checked() {
+        for rope in ropes() {
+            let bytes = rope.bytes().collect::<Vec<u8>>();
+            for i in 0..rope.len() + 2 {
+                assert_eq!(byte_at(&rope, i), rope.byte_at(i));
+                assert_eq!(rope.byte_at(i), bytes.get(i).cloned());
+            }
+        }
+    }
+
+    #[test]
+    fn char_at_agrees_with_checked() {
+        for rope in ropes() {
+            let string = rope.to_string();
+            for i in 0..rope.len() + 2 {
+                assert_eq!(char_at(&rope, i), rope.char_at(i));
+                let expected = string.get(i..).and_then(|s| s.chars().next());
+                assert_eq!(rope.char_at(i), expected);
+            }
+        }
+    }
+}
+
+mod grapheme_count_in_line {
+    use Rope;
+    use metric::Line;
+
+    #[test]
+    fn counts_graphemes_not_chars() {
+        let rope = Rope::from("plain\na\u{310}e\u{301}o\u{308}\u{332}\nend");
+        assert_eq!(rope.grapheme_count_in_line(Line(0)), 5);
+        assert_eq!(rope.grapheme_count_in_line(Line(1)), 3);
+        assert_eq!(rope.lines().nth(1).unwrap().chars().count(), 7);
+        assert_eq!(rope.grapheme_count_in_line(Line(2)), 3);
+    }
+
+    #[test]
+    fn line_split_across_leaves() {
+        let rope = Rope::from("x\nab") + Rope::from("o") + Rope::from("\u{332}c\n");
+        assert_eq!(rope.grapheme_count_in_line(Line(1)), 4);
+        assert_eq!(rope.grapheme_count_in_line(Line(2)), 0);
+    }
+
+    #[test]
+    fn line_endings_are_not_counted() {
+        let rope = Rope::from("ab\r\ncd\n\n");
+        assert_eq!(rope.grapheme_count_in_line(Line(0)), 2);
+        assert_eq!(rope.grapheme_count_in_line(Line(1)), 2);
+        assert_eq!(rope.grapheme_count_in_line(Line(2)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn line_out_of_bounds() {
+        Rope::from("one\ntwo").grapheme_count_in_line(Line(2));
+    }
+}
+
+mod dedup {
+    use Rope;
+
+    fn naive_dedup(s: &str) -> String {
+        let mut chars = s.chars().collect::<Vec<_>>();
+        chars.dedup();
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn single_leaf() {
+        let rope = Rope::from("bookkeeper  老老虎虎");
+        assert_eq!(&rope.dedup(), "bokeper 老虎");
+        assert_eq!(&Rope::new().dedup(), "");
+        assert_eq!(&Rope::from("abc").dedup(), "abc");
+    }
+
+    #[test]
+    fn runs_straddling_leaves() {
+        let rope = Rope::from("aa") + Rope::from("aab") + Rope::from("b")
+                 + Rope::from("bbc\n") + Rope::from("\n\n");
+        assert_eq!(&rope.dedup(), "abc\n");
+        assert!(rope.dedup().len_is_consistent());
+    }
+
+    #[test]
+    fn leaf_made_entirely_of_a_run() {
+        let rope = Rope::from("xy") + Rope::from("yyyy") + Rope::from("yz");
+        assert_eq!(&rope.dedup(), "xyz");
+        assert_eq!(rope.dedup().strings().count(), 2);
+    }
+
+    quickcheck! {
+        fn prop_matches_vec_dedup(a: String, b: String) -> bool {
+            let rope = Rope::from(a.clone()) + Rope::from(b.clone());
+            rope.dedup() == naive_dedup(&(a + &b))
+        }
+    }
+}
+
+mod rsplit {
+    use Rope;
+
+    fn inputs() -> Vec<(Rope, String)> {
+        let texts = [ "path/to/some/file.tar.gz"
+                    , "a--b----c--"
+                    , "--"
+                    , "no separators here"
+                    , "Löwe--老虎--Léopard"
+                    , ""
+                    ];
+        let mut inputs = texts.iter()
+                              .map(|t| (Rope::from(*t), t.to_string()))
+                              .collect::<Vec<_>>();
+        // a separator which spans a leaf boundary
+        inputs.push(( Rope::from("one-") + Rope::from("-two-")
+                                         + Rope::from("-three")
+                    , "one--two--three".to_string() ));
+        inputs
+    }
+
+    fn to_strings<'a, I>(pieces: I) -> Vec<String>
+    where I: Iterator<Item=::RopeSlice<'a>> {
+        pieces.map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn rsplit_once_matches_str() {
+        for (rope, string) in inputs() {
+            for pat in &["--", "/", ".", "老虎", "", "missing"] {
+                let expected = string.rsplit_once(pat)
+                                     .map(|(a, b)| (a.to_string(), b.to_string()));
+                let actual = rope.rsplit_once(*pat)
+                                 .map(|(a, b)| (a.to_string(), b.to_string()));
+                assert_eq!( actual, expected
+                          , "{:?}.rsplit_once({:?})", string, pat);
+            }
+            assert_eq!( rope.rsplit_once('-')
+                            .map(|(a, b)| (a.to_string(), b.to_string()))
+                      , string.rsplit_once('-')
+                              .map(|(a, b)| (a.to_string(), b.to_string())));
+        }
+    }
+
+    #[test]
+    fn rsplitn_matches_str() {
+        for (rope, string) in inputs() {
+            for n in 0..6 {
+                for pat in &["--", "/", ".", "老虎", "", "missing"] {
+                    assert_eq!( to_strings(rope.rsplitn(n, *pat))
+                              , string.rsplitn(n, pat)
+                                      .map(String::from)
+                                      .collect::<Vec<_>>()
+                              , "{:?}.rsplitn({}, {:?})", string, n, pat);
+                }
+                assert_eq!( to_strings(rope.rsplitn(n, '.'))
+                          , string.rsplitn(n, '.')
+                                  .map(String::from)
+                                  .collect::<Vec<_>>());
+                assert_eq!( to_strings(rope.rsplitn(n, char::is_whitespace))
+                          , string.rsplitn(n, char::is_whitespace)
+                                  .map(String::from)
+                                  .collect::<Vec<_>>());
+            }
+        }
+    }
+}
+
+mod pad {
+    use Rope;
+    use metric::{Grapheme, Measured};
+
+    #[test]
+    fn pads_to_grapheme_width() {
+        // 3 grapheme clusters, but 11 bytes and 6 chars
+        let rope = Rope::from("a\u{310}e\u{301}o\u{308}\u{332}");
+        assert_eq!(rope.len(), 11);
+        assert_eq!( &rope.pad_start(5, ' ')
+                  , "  a\u{310}e\u{301}o\u{308}\u{332}");
+        assert_eq!( &rope.pad_end(5, '·')
+                  , "a\u{310}e\u{301}o\u{308}\u{332}··");
+        let width: Grapheme = rope.pad_end(5, '·').measure();
+        assert_eq!(width, Grapheme(5));
+    }
+
+    #[test]
+    fn multibyte_fill_and_content() {
+        let rope = Rope::from("Löwe");
+        assert_eq!(&rope.pad_start(6, '老'), "老老Löwe");
+        assert_eq!(&rope.pad_end(5, '💖'), "Löwe💖");
+    }
+
+    #[test]
+    fn wide_enough_ropes_are_unchanged() {
+        let rope = Rope::from("abc") + Rope::from("def");
+        assert_eq!(&rope.pad_start(6, ' '), "abcdef");
+        assert_eq!(&rope.pad_end(2, ' '), "abcdef");
+        assert_eq!(&Rope::new().pad_start(0, ' '), "");
+        assert_eq!(&Rope::new().pad_end(2, 'x'), "xx");
+    }
+
+    #[test]
+    fn clusters_spanning_leaves_are_counted_once() {
+        // "e\u{301}" is one grapheme cluster, split across the two leaves
+        let rope = Rope::from("ae") + Rope::from("\u{301}x");
+        assert_eq!(&rope.pad_end(4, '.'), "ae\u{301}x.");
+        assert_eq!(&rope.pad_start(4, '.'), ".ae\u{301}x");
+        assert_eq!(&rope.pad_end(3, '.'), "ae\u{301}x");
+    }
+}
+
+mod byte_windows_around {
+    use Rope;
+
+    #[test]
+    fn window_in_the_middle() {
+        let rope = Rope::from("first line\nsecond line\n") + Rope::from("third");
+        assert_eq!(&rope.byte_windows_around(11, 5, 3), "line\nsec");
+        assert_eq!(&rope.byte_windows_around(11, 0, 0), "");
+    }
+
+    #[test]
+    fn clamped_near_the_start() {
+        let rope = Rope::from("Löwe 老虎");
+        assert_eq!(&rope.byte_windows_around(0, 5, 1), "L");
+        assert_eq!(&rope.byte_windows_around(1, 100, 0), "L");
+    }
+
+    #[test]
+    fn clamped_near_the_end() {
+        let rope = Rope::from("Löwe 老虎");
+        let len = rope.len();
+        assert_eq!(&rope.byte_windows_around(len, 3, 100), "虎");
+        assert_eq!(&rope.byte_windows_around(len + 10, 3, 3), "虎");
+        assert_eq!(&rope.byte_windows_around(3, 100, 100), "Löwe 老虎");
+    }
+
+    #[test]
+    fn snapped_to_char_boundaries() {
+        let rope = Rope::from("Löwe 老虎");
+        // byte 2 is inside 'ö', and byte 7 is inside '老'
+        assert_eq!(&rope.byte_windows_around(4, 2, 3), "öwe 老");
+        assert_eq!(&rope.byte_windows_around(2, 0, 0), "ö");
+        assert_eq!(&Rope::new().byte_windows_around(0, 3, 3), "");
+    }
+}
+
+mod count_graphemes_in_range {
+    use Rope;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    fn naive(rope: &Rope, start: usize, end: usize) -> usize {
+        rope.slice_bytes(start..end).to_string().graphemes(true).count()
+    }
+
+    #[test]
+    fn matches_slicing_on_multi_leaf_ropes() {
+        let rope = Rope::from("a\u{310}e\u{301}o\u{308}\u{332}\r\nline two\n")
+                 + Rope::from("Löwe 老虎 Léopard\n")
+                 + Rope::from("\u{1F1F7}\u{1F1FA}\u{1F1F8}\u{1F1F9}!");
+        let boundaries = (0..rope.len() + 1)
+            .filter(|&i| rope.is_char_boundary(i))
+            .collect::<Vec<_>>();
+        for &start in &boundaries {
+            for &end in boundaries.iter().filter(|&&end| end >= start) {
+                // only compare ranges which begin on a cluster boundary,
+                // since slicing mid-cluster starts a new cluster
+                if naive(&rope, 0, start) + naive(&rope, start, rope.len())
+                    == naive(&rope, 0, rope.len()) {
+                    assert_eq!( rope.count_graphemes_in_range(start..end)
+                              , naive(&rope, start, end)
+                              , "range {}..{}", start, end);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn whole_rope_matches_graphemes_count() {
+        let rope = Rope::from("one\n") + Rope::from("two̲\n")
+                 + Rope::from("\r\n") + Rope::from("three");
+        assert_eq!( rope.count_graphemes_in_range(0..rope.len())
+                  , rope.graphemes().count());
+        assert_eq!(rope.count_graphemes_in_range(3..3), 0);
+    }
+
+    #[test]
+    fn cluster_on_a_leaf_seam() {
+        let rope = Rope::from("ab") + Rope::from("e") + Rope::from("\u{301}x");
+        assert_eq!(rope.count_graphemes_in_range(0..rope.len()), 4);
+        assert_eq!(rope.count_graphemes_in_range(2..5), 1);
+        // the cluster "e\u{301}" begins before byte 3
+        assert_eq!(rope.count_graphemes_in_range(3..rope.len()), 1);
+        assert_eq!(rope.count_graphemes_in_range(0..3), 3);
+    }
+
+    #[test]
+    fn clusters_spanning_several_seams() {
+        // a flag split between two leaves, followed by one in the next leaf,
+        // and an emoji ZWJ sequence split across three leaves
+        let rope = Rope::from("x\u{1F1F7}") + Rope::from("\u{1F1FA}\u{1F1F8}")
+                 + Rope::from("\u{1F1F9}\u{1F468}") + Rope::from("\u{200D}")
+                 + Rope::from("\u{1F469}y");
+        let string = rope.to_string();
+        assert_eq!(rope.count_graphemes_in_range(0..rope.len()), 5);
+        for (start, _) in string.grapheme_indices(true) {
+            assert_eq!( rope.count_graphemes_in_range(start..rope.len())
+                      , naive(&rope, start, rope.len())
+                      , "range {}..", start);
+            assert_eq!( rope.count_graphemes_in_range(0..start)
+                      , naive(&rope, 0, start)
+                      , "range ..{}", start);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_bounds() {
+        Rope::from("abc").count_graphemes_in_range(1..4);
+    }
+}
+
+mod replace_all_chars {
+    use Rope;
+    use metric::{Char, Line, Measured};
+
+    fn check_metrics(rope: &Rope, expected: &str) {
+        assert_eq!(rope, expected);
+        assert!(rope.len_is_consistent());
+        assert_eq!(rope.len(), expected.len());
+        let chars: Char = rope.measure();
+        assert_eq!(chars, Char(expected.chars().count()));
+        let lines: Line = rope.measure();
+        assert_eq!(lines, Line(expected.matches('\n').count()));
+    }
+
+    #[test]
+    fn narrow_to_wide() {
+        let mut rope = Rope::from("a-b\n") + Rope::from("c-d-e");
+        // force the cached metrics to be computed before replacing
+        let _: Char = rope.measure();
+        rope.replace_all_chars('-', '老');
+        check_metrics(&rope, "a老b\nc老d老e");
+    }
+
+    #[test]
+    fn wide_to_narrow() {
+        let mut rope = Rope::from("老虎老虎\n") + Rope::from("老");
+        let _: Char = rope.measure();
+        rope.replace_all_chars('老', 'x');
+        check_metrics(&rope, "x虎x虎\nx");
+    }
+
+    #[test]
+    fn same_width_in_place() {
+        let mut rope = Rope::from("a-b-c\n") + Rope::from("d-e");
+        let _: Char = rope.measure();
+        let ptrs = rope.strings().map(str::as_ptr).collect::<Vec<_>>();
+        rope.replace_all_chars('-', '+');
+        check_metrics(&rope, "a+b+c\nd+e");
+        // the leaves were overwritten rather than reallocated
+        assert_eq!(rope.strings().map(str::as_ptr).collect::<Vec<_>>(), ptrs);
+    }
+
+    #[test]
+    fn shared_leaves_are_not_mutated() {
+        let mut rope = Rope::from("a-b\n") + Rope::from("no dashes\n")
+                     + Rope::from("c-d");
+        let original = rope.clone();
+        let ptrs = original.strings().map(str::as_ptr).collect::<Vec<_>>();
+        rope.replace_all_chars('-', '+');
+        check_metrics(&rope, "a+b\nno dashes\nc+d");
+        assert_eq!(&original, "a-b\nno dashes\nc-d");
+        // the leaf without a dash is still shared with the original
+        let new_ptrs = rope.strings().map(str::as_ptr).collect::<Vec<_>>();
+        assert!(new_ptrs[0] != ptrs[0]);
+        assert_eq!(new_ptrs[1], ptrs[1]);
+        assert!(new_ptrs[2] != ptrs[2]);
+    }
+
+    #[test]
+    fn newlines() {
+        let mut rope = Rope::from("one two\nthree four");
+        rope.replace_all_chars(' ', '\n');
+        check_metrics(&rope, "one\ntwo\nthree\nfour");
+        assert_eq!(rope.lines().count(), 4);
+        rope.replace_all_chars('\n', ' ');
+        check_metrics(&rope, "one two three four");
+    }
+}
+
+mod with_byte_offsets {
+    use Rope;
+
+    fn ropes() -> Vec<Rope> {
+        vec![ Rope::new()
+            , Rope::from("a̐éö̲\r\n")
+            , Rope::from("Brr, it's 29.3°F!\n") + Rope::from("Löwe 老虎")
+                                              + Rope::from(" Léopard")
+            , Rope::from("one\ntwo\n\nthree")
+            ]
+    }
+
+    #[test]
+    fn chars_match_char_indices() {
+        for rope in ropes() {
+            assert_eq!( rope.with_byte_offsets(rope.chars()).collect::<Vec<_>>()
+                      , rope.char_indices().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn graphemes_match_grapheme_indices() {
+        for rope in ropes() {
+            assert_eq!( rope.with_byte_offsets(rope.graphemes())
+                            .collect::<Vec<_>>()
+                      , rope.grapheme_indices().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn word_bounds_match_split_word_bound_indices() {
+        for rope in ropes() {
+            assert_eq!( rope.with_byte_offsets(rope.split_word_bounds())
+                            .collect::<Vec<_>>()
+                      , rope.split_word_bound_indices().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn words_skip_text_between_them() {
+        for rope in ropes() {
+            for (offset, word) in rope.with_byte_offsets(rope.unicode_words()) {
+                assert_eq!(&rope.slice_bytes(offset..offset + word.len()), word);
+            }
+        }
+        let rope = Rope::from("The quick (\"brown\")\n") + Rope::from("fox");
+        let words = rope.with_byte_offsets(rope.unicode_words())
+                        .collect::<Vec<_>>();
+        assert_eq!( words
+                  , vec![(0, "The"), (4, "quick"), (12, "brown"), (20, "fox")]);
+    }
+
+    #[test]
+    fn foreign_items_follow_the_previous_item() {
+        let rope = Rope::from("ae") + Rope::from("\u{301}x");
+        let items = rope.with_byte_offsets(vec!["zz"].into_iter()
+                                                     .chain(rope.graphemes()))
+                        .collect::<Vec<_>>();
+        assert_eq!( items
+                  , vec![ (0, "zz"), (0, "a"), (1, "e"), (2, "\u{301}")
+                        , (4, "x") ]);
+
+        let items = rope.with_byte_offsets(rope.graphemes().take(2)
+                                               .chain(vec!["zz"])
+                                               .chain(rope.graphemes()
+                                                          .skip(2)))
+                        .collect::<Vec<_>>();
+        assert_eq!( items
+                  , vec![ (0, "a"), (1, "e"), (2, "zz"), (2, "\u{301}")
+                        , (4, "x") ]);
+    }
+}
+
+mod trim_indent {
+    use Rope;
+
+    #[test]
+    fn indented_block() {
+        let rope = Rope::from("    if x {\n        y();\n    } else {\n      z();\n    }");
+        assert_eq!( &rope.trim_indent()
+                  , "if x {\n    y();\n} else {\n  z();\n}");
+    }
+
+    #[test]
+    fn blank_lines_are_ignored() {
+        let rope = Rope::from("\n      first\n\n  \n        second\n   \n      third\n");
+        assert_eq!( &rope.trim_indent()
+                  , "\nfirst\n\n\n  second\n\nthird\n");
+        let trimmed = rope.trim_indent();
+        assert_eq!(trimmed.lines().count(), rope.lines().count());
+        assert!(trimmed.len_is_consistent());
+    }
+
+    #[test]
+    fn unindented_and_empty() {
+        let rope = Rope::from("no\n  indent\n");
+        assert_eq!(&rope.trim_indent(), "no\n  indent\n");
+        assert_eq!(&Rope::new().trim_indent(), "");
+        assert_eq!(&Rope::from("   \n  ").trim_indent(), "   \n  ");
+    }
+
+    #[test]
+    fn tabs_and_multi_leaf() {
+        let rope = Rope::from("\t\tLöwe\n") + Rope::from("\t\t\t老虎\n")
+                 + Rope::from("\t\tLéopard");
+        assert_eq!(&rope.trim_indent(), "Löwe\n\t老虎\nLéopard");
+    }
+}
+
+mod char_to_byte_table {
+    use Rope;
+    use metric::{Char, Measured};
+
+    #[test]
+    fn matches_per_char_conversion() {
+        let rope = Rope::from("a̐éö̲\r\n") + Rope::from("Löwe 老虎 Léopard\n")
+                 + Rope::from("🦁 lion");
+        let table = rope.char_to_byte_table();
+        let chars: Char = rope.measure();
+        assert_eq!(table.len(), chars.0 + 1);
+        for (n, &byte) in table.iter().enumerate().take(chars.0) {
+            assert_eq!(rope.to_byte_index(Char(n)), Some(byte));
+            assert_eq!(rope.nth_char(n), rope.char_at(byte));
+        }
+        assert_eq!(table.last(), Some(&rope.len()));
+    }
+
+    #[test]
+    fn ascii_is_identity() {
+        let rope = Rope::from("hello\nworld");
+        assert_eq!(rope.char_to_byte_table(), (0..12).collect::<Vec<_>>());
+    }
+}
+
+mod split_at_grapheme {
+    use Rope;
+
+    #[test]
+    fn at_each_grapheme() {
+        let text = "a̐éö̲";
+        let bytes = [0, 3, 5, 9];
+        for (n, &at) in bytes.iter().enumerate() {
+            let (left, right) = Rope::from(text).split_at_grapheme(n);
+            assert_eq!(&left, &text[..at]);
+            assert_eq!(&right, &text[at..]);
+            assert!(left.is_valid_utf8() && right.is_valid_utf8());
+            assert_eq!(&(left + right), text);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn past_the_end() {
+        Rope::from("a̐éö̲").split_at_grapheme(4);
+    }
+}
+
+mod from_lines {
+    use Rope;
+    use metric::{Line, Measured};
+
+    #[test]
+    fn lines_match_input() {
+        let input = vec!["Löwe", "", "老虎", "Léopard", "  lion"];
+        let rope = Rope::from_lines(&input);
+        let lines = rope.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+        assert_eq!(lines, input);
+        assert_eq!(&rope, &input.join("\n")[..]);
+        assert!(rope.len_is_consistent());
+    }
+
+    #[test]
+    fn line_metric() {
+        let input = [String::from("one"), String::from("two")
+                        , String::from("three")];
+        let joined = Rope::from_lines(input.iter());
+        let terminated = Rope::from_terminated_lines(input.iter());
+        let expected: Line = Rope::from(input.join("\n")).measure();
+        let measured: Line = joined.measure();
+        assert_eq!(measured, expected);
+        let expected: Line = Rope::from(input.join("\n") + "\n").measure();
+        let measured: Line = terminated.measure();
+        assert_eq!(measured, expected);
+        assert_eq!(&terminated, "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn empty_and_embedded_newlines() {
+        assert_eq!(&Rope::from_lines(Vec::<&str>::new()), "");
+        assert_eq!(&Rope::from_lines(vec![""]), "");
+        assert_eq!(&Rope::from_terminated_lines(vec![""]), "\n");
+        let rope = Rope::from_lines(vec!["a\nb", "c"]);
+        assert_eq!(&rope, "a\nb\nc");
+        assert_eq!(rope.lines().count(), 3);
+    }
+}
+
+mod byte_indices_of_char {
+    use Rope;
+
+    fn manual_scan(text: &str, c: char) -> Vec<usize> {
+        text.char_indices()
+            .filter(|&(_, ch)| ch == c)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn matches_manual_scan() {
+        let rope = Rope::from("老虎 and 老鼠\n") + Rope::from("lion, 老")
+                 + Rope::from("虎, tiger\n") + Rope::from("老");
+        let text = rope.to_string();
+        for &c in &['老', ',', '\n', ' ', '虎', 'x'] {
+            assert_eq!( rope.byte_indices_of_char(c).collect::<Vec<_>>()
+                      , manual_scan(&text, c));
+        }
+    }
+
+    #[test]
+    fn empty_rope() {
+        assert_eq!(Rope::new().byte_indices_of_char('a').count(), 0);
+    }
+}
+
+mod truncate_to_grapheme_width {
+    use Rope;
+
+    #[test]
+    fn never_splits_a_cluster() {
+        let rope = Rope::from("a̐éö̲\r\n");
+        let bounds = rope.collect_grapheme_offsets();
+        for width in 0..bounds.len() {
+            let clipped = rope.truncate_to_grapheme_width(width);
+            assert_eq!(clipped.len(), bounds[width]);
+            assert!(bounds.contains(&clipped.len()));
+        }
+        assert_eq!(&rope.truncate_to_grapheme_width(3), "a̐éö̲");
+    }
+
+    #[test]
+    fn across_leaves() {
+        let rope = Rope::from("Löwe ") + Rope::from("老虎");
+        assert_eq!(&rope.truncate_to_grapheme_width(6), "Löwe 老");
+        assert_eq!(&rope.truncate_to_grapheme_width(0), "");
+        assert_eq!(&Rope::new().truncate_to_grapheme_width(3), "");
+    }
+
+    #[test]
+    fn cluster_on_a_leaf_seam() {
+        let rope = Rope::from("ab") + Rope::from("e") + Rope::from("\u{301}x");
+        assert_eq!(&rope.truncate_to_grapheme_width(3), "abe\u{301}");
+        assert_eq!(&rope.truncate_to_grapheme_width(2), "ab");
+        assert_eq!(&rope.truncate_to_grapheme_width(4), "abe\u{301}x");
+    }
+}
+
+mod insert_newline {
+    use Rope;
+    use metric::{Line, Measured};
+
+    fn line_count(rope: &Rope) -> usize {
+        let Line(n) = rope.measure();
+        n
+    }
+
+    #[test]
+    fn increments_line_count() {
+        let rope = Rope::from("Löwe 老虎\nLéopard\n");
+        assert_eq!(line_count(&rope), 2);
+        for &at in &[0, 3, 6, 13, 14, rope.len()] {
+            let split = rope.insert_newline(at);
+            assert_eq!(line_count(&split), 3);
+            let text = rope.to_string();
+            let expected = format!("{}\n{}", &text[..at], &text[at..]);
+            assert_eq!(&split, &expected[..]);
+            assert!(split.len_is_consistent());
+        }
+        assert_eq!(line_count(&rope), 2);
+    }
+
+    #[test]
+    fn lines_reflect_split() {
+        let rope = Rope::from("hello world\n") + Rope::from("second");
+        let split = rope.insert_newline(5);
+        let lines = split.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+        assert_eq!(lines, vec!["hello", " world", "second"]);
+        let split = split.insert_newline(split.len());
+        assert_eq!(line_count(&split), 3);
+    }
+}
+
+mod byte_len_utf8_of_char_at {
+    use Rope;
+
+    #[test]
+    fn across_leaf_boundaries() {
+        let rope = Rope::from("aé") + Rope::from("老🦁") + Rope::from("z\n")
+                 + Rope::from("ö");
+        let widths = (0..8).map(|n| rope.byte_len_utf8_of_char_at(n))
+                           .collect::<Vec<_>>();
+        assert_eq!( widths
+                  , vec![ Some(1), Some(2), Some(3), Some(4), Some(1), Some(1)
+                        , Some(2), None ]);
+    }
+
+    #[test]
+    fn advancing_by_width_visits_every_char() {
+        let rope = Rope::from("Löwe ") + Rope::from("老虎\n") + Rope::from("🦁");
+        let mut offset = 0;
+        let mut n = 0;
+        while let Some(width) = rope.byte_len_utf8_of_char_at(n) {
+            assert!(rope.is_char_boundary(offset));
+            offset += width;
+            n += 1;
+        }
+        assert_eq!(offset, rope.len());
+        assert_eq!(n, rope.chars().count());
+        assert_eq!(Rope::new().byte_len_utf8_of_char_at(0), None);
+    }
+}
+
+mod fold_bytes {
+    use Rope;
+
+    fn rolling_sum(sum: u32, b: u8) -> u32 {
+        sum.wrapping_mul(31).wrapping_add(u32::from(b))
+    }
+
+    #[test]
+    fn matches_bytes_fold() {
+        let rope = Rope::from("Löwe 老虎\n") + Rope::from("Léopard\n")
+                 + Rope::from("") + Rope::from("🦁 lion");
+        let by_leaf = rope.fold_bytes(0, |sum, bytes| {
+            bytes.iter().fold(sum, |sum, &b| rolling_sum(sum, b))
+        });
+        assert_eq!(by_leaf, rope.bytes().fold(0, rolling_sum));
+    }
+
+    #[test]
+    fn visits_leaves_in_order() {
+        let rope = Rope::from("ab") + Rope::from("cd") + Rope::from("ef");
+        let leaves = rope.fold_bytes(Vec::new(), |mut leaves, bytes| {
+            leaves.push(bytes.to_vec());
+            leaves
+        });
+        assert_eq!(leaves.concat(), b"abcdef".to_vec());
+        assert_eq!(Rope::new().fold_bytes(0, |n, b| n + b.len()), 0);
+    }
+}
+
+mod chars_eq {
+    use Rope;
+
+    #[test]
+    fn compare_to_str_chars() {
+        let rope = Rope::from("a̐é") + Rope::from("ö̲\n") + Rope::from("老虎");
+        assert!(rope.chars_eq("a̐éö̲\n老虎".chars()));
+        assert!(!rope.chars_eq("a̐éö̲\n老鼠".chars()));
+        assert!(!rope.chars_eq("a̐éö̲\n老".chars()));
+        assert!(Rope::new().chars_eq("".chars()));
+        assert!(!Rope::new().chars_eq("x".chars()));
+    }
+
+    /// Yields the letters from `next` to `last`, without a backing string.
+    struct Letters { next: char, last: char }
+
+    impl Iterator for Letters {
+        type Item = char;
+        fn next(&mut self) -> Option<char> {
+            if self.next > self.last { return None }
+            let c = self.next;
+            self.next = (c as u8 + 1) as char;
+            Some(c)
+        }
+    }
+
+    #[test]
+    fn compare_to_custom_iterator() {
+        let rope = Rope::from("abc") + Rope::from("def");
+        assert!(rope.chars_eq(Letters { next: 'a', last: 'f' }));
+        assert!(!rope.chars_eq(Letters { next: 'a', last: 'g' }));
+        assert!(!rope.chars_eq(Letters { next: 'b', last: 'g' }));
+    }
+
+    #[test]
+    fn stops_at_first_mismatch() {
+        let rope = Rope::from("xyz");
+        let mut pulled = 0;
+        let other = "xqzzzzzz".chars().inspect(|_| pulled += 1);
+        assert!(!rope.chars_eq(other));
+        assert_eq!(pulled, 2);
+    }
+}
+
+mod graphemes_eq {
+    use Rope;
+
+    #[test]
+    fn agrees_with_byte_equality() {
+        let texts = [ "a̐éö̲\r\n", "a̐éö̲\n", "aéö̲\r\n", "Löwe 老虎", "", "\r\n" ];
+        for a in &texts {
+            for b in &texts {
+                let (a, b) = (Rope::from(*a), Rope::from(*b));
+                assert_eq!(a.graphemes_eq(&b), a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn combining_sequences_across_leaves() {
+        let whole = Rope::from("a̐éö̲");
+        let split = Rope::from("a") + Rope::from("\u{310}é")
+                  + Rope::from("ö") + Rope::from("\u{332}");
+        assert!(whole.graphemes_eq(&split));
+        assert!(split.graphemes_eq(&whole));
+        // a lone combining mark is not the same as the combined cluster
+        assert!(!whole.graphemes_eq(&Rope::from("a\u{310}éö")));
+    }
+}
+
+mod insert_rope_rebalancing {
+    use Rope;
+
+    fn depth(rope: &Rope) -> usize {
+        rope.shrink_leaf_fragmentation_report().depth
+    }
+
+    /// The greatest depth an AVL-balanced tree with `leaves` leaves can have.
+    fn max_balanced_depth(leaves: usize) -> usize {
+        (1.45 * ((leaves + 2) as f64).log2()).ceil() as usize
+    }
+
+    #[test]
+    fn stays_balanced_after_localized_inserts() {
+        let lines = (0..256).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+        let mut rope = Rope::from_terminated_lines(&lines);
+        let mut expected = rope.to_string();
+        let middle = rope.len() / 2;
+        for i in 0..500 {
+            let at = middle + (i % 7);
+            rope = rope.insert_rope(at, &Rope::from("ab"));
+            expected.insert_str(at, "ab");
+            let leaves = rope.shrink_leaf_fragmentation_report().leaf_count;
+            assert!( depth(&rope) <= max_balanced_depth(leaves)
+                   , "depth {} too great for {} leaves after {} inserts"
+                   , depth(&rope), leaves, i + 1);
+        }
+        assert_eq!(&rope, &expected[..]);
+        assert!(rope.len_is_consistent());
+    }
+
+    #[test]
+    fn untouched_subtrees_are_shared() {
+        let lines = (0..64).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+        let original = Rope::from_terminated_lines(&lines);
+        let ptrs = original.strings().map(str::as_ptr).collect::<Vec<_>>();
+        // insert into the middle of the 40th line
+        let at = lines[..40].iter().map(|l| l.len() + 1).sum::<usize>() + 2;
+        let rope = original.insert_rope(at, &Rope::from("--"));
+        let new_ptrs = rope.strings().map(str::as_ptr).collect::<Vec<_>>();
+        // the split leaf becomes two leaves around the inserted one
+        assert_eq!(new_ptrs.len(), ptrs.len() + 2);
+        assert_eq!(&new_ptrs[..40], &ptrs[..40]);
+        assert_eq!(&new_ptrs[43..], &ptrs[41..]);
+        assert_eq!(rope.lines().nth(40).map(|l| l.to_string())
+                  , Some(String::from("li--ne 40")));
+    }
+}
+
+mod char_indices_rev {
+    use Rope;
+
+    fn assert_reverse_of_forward(rope: &Rope) {
+        let mut forward = rope.char_indices().collect::<Vec<_>>();
+        forward.reverse();
+        assert_eq!(rope.char_indices_rev().collect::<Vec<_>>(), forward);
+    }
+
+    #[test]
+    fn matches_forward_reversed() {
+        assert_reverse_of_forward(&Rope::from("Löwe 老虎 Léopard"));
+        assert_reverse_of_forward(&( Rope::from("a̐é") + Rope::from("")
+                                   + Rope::from("ö̲\r\n") + Rope::from("🦁")));
+        assert_reverse_of_forward(&Rope::from("one\ntwo\nthree\n"));
+        assert_reverse_of_forward(&Rope::new());
+    }
+
+    #[test]
+    fn scanning_backwards() {
+        let rope = Rope::from("key = value\n") + Rope::from("other = 老虎");
+        let last_eq = rope.char_indices_rev()
+                          .find(|&(_, c)| c == '=')
+                          .map(|(i, _)| i);
+        assert_eq!(last_eq, Some(18));
+        assert_eq!(rope.char_indices_rev().next(), Some((23, '虎')));
+    }
+}
+
+mod bytes_in_line {
+    use Rope;
+    use metric::Line;
+
+    #[test]
+    fn varying_lengths() {
+        let rope = Rope::from("a\n") + Rope::from("Löwe 老虎\r\n")
+                 + Rope::from("\n") + Rope::from("🦁🦁\r\n") + Rope::from("end");
+        let text = rope.to_string();
+        let lines = text.split('\n').collect::<Vec<_>>();
+        for (n, line) in lines.iter().enumerate() {
+            let content = line.trim_end_matches('\r');
+            let terminated = n + 1 < lines.len();
+            assert_eq!(rope.bytes_in_line(Line(n), false), content.len());
+            assert_eq!( rope.bytes_in_line(Line(n), true)
+                      , line.len() + if terminated { 1 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn trailing_empty_line() {
+        let rope = Rope::from("text\n");
+        assert_eq!(rope.bytes_in_line(Line(1), true), 0);
+        assert_eq!(rope.bytes_in_line(Line(1), false), 0);
+        assert_eq!(Rope::new().bytes_in_line(Line(0), true), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_bounds() {
+        Rope::from("one\ntwo").bytes_in_line(Line(2), false);
+    }
+}
+
+mod repeat_into {
+    use Rope;
+
+    #[test]
+    fn accumulates_into_prepopulated_buf() {
+        let piece = Rope::from("Löwe\n") + Rope::from("老虎 ");
+        let mut buf = Rope::from("header\n");
+        let mut expected = String::from("header\n");
+        for &n in &[0, 1, 2, 5, 8, 13] {
+            piece.repeat_into(&mut buf, n);
+            for _ in 0..n { expected.push_str("Löwe\n老虎 "); }
+            assert_eq!(&buf, &expected[..]);
+            assert!(buf.len_is_consistent());
+        }
+        assert_eq!(&piece, "Löwe\n老虎 ");
+    }
+
+    #[test]
+    fn shares_structure_and_stays_shallow() {
+        let piece = Rope::from("abc");
+        let mut buf = Rope::new();
+        piece.repeat_into(&mut buf, 1000);
+        assert_eq!(buf.len(), 3000);
+        let leaf = piece.strings().next().unwrap().as_ptr();
+        assert!(buf.strings().all(|s| s.as_ptr() == leaf));
+        assert!(buf.shrink_leaf_fragmentation_report().depth <= 15);
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let mut buf = Rope::from("x");
+        Rope::new().repeat_into(&mut buf, 10);
+        Rope::from("y").repeat_into(&mut buf, 0);
+        assert_eq!(&buf, "x");
+    }
+}
+
+mod find_line_bounds {
+    use Rope;
+
+    #[test]
+    fn within_at_start_and_at_end() {
+        let rope = Rope::from("Löwe\n") + Rope::from("老虎\r\n")
+                 + Rope::from("\n") + Rope::from("last");
+        // (index, line start, line end)
+        let cases = [ (0, 0, 5), (3, 0, 5), (5, 0, 5)
+                    , (6, 6, 12), (9, 6, 12), (12, 6, 12), (13, 6, 12)
+                    , (14, 14, 14)
+                    , (15, 15, 19), (17, 15, 19), (19, 15, 19) ];
+        for &(index, start, end) in &cases {
+            assert_eq!(rope.find_line_start(index), start, "start of {}", index);
+            assert_eq!(rope.find_line_end(index), end, "end of {}", index);
+        }
+    }
+
+    #[test]
+    fn select_current_line() {
+        let rope = Rope::from("one\ntwo\nthree\n");
+        let at = 5;
+        let line = rope.slice(rope.find_line_start(at)..rope.find_line_end(at));
+        assert_eq!(line.to_string(), "two");
+        assert_eq!(rope.find_line_start(rope.len()), rope.len());
+        assert_eq!(rope.find_line_end(rope.len()), rope.len());
+        assert_eq!(Rope::new().find_line_end(0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_bounds() {
+        Rope::from("abc").find_line_start(4);
+    }
+}
+
+mod iter_lines_with_endings {
+    use {Rope, LineEnding};
+
+    fn lines(rope: &Rope) -> Vec<(String, Option<LineEnding>)> {
+        rope.iter_lines_with_endings()
+            .map(|(line, ending)| (line.to_string(), ending))
+            .collect()
+    }
+
+    #[test]
+    fn mixed_endings() {
+        let rope = Rope::from("Löwe\r") + Rope::from("\n老虎\r\r")
+                 + Rope::from("\n") + Rope::from("Léopard\rlion");
+        assert_eq!( lines(&rope)
+                  , vec![ ("Löwe".to_string(), Some(LineEnding::CrLf))
+                        , ("老虎".to_string(), Some(LineEnding::Cr))
+                        , ("".to_string(), Some(LineEnding::CrLf))
+                        , ("Léopard".to_string(), Some(LineEnding::Cr))
+                        , ("lion".to_string(), None) ]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "a\r\n\nb\rc\r\n\r\rd\n";
+        let rope = Rope::from(text);
+        let rebuilt = rope.iter_lines_with_endings()
+            .fold(String::new(), |mut s, (line, ending)| {
+                s.push_str(&line.to_string());
+                s.push_str(ending.map_or("", |e| e.as_str()));
+                s
+            });
+        assert_eq!(rebuilt, text);
+        assert_eq!( lines(&rope).last()
+                  , Some(&("d".to_string(), Some(LineEnding::Lf))));
+    }
+
+    #[test]
+    fn empty_and_unterminated() {
+        assert!(lines(&Rope::new()).is_empty());
+        assert_eq!( lines(&Rope::from("just one"))
+                  , vec![("just one".to_string(), None)]);
+        assert_eq!( lines(&Rope::from("\n"))
+                  , vec![("".to_string(), Some(LineEnding::Lf))]);
+    }
+}
+
+mod shrink_small_leaves_threshold {
+    use Rope;
+
+    fn fragmented(text: &str) -> Rope {
+        text.chars()
+            .fold(Rope::new(), |rope, c| rope + Rope::from(c.to_string()))
+    }
+
+    fn ceil_log2(n: usize) -> usize {
+        (0..).find(|&d| 1 << d >= n).unwrap()
+    }
+
+    #[test]
+    fn balanced_and_defragmented() {
+        let text = "Löwe 老虎 Léopard\nlion tiger leopard\nend";
+        let mut rope = fragmented(text);
+        let before = rope.shrink_leaf_fragmentation_report();
+        if cfg!(not(feature = "rebalance")) {
+            assert!(before.depth >= before.leaf_count - 1);
+        }
+
+        rope.shrink_small_leaves_threshold(8);
+        assert_eq!(&rope, text);
+        assert!(rope.len_is_consistent());
+        let after = rope.shrink_leaf_fragmentation_report();
+        assert!(after.leaf_count < before.leaf_count / 4);
+        assert!(after.max_leaf_len <= 8);
+        assert_eq!(after.depth, ceil_log2(after.leaf_count));
+        // leaves are never merged across a line ending
+        assert_eq!(rope.lines().count(), 3);
+        assert!(rope.strings().all(|s| !s[..s.len() - 1].contains('\n')));
+    }
+
+    #[test]
+    fn zero_threshold_only_rebalances() {
+        let mut rope = fragmented("abcdefgh");
+        let ptrs = rope.strings().map(str::as_ptr).collect::<Vec<_>>();
+        rope.shrink_small_leaves_threshold(0);
+        assert_eq!(rope.strings().map(str::as_ptr).collect::<Vec<_>>(), ptrs);
+        assert_eq!(rope.shrink_leaf_fragmentation_report().depth, 3);
+    }
+
+    #[test]
+    fn large_leaves_are_kept() {
+        let mut rope = Rope::from("a long leaf of text") + fragmented("xyz")
+                     + Rope::from("another long leaf");
+        rope.shrink_small_leaves_threshold(4);
+        assert_eq!(&rope, "a long leaf of textxyzanother long leaf");
+        assert_eq!( rope.strings().collect::<Vec<_>>()
+                  , vec!["a long leaf of text", "xyz", "another long leaf"]);
+    }
+}
+
+mod byte_to_char_in_line {
+    use Rope;
+
+    #[test]
+    fn multi_byte_lines() {
+        let rope = Rope::from("Löwe 老虎\n") + Rope::from("🦁 lion\r\n")
+                 + Rope::from("a̐éö̲");
+        let text = rope.to_string();
+        for (index, _) in text.char_indices().chain(Some((text.len(), ' '))) {
+            let start = text[..index].rfind('\n').map_or(0, |i| i + 1);
+            assert_eq!( rope.byte_to_char_in_line(index)
+                      , text[start..index].chars().count()
+                      , "column of byte {}", index);
+        }
+        // combining marks are counted as `char`s of their own
+        assert_eq!(rope.byte_to_char_in_line(rope.len()), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn inside_a_char() {
+        Rope::from("老虎").byte_to_char_in_line(1);
+    }
+}
+
+mod append_to_empty {
+    use Rope;
+
+    #[test]
+    fn keeps_structure_shallow() {
+        let big = "Löwe 老虎\nLéopard\nlion\ntiger\nleopard\n";
+        let from = Rope::from(big);
+        let appended = Rope::new().append(&from);
+        let prepended = Rope::new().prepend(&from);
+        let leaves = from.strings().map(str::as_ptr).collect::<Vec<_>>();
+        for rope in &[&appended, &prepended] {
+            assert_eq!( rope.shrink_leaf_fragmentation_report()
+                      , from.shrink_leaf_fragmentation_report());
+            assert_eq!( rope.strings().map(str::as_ptr).collect::<Vec<_>>()
+                      , leaves);
+        }
+    }
+
+    #[test]
+    fn built_up_from_empty() {
+        let rope = ["a", "b", "c", "d"].iter()
+            .fold(Rope::new(), |rope, s| rope + Rope::from(*s));
+        assert_eq!(&rope, "abcd");
+        // no empty leaf is left at the bottom of the tree
+        assert!(rope.shrink_leaf_fragmentation_report().depth <= 3);
+        assert!(rope.len_is_consistent());
+    }
+}
+
+mod cursor {
+    use Rope;
+    use metric::Line;
+
+    #[test]
+    fn yields_char_indices() {
+        let rope = Rope::from("Löwe\n") + Rope::from("老虎");
+        assert_eq!( rope.iter().collect::<Vec<_>>()
+                  , rope.char_indices().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn seeking() {
+        let rope = Rope::from("Löwe\n") + Rope::from("老虎");
+        let mut cursor = rope.iter();
+        cursor.seek(6);
+        assert_eq!(cursor.offset(), 6);
+        assert_eq!(cursor.peek_char(), Some('老'));
+        assert_eq!(cursor.prev_char(), Some((5, '\n')));
+        assert_eq!(cursor.prev_char(), Some((4, 'e')));
+        cursor.seek(rope.len());
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.prev_char(), Some((9, '虎')));
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn seek_inside_a_char() {
+        Rope::from("老虎").iter().seek(1);
+    }
+
+    #[test]
+    fn graphemes_over_combining_sequences() {
+        let rope = Rope::from("a") + Rope::from("\u{310}é")
+                 + Rope::from("ö") + Rope::from("\u{332}\r") + Rope::from("\nz");
+        let mut cursor = rope.iter();
+        let mut forward = Vec::new();
+        while let Some(range) = cursor.next_grapheme() { forward.push(range); }
+        assert_eq!(forward, vec![0..3, 3..5, 5..9, 9..11, 11..12]);
+        let mut backward = Vec::new();
+        while let Some(range) = cursor.prev_grapheme() { backward.push(range); }
+        backward.reverse();
+        assert_eq!(backward, forward);
+        assert_eq!(cursor.offset(), 0);
+    }
+
+    #[test]
+    fn graphemes_from_inside_a_cluster() {
+        let rope = Rope::from("ab") + Rope::from("e") + Rope::from("\u{301}x");
+        let mut cursor = rope.iter();
+        cursor.seek(3);
+        assert_eq!(cursor.next_grapheme(), Some(3..5));
+        cursor.seek(3);
+        assert_eq!(cursor.prev_grapheme(), Some(2..3));
+        assert_eq!(cursor.prev_grapheme(), Some(1..2));
+    }
+
+    #[test]
+    fn line_and_column() {
+        let rope = Rope::from("first\n") + Rope::from("a̐éö̲ x");
+        let mut cursor = rope.iter();
+        assert_eq!(cursor.line_column(), (Line(0), 0));
+        cursor.seek(6);
+        assert_eq!(cursor.line_column(), (Line(1), 0));
+        cursor.next_grapheme();
+        cursor.next_grapheme();
+        // two grapheme clusters, but three `char`s
+        assert_eq!(cursor.line_column(), (Line(1), 3));
+        cursor.seek(rope.len());
+        assert_eq!(cursor.line_column(), (Line(1), 7));
+    }
+}
+
+mod line_split {
+    use Rope;
+    use metric::Line;
+
+    const TEXT: &str = "this is\na\nmulti\nline\nrope";
+
+    #[test]
+    fn split_at_each_line_end() {
+        let rope = Rope::from(TEXT);
+        let ends = [8, 10, 16, 21, 25];
+        for (n, &end) in ends.iter().enumerate() {
+            let (l, r) = rope.split(Line(n));
+            assert_eq!(&l, &TEXT[..end]);
+            assert_eq!(&r, &TEXT[end..]);
+        }
+    }
+
+    #[test]
+    fn split_after_last_newline() {
+        let rope = Rope::from("one\ntwo\n");
+        let (l, r) = rope.split(Line(1));
+        assert_eq!(&l, "one\ntwo\n");
+        assert_eq!(&r, "");
+        let (l, r) = rope.split(Line(2));
+        assert_eq!(&l, "one\ntwo\n");
+        assert_eq!(&r, "");
+    }
+
+    #[test]
+    fn split_at_final_unterminated_line() {
+        let rope = Rope::from("one\ntwo");
+        let (l, r) = rope.split(Line(0));
+        assert_eq!(&l, "one\n");
+        assert_eq!(&r, "two");
+        let (l, r) = rope.split(Line(1));
+        assert_eq!(&l, "one\ntwo");
+        assert_eq!(&r, "");
+    }
+
+    #[test]
+    fn split_concatenated_rope() {
+        let rope = Rope::from("this is\na\nmul")
+            + Rope::from("ti\nline\nrope");
+        let (l, r) = rope.split(Line(2));
+        assert_eq!(&l, "this is\na\nmulti\n");
+        assert_eq!(&r, "line\nrope");
+    }
+
+    #[test]
+    fn delete_single_lines() {
+        let rope = Rope::from(TEXT);
+        assert_eq!(&rope.delete(Line(2)..Line(2)), "this is\na\nline\nrope");
+        assert_eq!(&rope.delete(Line(4)..Line(4)), "this is\na\nmulti\nline\n");
+        let rope = Rope::from("this is\na\nmul") + Rope::from("ti\nline\nrope");
+        assert_eq!(&rope.delete(Line(2)..Line(2)), "this is\na\nline\nrope");
+    }
+
+    #[test]
+    fn split_agrees_with_line_ranges() {
+        let rope = Rope::from("l0\nl1\nl2\n");
+        // splitting at a line keeps that line on the left, so the rest of
+        // the rope begins with the following line
+        let (l, r) = rope.split(Line(1));
+        assert_eq!(&l, "l0\nl1\n");
+        assert_eq!( r.bytes().collect::<Vec<u8>>()
+                  , rope.bytes_from_line(Line(2)).collect::<Vec<u8>>());
+        assert_eq!(&rope.try_slice(Line(0)..Line(1)).unwrap(), "l0\nl1\n");
+    }
+
+    #[test]
+    fn try_slice_lines() {
+        let rope = Rope::from("this is\na\nmul") + Rope::from("ti\nline\nrope");
+        assert_eq!(&rope.try_slice(Line(2)..Line(2)).unwrap(), "multi\n");
+        assert_eq!(&rope.try_slice(Line(4)..Line(4)).unwrap(), "rope");
+        assert_eq!(&rope.try_slice(Line(0)..Line(4)).unwrap(), TEXT);
+        assert!(rope.try_slice(Line(0)..Line(5)).is_err());
+    }
+
+    #[test]
+    fn str_line_measure_counts_interior_newlines() {
+        use metric::Measured;
+        let lines: Line = TEXT.measure();
+        assert_eq!(lines, Line(4));
+        assert_eq!(TEXT.to_byte_index(Line(1)), Some(10));
+        assert_eq!(TEXT.to_byte_index(Line(4)), Some(TEXT.len()));
+        assert_eq!(TEXT.to_byte_index(Line(5)), None);
+    }
+}
+
+mod from_byte_chunks {
+    use Rope;
+
+    #[test]
+    fn char_split_across_chunks() {
+        let bytes = "Löwe 老虎 Léopard".as_bytes();
+        for i in 0..bytes.len() + 1 {
+            let chunks = vec![bytes[..i].to_vec(), bytes[i..].to_vec()];
+            let rope = Rope::from_byte_chunks(chunks).unwrap();
+            assert_eq!(&rope, "Löwe 老虎 Léopard");
+        }
+    }
+
+    #[test]
+    fn char_split_across_three_chunks() {
+        let chunks = vec![vec![0xe8], vec![0x80], vec![0x81, b'!']];
+        let rope = Rope::from_byte_chunks(chunks).unwrap();
+        assert_eq!(&rope, "老!");
+    }
+
+    #[test]
+    fn no_chunks() {
+        let rope = Rope::from_byte_chunks(Vec::new()).unwrap();
+        assert_eq!(&rope, "");
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let chunks = vec![b"fine".to_vec(), vec![0, 159, 146, 150]];
+        assert!(Rope::from_byte_chunks(chunks).is_err());
+    }
+
+    #[test]
+    fn incomplete_final_char() {
+        let chunks = vec![b"fine".to_vec(), vec![0xe8, 0x80]];
+        let err = Rope::from_byte_chunks(chunks).unwrap_err();
+        assert_eq!(err.into_bytes(), [0xe8, 0x80]);
+    }
+}
+
+mod graphemes_at_line_col {
+    use Rope;
+    use metric::Line;
+
+    #[test]
+    fn wide_and_combining_clusters() {
+        let rope = Rope::from("老虎\nLo\u{308}we a\u{310}\n");
+        assert_eq!(&rope.graphemes_at_line_col(Line(0), 0).unwrap(), "老");
+        assert_eq!(&rope.graphemes_at_line_col(Line(0), 1).unwrap(), "虎");
+        assert!(rope.graphemes_at_line_col(Line(0), 2).is_none());
+        assert_eq!( &rope.graphemes_at_line_col(Line(1), 1).unwrap()
+                  , "o\u{308}");
+        assert_eq!(&rope.graphemes_at_line_col(Line(1), 2).unwrap(), "w");
+        assert_eq!( &rope.graphemes_at_line_col(Line(1), 5).unwrap()
+                  , "a\u{310}");
+        assert!(rope.graphemes_at_line_col(Line(1), 6).is_none());
+        assert!(rope.graphemes_at_line_col(Line(2), 0).is_none());
+    }
+
+    #[test]
+    fn cluster_spanning_leaves() {
+        let rope = Rope::from("first\nLo") + Rope::from("\u{308}we");
+        assert_eq!( &rope.graphemes_at_line_col(Line(1), 1).unwrap()
+                  , "o\u{308}");
+        assert_eq!(&rope.graphemes_at_line_col(Line(1), 2).unwrap(), "w");
+    }
+
+    #[test]
+    fn past_the_last_line() {
+        let rope = Rope::from("one\ntwo");
+        assert!(rope.graphemes_at_line_col(Line(2), 0).is_none());
+    }
+}
+
+mod push_grapheme {
+    use Rope;
+    use metric::{Grapheme, Measured};
+
+    #[test]
+    fn grapheme_measure_increments_by_one() {
+        let graphemes = [ "a\u{310}", "e\u{301}", "o\u{308}\u{332}", "老"
+                        , "\r\n", "u\u{308}", "\n", "🇦🇺" ];
+        let mut rope = Rope::new();
+        for (i, g) in graphemes.iter().enumerate() {
+            rope.push_grapheme(g);
+            let len: Grapheme = rope.measure();
+            assert_eq!(len, Grapheme(i + 1));
+            assert_eq!(rope.graphemes().count(), i + 1);
+        }
+        assert_eq!(rope, graphemes.concat());
+    }
+
+    #[test]
+    fn merges_into_last_leaf() {
+        let mut rope = Rope::from("first\n");
+        for _ in 0..10 { rope.push_grapheme("e\u{301}"); }
+        assert_eq!(rope.strings().count(), 2);
+        assert_eq!(rope.lines().count(), 2);
+    }
+}
+
+mod byte_slice_lines {
+    use Rope;
+
+    const TEXT: &str = "Löwe\n老虎\r\nLéopard\n\nlast";
+
+    fn lines(rope: &Rope, range: ::std::ops::Range<usize>) -> Vec<String> {
+        rope.byte_slice_lines(range).map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn covers_every_range_exactly() {
+        let rope = Rope::from("Löwe\n老虎\r\n") + Rope::from("Léopard\n\nlast");
+        let bounds = (0..TEXT.len() + 1)
+            .filter(|&i| TEXT.is_char_boundary(i))
+            .collect::<Vec<_>>();
+        for &start in &bounds {
+            for &end in bounds.iter().filter(|&&end| end >= start) {
+                let lines = lines(&rope, start..end);
+                assert_eq!(lines.concat(), &TEXT[start..end]);
+                // every line but the last ends with a newline, and none
+                // contains one anywhere else
+                for (i, line) in lines.iter().enumerate() {
+                    assert!(!line.is_empty());
+                    assert!(!line.trim_end_matches('\n').contains('\n'));
+                    if i + 1 < lines.len() { assert!(line.ends_with('\n')) }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn whole_rope() {
+        let rope = Rope::from(TEXT);
+        assert_eq!( lines(&rope, 0..TEXT.len())
+                  , ["Löwe\n", "老虎\r\n", "Léopard\n", "\n", "last"]);
+    }
+
+    #[test]
+    fn empty_range() {
+        let rope = Rope::from(TEXT);
+        assert!(lines(&rope, 3..3).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn not_a_char_boundary() {
+        let _ = Rope::from(TEXT).byte_slice_lines(0..2);
+    }
+}
+
+mod count_chars {
+    use Rope;
+
+    #[test]
+    fn matches_chars_count() {
+        for s in &["", "a", "Löwe 老虎 Léopard", "a\u{310}e\u{301}\n🇦🇺\r\n"] {
+            let rope = Rope::from(*s);
+            assert_eq!(rope.count_chars(), rope.chars().count());
+            assert_eq!(rope.count_chars(), s.chars().count());
+        }
+    }
+
+    #[test]
+    fn stays_correct_after_edits() {
+        let mut rope = Rope::from("Löwe 老虎\nLéopard");
+        assert_eq!(rope.count_chars(), rope.chars().count());
+        rope = rope.insert_str(5, "🐯 ");
+        assert_eq!(rope.count_chars(), rope.chars().count());
+        rope = rope.delete(0..10);
+        assert_eq!(rope.count_chars(), rope.chars().count());
+        rope.push_rope(Rope::from("\nGepardé"));
+        assert_eq!(rope.count_chars(), rope.chars().count());
+        rope = rope.append(&Rope::from("老"));
+        assert_eq!(rope.count_chars(), rope.chars().count());
+        assert_eq!(rope.count_chars(), rope.to_string().chars().count());
+    }
+}
+
+#[cfg(feature = "case")]
+mod bytes_eq_ignore_case_unicode {
+    use Rope;
+
+    fn eq(a: &str, b: &str) -> bool {
+        Rope::from(a).bytes_eq_ignore_case_unicode(&Rope::from(b))
+    }
+
+    #[test]
+    fn greek_sigma_forms() {
+        assert!(eq("Σ", "σ"));
+        assert!(eq("σ", "ς"));
+        assert!(eq("ΟΔΥΣΣΕΥΣ", "οδυσσευς"));
+        assert!(eq("Οδυσσευσ", "ΟΔΥΣΣΕΥΣ"));
+        assert!(!eq("ΟΔΥΣΣΕΥΣ", "οδυσευς"));
+    }
+
+    #[test]
+    fn mixed_case_across_leaves() {
+        let rope = Rope::from("LÖWE ") + Rope::from("Léopard");
+        let same = Rope::from("löwe LÉOPARD");
+        let different = Rope::from("löwe LEOPARD");
+        assert!(rope.bytes_eq_ignore_case_unicode(&same));
+        assert!(!rope.bytes_eq_ignore_case_unicode(&different));
+    }
+
+    #[test]
+    fn other_case_variants() {
+        // long s, micro sign, and the capital sharp s
+        assert!(eq("ſ", "S"));
+        assert!(eq("µ", "Μ"));
+        assert!(eq("ẞ", "ß"));
+        assert!(!eq("ß", "ss"));
+        // U+0130 only has a full case folding, so it is left unchanged
+        assert!(eq("İ", "İ"));
+        assert!(!eq("İ", "i\u{307}"));
+        assert!(!eq("İ", "i"));
+        // Cherokee folds to upper case, rather than lower case
+        assert!(eq("ᏸ", "Ᏸ"));
+        assert!(eq("", ""));
+        assert!(!eq("a", ""));
+    }
+}
+
+mod map_lines {
+    use Rope;
+
+    fn quote(rope: &Rope) -> Rope {
+        rope.map_lines(|line| Rope::from(format!("> {}", line)))
+    }
+
+    #[test]
+    fn quote_every_line() {
+        let rope = Rope::from("Löwe\n老虎\n") + Rope::from("Léopard");
+        assert_eq!(&quote(&rope), "> Löwe\n> 老虎\n> Léopard");
+    }
+
+    #[test]
+    fn keeps_line_endings() {
+        let rope = Rope::from("one\r\ntwo\n\nfour\n");
+        assert_eq!(&quote(&rope), "> one\r\n> two\n> \n> four\n");
+    }
+
+    #[test]
+    fn empty_rope() {
+        assert_eq!(&quote(&Rope::new()), "");
+    }
+
+    #[test]
+    fn lines_can_be_removed() {
+        let rope = Rope::from("keep\ndrop\nkeep");
+        let mapped = rope.map_lines(|line|
+            if &line == "drop" { Rope::new() }
+            else { Rope::from(line.to_string().to_uppercase()) });
+        assert_eq!(&mapped, "KEEP\n\nKEEP");
+    }
+}
+
+mod indent {
+    use Rope;
+
+    #[test]
+    fn indent_every_line() {
+        let rope = Rope::from("Löwe\n\n老虎\r\n") + Rope::from("Léopard");
+        assert_eq!(&rope.indent("\t"), "\tLöwe\n\t\n\t老虎\r\n\tLéopard");
+    }
+
+    #[test]
+    fn terminated_final_line() {
+        let rope = Rope::from("one\ntwo\n");
+        assert_eq!(&rope.indent("- "), "- one\n- two\n");
+    }
+
+    #[test]
+    fn dedent_only_prefixed_lines() {
+        let rope = Rope::from("  one\n    two\nthree\n ");
+        assert_eq!(&rope.dedent("  "), "one\n  two\nthree\n ");
+    }
+
+    #[test]
+    fn round_trip() {
+        let texts = [ "", "one", "one\n", "one\ntwo", "\n\n", "a\r\nb\r\n"
+                    , "    already indented\n" ];
+        for text in &texts {
+            let rope = Rope::from(*text);
+            for prefix in &["", "    ", "> ", "老"] {
+                assert_eq!(&rope.indent(prefix).dedent(prefix), *text);
+            }
+        }
+    }
+}
+
+mod splice_graphemes {
+    use Rope;
+    use metric::{Grapheme, Measured};
+
+    // "a̐éö̲", with the combining sequences spelled out
+    const TEXT: &str = "a\u{310}\u{e9}o\u{308}\u{332}";
+
+    fn graphemes(rope: &Rope) -> Vec<String> {
+        rope.graphemes().map(String::from).collect()
+    }
+
+    #[test]
+    fn replace_middle_grapheme() {
+        let mut rope = Rope::from(TEXT);
+        rope.splice_graphemes(Grapheme(1)..Grapheme(2), "e\u{301}");
+        assert_eq!( graphemes(&rope)
+                  , ["a\u{310}", "e\u{301}", "o\u{308}\u{332}"]);
+    }
+
+    #[test]
+    fn replace_combining_sequences() {
+        let mut rope = Rope::from(TEXT);
+        rope.splice_graphemes(Grapheme(0)..Grapheme(1), "x");
+        assert_eq!(graphemes(&rope), ["x", "\u{e9}", "o\u{308}\u{332}"]);
+        rope.splice_graphemes(Grapheme(2)..Grapheme(3), "u\u{308}");
+        assert_eq!(graphemes(&rope), ["x", "\u{e9}", "u\u{308}"]);
+        let len: Grapheme = rope.measure();
+        assert_eq!(len, Grapheme(3));
+    }
+
+    #[test]
+    fn delete_and_insert() {
+        let mut rope = Rope::from(TEXT);
+        rope.splice_graphemes(Grapheme(0)..Grapheme(2), "");
+        assert_eq!(&rope, "o\u{308}\u{332}");
+        rope.splice_graphemes(Grapheme(1)..Grapheme(1), "!");
+        assert_eq!(&rope, "o\u{308}\u{332}!");
+        rope.splice_graphemes(Grapheme(0)..Grapheme(0), "a\u{310}");
+        assert_eq!(graphemes(&rope), ["a\u{310}", "o\u{308}\u{332}", "!"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rope::splice_graphemes: index")]
+    fn out_of_bounds() {
+        let mut rope = Rope::from(TEXT);
+        rope.splice_graphemes(Grapheme(2)..Grapheme(4), "");
+    }
+}
+
+mod grapheme_ranges {
+    use Rope;
+
+    fn ranges(rope: &Rope) -> Vec<::std::ops::Range<usize>> {
+        rope.root.grapheme_ranges(0).collect()
+    }
+
+    #[test]
+    fn agrees_with_str_segmentation() {
+        use unicode_segmentation::UnicodeSegmentation;
+        let text = "a\u{310}e\u{301}o\u{308}\u{332}\r\n老虎 \
+                    \u{1F1F7}\u{1F1F8}";
+        let expected = text.grapheme_indices(true)
+                           .map(|(i, g)| i..i + g.len())
+                           .collect::<Vec<_>>();
+        assert_eq!(ranges(&Rope::from(text)), expected);
+    }
+
+    #[test]
+    fn clusters_spanning_leaves() {
+        let rope = Rope::from("ab") + Rope::from("e") + Rope::from("\u{301}x");
+        assert_eq!(ranges(&rope), vec![0..1, 1..2, 2..5, 5..6]);
+        let rope = Rope::from("a\r") + Rope::from("\nb");
+        assert_eq!(ranges(&rope), vec![0..1, 1..3, 3..4]);
+    }
+
+    #[test]
+    fn regional_indicators_spanning_leaves() {
+        // deciding whether to break between two regional indicators needs
+        // the text before them, which is in the earlier leaves
+        let rope = Rope::from("\u{1F1F7}") + Rope::from("\u{1F1F8}")
+                 + Rope::from("\u{1F1EE}") + Rope::from("\u{1F1F4}");
+        assert_eq!(ranges(&rope), vec![0..8, 8..16]);
+        let mut cursor = rope.root.grapheme_ranges(12);
+        assert!(!cursor.is_boundary());
+        assert_eq!(cursor.prev_boundary(), Some(8));
+        let mut cursor = rope.root.grapheme_ranges(rope.len());
+        assert_eq!(cursor.prev_boundary(), Some(8));
+        assert_eq!(cursor.prev_boundary(), Some(0));
+    }
+
+    #[test]
+    fn control_after_a_seam() {
+        // a prepended character joins the cluster after it, unless that
+        // cluster is a control character
+        let rope = Rope::from("a\u{600}") + Rope::from("\r\nb");
+        assert_eq!(ranges(&rope), vec![0..1, 1..3, 3..5, 5..6]);
+        assert!(rope.root.grapheme_ranges(3).is_boundary());
+    }
+
+    #[test]
+    fn boundaries_around_an_index() {
+        let rope = Rope::from("ab") + Rope::from("e") + Rope::from("\u{301}x");
+        let mut cursor = rope.root.grapheme_ranges(3);
+        assert!(!cursor.is_boundary());
+        assert_eq!(cursor.next_boundary(), Some(5));
+        assert_eq!(cursor.prev_boundary(), Some(2));
+        assert_eq!(cursor.prev_boundary(), Some(1));
+        let mut cursor = rope.root.grapheme_ranges(rope.len());
+        assert!(cursor.is_boundary());
+        assert_eq!(cursor.next_boundary(), None);
+        assert_eq!(cursor.prev_boundary(), Some(5));
+        assert_eq!(cursor.prev_boundary(), Some(2));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(ranges(&Rope::new()).is_empty());
+        assert_eq!(Rope::new().root.grapheme_ranges(0).prev_boundary(), None);
+    }
+
+    #[test]
+    fn text_in() {
+        let rope = Rope::from("ab") + Rope::from("e") + Rope::from("\u{301}x");
+        assert_eq!(rope.root.text_in(0..2), "ab");
+        assert_eq!(rope.root.text_in(2..5), "e\u{301}");
+        assert_eq!(rope.root.text_in(1..6), "be\u{301}x");
+    }
+}
+
+#[cfg(feature = "width")]
+mod total_grapheme_width {
+    use Rope;
+
+    #[test]
+    fn ascii() {
+        assert_eq!(Rope::from("").total_grapheme_width(), 0);
+        assert_eq!(Rope::from("an rope").total_grapheme_width(), 7);
+    }
+
+    #[test]
+    fn wide_cjk() {
+        assert_eq!(Rope::from("老虎").total_grapheme_width(), 4);
+        assert_eq!(Rope::from("ｗｉｄｅ").total_grapheme_width(), 8);
+    }
+
+    #[test]
+    fn combining() {
+        let rope = Rope::from("a\u{310}e\u{301}o\u{308}\u{332}");
+        assert_eq!(rope.total_grapheme_width(), 3);
+        assert_eq!(rope.graphemes().count(), 3);
+    }
+
+    #[test]
+    fn mixed() {
+        let rope = Rope::from("Löwe 老虎 ") + Rope::from("Le\u{301}opard");
+        assert_eq!(rope.total_grapheme_width(), 17);
+    }
+
+    #[test]
+    fn clusters_spanning_leaves() {
+        // U+2764 followed by an emoji variation selector is drawn as a wide
+        // emoji, but only when the two are measured together
+        let whole = Rope::from("\u{2764}\u{fe0f}!");
+        let split = Rope::from("\u{2764}") + Rope::from("\u{fe0f}!");
+        assert_eq!(whole.total_grapheme_width(), 3);
+        assert_eq!(split.total_grapheme_width(), 3);
+    }
+}
+
+#[cfg(feature = "width")]
+mod line_width_columns {
+    use Rope;
+    use metric::Line;
+
+    #[test]
+    fn tabs_advance_to_tab_stops() {
+        let rope = Rope::from("\tone\nab\tc\n\t\t");
+        assert_eq!(rope.line_width_columns(Line(0), 4), 7);
+        assert_eq!(rope.line_width_columns(Line(1), 4), 5);
+        assert_eq!(rope.line_width_columns(Line(1), 2), 5);
+        assert_eq!(rope.line_width_columns(Line(1), 8), 9);
+        assert_eq!(rope.line_width_columns(Line(2), 4), 8);
+    }
+
+    #[test]
+    fn wide_chars() {
+        let rope = Rope::from("老虎\n老\t虎\r\n");
+        assert_eq!(rope.line_width_columns(Line(0), 4), 4);
+        assert_eq!(rope.line_width_columns(Line(1), 4), 6);
+        assert_eq!(rope.line_width_columns(Line(2), 4), 0);
+    }
+
+    #[test]
+    fn combining_across_leaves() {
+        let rope = Rope::from("\to") + Rope::from("\u{308}\u{332}\t!");
+        assert_eq!(rope.line_width_columns(Line(0), 4), 9);
+        assert_eq!(rope.line_width_columns(Line(0), 0), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn line_out_of_bounds() {
+        Rope::from("one\ntwo").line_width_columns(Line(2), 4);
+    }
+
+    #[test]
+    fn wide_cluster_spanning_leaves() {
+        // U+FE0F asks for the emoji presentation of "☺", which is two
+        // columns wide, but only when it's measured with the "☺"
+        let rope = Rope::from("a\t\u{263A}") + Rope::from("\u{FE0F}b");
+        assert_eq!(rope.line_width_columns(Line(0), 4), 7);
+        assert_eq!(rope.byte_position_of_column(Line(0), 6, 4), 8);
+    }
+}
+
+#[cfg(feature = "width")]
+mod byte_position_of_column {
+    use Rope;
+    use metric::Line;
+
+    #[test]
+    fn tabs() {
+        let rope = Rope::from("a\tb\n\t\tc");
+        let expected = [0, 1, 1, 1, 2, 3, 3];
+        for (column, &i) in expected.iter().enumerate() {
+            assert_eq!(rope.byte_position_of_column(Line(0), column, 4), i);
+        }
+        assert_eq!(rope.byte_position_of_column(Line(1), 0, 4), 4);
+        assert_eq!(rope.byte_position_of_column(Line(1), 4, 4), 5);
+        assert_eq!(rope.byte_position_of_column(Line(1), 8, 4), 6);
+        assert_eq!(rope.byte_position_of_column(Line(1), 9, 4), 7);
+    }
+
+    #[test]
+    fn wide_chars() {
+        let rope = Rope::from("老虎!\r\n");
+        let expected = [0, 0, 3, 3, 6, 7, 7];
+        for (column, &i) in expected.iter().enumerate() {
+            assert_eq!(rope.byte_position_of_column(Line(0), column, 4), i);
+        }
+        assert_eq!(rope.byte_position_of_column(Line(1), 3, 4), 9);
+    }
+
+    #[test]
+    fn combining_clusters_are_not_split() {
+        let rope = Rope::from("o\u{308}") + Rope::from("\u{332}x");
+        assert_eq!(rope.byte_position_of_column(Line(0), 0, 4), 0);
+        assert_eq!(rope.byte_position_of_column(Line(0), 1, 4), 5);
+        assert_eq!(rope.byte_position_of_column(Line(0), 2, 4), 6);
+    }
+
+    #[test]
+    fn inverse_of_line_width() {
+        let rope = Rope::from("\t老虎 Löwe\t!");
+        let width = rope.line_width_columns(Line(0), 4);
+        assert_eq!(rope.byte_position_of_column(Line(0), width, 4), rope.len());
+        assert!(rope.byte_position_of_column(Line(0), width - 1, 4)
+                < rope.len());
+    }
+}
+
+mod into_leaf_strings {
+    use Rope;
+
+    #[test]
+    fn concatenation_is_original() {
+        let rope = Rope::from("Löwe\n老虎\n") + Rope::from("Léopard")
+                 + Rope::new() + Rope::from("\nGepard");
+        let text = rope.to_string();
+        let leaves = rope.into_leaf_strings();
+        assert!(!leaves.is_empty());
+        assert!(leaves.iter().all(|leaf| !leaf.is_empty()));
+        assert_eq!(leaves.concat(), text);
+    }
+
+    #[test]
+    fn one_string_per_leaf() {
+        let rope = Rope::from("one\ntwo\nthree");
+        let count = rope.strings().filter(|s| !s.is_empty()).count();
+        assert_eq!(rope.into_leaf_strings().len(), count);
+    }
+
+    #[test]
+    fn empty_rope() {
+        assert!(Rope::new().into_leaf_strings().is_empty());
+        assert!((Rope::new() + Rope::new()).into_leaf_strings().is_empty());
+    }
+}
+
+mod from_leaves {
+    use Rope;
+
+    fn ropes() -> Vec<Rope> {
+        vec![ Rope::new()
+            , Rope::from("one leaf")
+            , Rope::from("Löwe\n老虎\n") + Rope::from("Léopard")
+                + Rope::from("\nGepard")
+            , Rope::from("a\u{310}") + Rope::from("e\u{301}\r\n")
+                + Rope::from("\n\n")
+            ]
+    }
+
+    #[test]
+    fn round_trip() {
+        for rope in ropes() {
+            let leaves = rope.clone().into_leaf_strings();
+            let rebuilt = Rope::from_leaves(leaves.clone());
+            assert_eq!(rebuilt, rope);
+            // the chunking is restored, too
+            assert_eq!(rebuilt.into_leaf_strings(), leaves);
+        }
+    }
+
+    #[test]
+    fn skips_empty_strings() {
+        let rope = Rope::from_leaves(vec![ String::new(), "ab".to_string()
+                                         , String::new(), "cd".to_string() ]);
+        assert_eq!(&rope, "abcd");
+        assert_eq!(rope.into_leaf_strings(), ["ab", "cd"]);
+    }
+
+    #[test]
+    fn splits_interior_newlines() {
+        let rope = Rope::from_leaves(vec![ "one\ntwo\n".to_string()
+                                         , "three".to_string() ]);
+        assert_eq!(&rope, "one\ntwo\nthree");
+        assert_eq!(rope.lines().count(), 3);
+        assert_eq!(rope.into_leaf_strings(), ["one\n", "two\n", "three"]);
+    }
+}
+
+mod range_to_slice {
+    use Rope;
+    use metric::Grapheme;
+
+    // "a̐éö̲", with the combining sequences spelled out
+    const TEXT: &str = "a\u{310}\u{e9}o\u{308}\u{332} tail";
+
+    #[test]
+    fn bytes_and_graphemes_differ() {
+        let rope = Rope::from(TEXT);
+        assert_eq!(&rope.byte_range_to_slice(0..3), "a\u{310}");
+        assert_eq!( &rope.grapheme_range_to_slice(Grapheme(0)..Grapheme(3))
+                  , "a\u{310}\u{e9}o\u{308}\u{332}");
+        assert_eq!( &rope.byte_range_to_slice(3..5), "\u{e9}");
+        assert_eq!( &rope.grapheme_range_to_slice(Grapheme(1)..Grapheme(2))
+                  , "\u{e9}");
+        // ASCII text is the same either way
+        assert_eq!(&rope.byte_range_to_slice(11..15), "tail");
+        assert_eq!( &rope.grapheme_range_to_slice(Grapheme(4)..Grapheme(8))
+                  , "tail");
+    }
+
+    #[test]
+    fn whole_and_empty_ranges() {
+        let rope = Rope::from(TEXT);
+        assert_eq!(&rope.byte_range_to_slice(0..rope.len()), TEXT);
+        assert_eq!( &rope.grapheme_range_to_slice(Grapheme(0)..Grapheme(8))
+                  , TEXT);
+        assert_eq!(&rope.byte_range_to_slice(5..5), "");
+        assert_eq!( &rope.grapheme_range_to_slice(Grapheme(8)..Grapheme(8))
+                  , "");
+    }
+
+    #[test]
+    #[should_panic(expected = "Rope::byte_range_to_slice: byte index 2 is not \
+                               a char boundary")]
+    fn byte_range_not_on_char_boundary() {
+        Rope::from(TEXT).byte_range_to_slice(0..2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rope::grapheme_range_to_slice: index 9 is out \
+                               of bounds (length 8)")]
+    fn grapheme_range_out_of_bounds() {
+        Rope::from(TEXT).grapheme_range_to_slice(Grapheme(2)..Grapheme(9));
+    }
+
+    #[test]
+    #[should_panic(expected = "start index 2 is greater than end index 1")]
+    fn grapheme_range_backwards() {
+        Rope::from(TEXT).grapheme_range_to_slice(Grapheme(2)..Grapheme(1));
+    }
+}
+
+mod trim_start_matches {
+    use Rope;
+
+    #[test]
+    fn repeated_prefixes() {
+        let rope = Rope::from("abababc");
+        assert_eq!(&rope.trim_start_matches("ab"), "c");
+        assert_eq!(&rope.trim_start_matches("aba"), "babc");
+        assert_eq!(&rope.trim_start_matches("c"), "abababc");
+        assert_eq!(&rope.trim_start_matches(""), "abababc");
+    }
+
+    #[test]
+    fn multi_byte_prefixes() {
+        let rope = Rope::from("老虎老虎老Löwe");
+        assert_eq!(&rope.trim_start_matches("老虎"), "老Löwe");
+        assert_eq!(&rope.trim_start_matches("老"), "虎老虎老Löwe");
+    }
+
+    #[test]
+    fn cross_leaf_prefixes() {
+        let rope = Rope::from("> >") + Rope::from(" > quote");
+        assert_eq!(&rope.trim_start_matches("> "), "quote");
+        let rope = Rope::from("ab") + Rope::from("a") + Rope::from("b");
+        assert_eq!(&rope.trim_start_matches("ab"), "");
+    }
+
+    #[test]
+    fn prefix_longer_than_rope() {
+        let rope = Rope::from("ab");
+        assert_eq!(&rope.trim_start_matches("abc"), "ab");
+    }
+
+    #[test]
+    fn char_set() {
+        let rope = Rope::from(" \t老") + Rope::from("虎 ") + Rope::from("\t\n");
+        assert_eq!(&rope.trim_matches_char_set(&[' ', '\t', '\n']), "老虎");
+        assert_eq!(&rope.trim_matches_char_set(&[' ', '\t', '\n', '老']), "虎");
+        assert_eq!( &rope.trim_matches_char_set(&[' ', '\t', '\n', '老', '虎'])
+                  , "");
+        assert_eq!(&Rope::new().trim_matches_char_set(&['a']), "");
+    }
+}
+
+mod append_line {
+    use Rope;
+    use metric::{Line, Measured};
+
+    #[test]
+    fn many_lines() {
+        let mut rope = Rope::new();
+        let mut expected = String::new();
+        for i in 0..200 {
+            let line = format!("line {} 老虎", i);
+            rope.append_line(&line);
+            expected.push_str(&line);
+            expected.push('\n');
+            let lines: Line = rope.measure();
+            assert_eq!(lines, Line(i + 1));
+        }
+        assert_eq!(rope, expected);
+        assert_eq!(rope.lines().count(), 200);
+        assert_eq!(rope.strings().filter(|s| !s.is_empty()).count(), 200);
+    }
+
+    #[test]
+    fn continues_unterminated_line() {
+        let mut rope = Rope::from("partial ");
+        rope.append_line("line");
+        rope.append_line("");
+        assert_eq!(&rope, "partial line\n\n");
+        assert_eq!(rope.strings().filter(|s| !s.is_empty()).count(), 2);
+        let lines: Line = rope.measure();
+        assert_eq!(lines, Line(2));
+    }
+
+    #[test]
+    fn line_with_newlines() {
+        let mut rope = Rope::from("first\n");
+        rope.append_line("second\nthird");
+        assert_eq!(&rope, "first\nsecond\nthird\n");
+        assert_eq!(rope.lines().count(), 3);
+        assert!(rope.strings().all(|s| !s.trim_end_matches('\n').contains('\n')));
+    }
+}
+
+mod first_and_last_line {
+    use Rope;
+
+    #[test]
+    fn multi_line_without_trailing_newline() {
+        let rope = Rope::from("Löwe\n老虎\n") + Rope::from("Léopard");
+        assert_eq!(&rope.first_line(), "Löwe");
+        assert_eq!(&rope.last_line(), "Léopard");
+    }
+
+    #[test]
+    fn multi_line_with_trailing_newline() {
+        let rope = Rope::from("Löwe\n老虎\n") + Rope::from("Léopard\n");
+        assert_eq!(&rope.first_line(), "Löwe");
+        assert_eq!(&rope.last_line(), "Léopard");
+        assert_eq!(&rope.last_line(), &rope.lines().last().unwrap());
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let rope = Rope::from("one\r\ntwo\r\n");
+        assert_eq!(&rope.first_line(), "one");
+        assert_eq!(&rope.last_line(), "two");
+    }
+
+    #[test]
+    fn blank_lines() {
+        let rope = Rope::from("\n\nlast");
+        assert_eq!(&rope.first_line(), "");
+        assert_eq!(&rope.last_line(), "last");
+        let rope = Rope::from("first\n\n");
+        assert_eq!(&rope.last_line(), "");
+        assert_eq!(&Rope::from("\n").first_line(), "");
+        assert_eq!(&Rope::from("\n").last_line(), "");
+    }
+
+    #[test]
+    fn single_line_and_empty() {
+        let rope = Rope::from("only");
+        assert_eq!(&rope.first_line(), "only");
+        assert_eq!(&rope.last_line(), "only");
+        assert_eq!(&Rope::new().first_line(), "");
+        assert_eq!(&Rope::new().last_line(), "");
+    }
+}
+
+mod count_occurrences_of_char {
+    use Rope;
+
+    #[test]
+    fn matches_chars_filter() {
+        let rope = Rope::from("\tLöwe\t老虎\n") + Rope::from("Léopard\t老\n")
+                 + Rope::from("ö\u{308}\t");
+        for &ch in &['\t', '\n', 'ö', '老', '虎', '\u{308}', 'L', 'x', '🐯'] {
+            assert_eq!( rope.count_occurrences_of_char(ch)
+                      , rope.chars().filter(|c| *c == ch).count());
+        }
+    }
+
+    #[test]
+    fn empty_rope() {
+        assert_eq!(Rope::new().count_occurrences_of_char('a'), 0);
+    }
+}
+
+mod split_whitespace_ranges {
+    use Rope;
+
+    fn expected(text: &str) -> Vec<(::std::ops::Range<usize>, String)> {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices().chain(Some((text.len(), ' '))) {
+            match (start, c.is_whitespace()) {
+                (None, false) => start = Some(i)
+              , (Some(s), true) => {
+                    tokens.push((s..i, text[s..i].to_string()));
+                    start = None;
+                }
+              , _ => {}
+            }
+        }
+        tokens
+    }
+
+    fn actual(rope: &Rope) -> Vec<(::std::ops::Range<usize>, String)> {
+        rope.split_whitespace_ranges()
+            .map(|(range, token)| (range, token.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_manual_computation() {
+        let texts = [ "", "   ", "one", " one two  three ", "Löwe\t老虎\r\nLéopard"
+                    , "a\u{3000}b\u{a0}c" ];
+        for text in &texts {
+            assert_eq!(actual(&Rope::from(*text)), expected(text));
+        }
+    }
+
+    #[test]
+    fn tokens_spanning_leaves() {
+        let rope = Rope::from("hel") + Rope::from("lo wor") + Rope::from("ld")
+                 + Rope::from(" 老") + Rope::from("虎 ");
+        let text = rope.to_string();
+        assert_eq!(actual(&rope), expected(&text));
+        assert_eq!( actual(&rope).into_iter().map(|(_, t)| t).collect::<Vec<_>>()
+                  , ["hello", "world", "老虎"]);
+    }
+
+    #[test]
+    fn whitespace_at_leaf_boundaries() {
+        let rope = Rope::from("one ") + Rope::from(" two\n") + Rope::from("three");
+        let text = rope.to_string();
+        assert_eq!(actual(&rope), expected(&text));
+    }
+}
+
+mod is_line_boundary {
+    use Rope;
+
+    #[test]
+    fn every_position() {
+        let text = "Löwe\n老虎\r\n\nLéopard\n";
+        let rope = Rope::from("Löwe\n老") + Rope::from("虎\r")
+                 + Rope::from("\n\nLéopard\n");
+        for i in 0..text.len() + 1 {
+            let expected = i == 0 || text.as_bytes()[i - 1] == b'\n';
+            assert_eq!(rope.is_line_boundary(i), expected, "at byte {}", i);
+        }
+    }
+
+    #[test]
+    fn without_trailing_newline() {
+        let rope = Rope::from("one\ntwo");
+        assert!(rope.is_line_boundary(4));
+        assert!(!rope.is_line_boundary(7));
+        assert!(Rope::new().is_line_boundary(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Rope::is_line_boundary: byte index 8 is out of \
+                               bounds (length 7)")]
+    fn out_of_bounds() {
+        Rope::from("one\ntwo").is_line_boundary(8);
+    }
+}
+
+mod char_iter_from_line {
+    use Rope;
+    use metric::Line;
+
+    #[test]
+    fn matches_iterating_from_line_start() {
+        let rope = Rope::from("Löwe\n老") + Rope::from("虎\r\n\n")
+                 + Rope::from("Léopard\nGe") + Rope::from("pard");
+        let text = rope.to_string();
+        let starts = Some(0).into_iter()
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect::<Vec<_>>();
+        for (n, &start) in starts.iter().enumerate() {
+            let chars = rope.char_iter_from_line(Line(n)).collect::<String>();
+            assert_eq!(chars, &text[start..]);
+            assert_eq!(chars, rope.slice_bytes(start..rope.len()).to_string());
+        }
+    }
+
+    #[test]
+    fn after_trailing_newline() {
+        let rope = Rope::from("one\ntwo\n");
+        assert_eq!(rope.char_iter_from_line(Line(2)).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn line_out_of_bounds() {
+        let _ = Rope::from("one\ntwo").char_iter_from_line(Line(2));
+    }
+}
+
+mod join_lines {
+    use Rope;
+
+    #[test]
+    fn into_a_single_line() {
+        let rope = Rope::from("Löwe\n老虎\r\n") + Rope::from("Léopard\nGepard");
+        assert_eq!(&rope.join_lines(" "), "Löwe 老虎 Léopard Gepard");
+        assert_eq!(&rope.join_lines(""), "Löwe老虎LéopardGepard");
+        assert_eq!(&rope.join_lines(" | "), "Löwe | 老虎 | Léopard | Gepard");
+    }
+
+    #[test]
+    fn splits_where_lines_does() {
+        let rope = Rope::from("one\rtwo\nthree");
+        assert_eq!(&rope.join_lines(" "), "one\rtwo three");
+        assert_eq!(rope.lines().count(), 2);
+        let rope = Rope::from("a\r") + Rope::from("b\r\nc\r");
+        assert_eq!(&rope.join_lines("|"), "a\rb|c\r");
+        assert_eq!( rope.join_lines("|").to_string().split('|').count()
+                  , rope.lines().count());
+    }
+
+    #[test]
+    fn crlf_across_leaves() {
+        let rope = Rope::from("one\r") + Rope::from("\ntwo\r")
+                 + Rope::from("\n");
+        assert_eq!(&rope.join_lines(" "), "one two");
+    }
+
+    #[test]
+    fn trailing_line_endings() {
+        assert_eq!(&Rope::from("one\n").join_lines(" "), "one");
+        assert_eq!(&Rope::from("one\n\n").join_lines("-"), "one-");
+        assert_eq!(&Rope::from("one\r\n").join_lines("-"), "one");
+        assert_eq!(&Rope::from("one\r").join_lines("-"), "one\r");
+        assert_eq!(&Rope::from("\n").join_lines("-"), "");
+        assert_eq!(&Rope::new().join_lines("-"), "");
+    }
+
+    #[test]
+    fn blank_lines() {
+        let rope = Rope::from("\none\n\n\ntwo");
+        assert_eq!(&rope.join_lines("-"), "-one---two");
+    }
+
+    #[test]
+    fn many_lines_stay_balanced() {
+        let rope = Rope::from("line\r\n".repeat(1000));
+        let joined = rope.join_lines(" ");
+        assert_eq!(joined.len(), 4999);
+        assert!(joined.shrink_leaf_fragmentation_report().depth <= 12);
+    }
+}
+
+mod binary_search_by {
+    use Rope;
+
+    #[test]
+    fn matches_slice_binary_search() {
+        let chars = ['\t', ' ', '0', 'A', 'a', 'é', 'ö', 'ω', '老', '虎', '🐯'];
+        let text = chars.iter().collect::<String>();
+        let rope = Rope::from(&text[..7]) + Rope::from(&text[7..]);
+        for &target in &chars {
+            assert_eq!( rope.binary_search_by(|c| c.cmp(&target))
+                      , chars.binary_search(&target));
+        }
+        for &target in &['\0', '1', 'b', 'ü', '豹', '\u{10ffff}'] {
+            assert_eq!( rope.binary_search_by(|c| c.cmp(&target))
+                      , chars.binary_search(&target));
+        }
+    }
+
+    #[test]
+    fn finds_where_predicate_flips() {
+        let rope = Rope::from("aaaabbbbbbcc");
+        // searching for a position between the runs finds its boundary
+        let flip = rope.binary_search_by(|c|
+            if c <= 'a' { ::std::cmp::Ordering::Less }
+            else { ::std::cmp::Ordering::Greater });
+        assert_eq!(flip, Err(4));
+        let found = rope.binary_search_by(|c| c.cmp(&'b')).unwrap();
+        assert!((4..10).contains(&found));
+    }
+
+    #[test]
+    fn empty_rope() {
+        assert_eq!(Rope::new().binary_search_by(|c| c.cmp(&'a')), Err(0));
+    }
+}
+
+#[cfg(feature = "crc")]
+mod bytes_checksum_crc32 {
+    use Rope;
+
+    #[test]
+    fn known_values() {
+        assert_eq!(Rope::new().bytes_checksum_crc32(), 0);
+        assert_eq!(Rope::from("123456789").bytes_checksum_crc32(), 0xCBF43926);
+    }
+
+    #[test]
+    fn stable_across_structure() {
+        let flat = Rope::from("Löwe 老虎 Léopard\nand a second line");
+        let built = Rope::from("Löwe ")
+            .append(&Rope::from("老虎 Léo"))
+            .append(&Rope::from("pard\nand a "))
+            .append(&Rope::from("second line"));
+        let inserted = Rope::from("Löwe  Léopard\nand a second line")
+            .insert_str(6, "老虎");
+        assert_eq!(flat, built);
+        assert_eq!(flat.bytes_checksum_crc32(), built.bytes_checksum_crc32());
+        assert_eq!( flat.bytes_checksum_crc32()
+                  , inserted.bytes_checksum_crc32());
+    }
+
+    #[test]
+    fn changes_with_content() {
+        let rope = Rope::from("hello ") + Rope::from("world");
+        let checksum = rope.bytes_checksum_crc32();
+        assert!(rope.insert_str(5, ",").bytes_checksum_crc32() != checksum);
+        assert!(rope.delete(0..1).bytes_checksum_crc32() != checksum);
+        assert!(Rope::from("hello World").bytes_checksum_crc32() != checksum);
+    }
+}
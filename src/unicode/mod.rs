@@ -3,6 +3,8 @@ use unicode_segmentation::UnicodeSegmentation;
 
 #[cfg(test)]
 mod test;
+#[cfg(feature = "case")]
+pub mod case_folding;
 
 pub trait Unicode<'a> {
     // type GraphemeIndices: Iterator<Item = (usize, &'a str)>;
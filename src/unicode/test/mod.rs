@@ -160,3 +160,53 @@ fn test_graphemes() {
 //                  "Reverse word indices");
 //     }
 // }
+
+#[test]
+fn size_hints_narrow_during_iteration() {
+    let r = Rope::from("Brr, it's\n") + Rope::from("29.3°F!");
+    let mut graphemes = r.grapheme_indices();
+    let mut last_upper = r.len();
+    assert_eq!(graphemes.size_hint(), (1, Some(last_upper)));
+    while let Some((_, g)) = graphemes.next() {
+        let (lower, upper) = graphemes.size_hint();
+        assert_eq!(upper, Some(last_upper - g.len()));
+        assert_eq!(lower, if upper == Some(0) { 0 } else { 1 });
+        last_upper = upper.unwrap();
+    }
+    assert_eq!(graphemes.size_hint(), (0, Some(0)));
+
+    let mut words = r.split_word_bound_indices();
+    let mut last_upper = r.len();
+    assert_eq!(words.size_hint(), (1, Some(last_upper)));
+    while let Some((_, w)) = words.next() {
+        let (lower, upper) = words.size_hint();
+        assert_eq!(upper, Some(last_upper - w.len()));
+        assert_eq!(lower, if upper == Some(0) { 0 } else { 1 });
+        last_upper = upper.unwrap();
+    }
+    assert_eq!(words.size_hint(), (0, Some(0)));
+}
+
+#[test]
+fn grapheme_indices_size_hint() {
+    let r = Rope::from("a\u{310}\u{e9}\u{f6}\u{332}\r\n");
+    let mut gr_inds_iter = r.grapheme_indices();
+    {
+        let gr_inds = gr_inds_iter.by_ref();
+        let e1 = gr_inds.size_hint();
+        assert_eq!(e1, (1, Some(11)));
+        let c = gr_inds.count();
+        assert_eq!(c, 4);
+    }
+    let e2 = gr_inds_iter.size_hint();
+    assert_eq!(e2, (0, Some(0)));
+}
+
+#[test]
+fn empty_rope_indices() {
+    let r = Rope::new();
+    assert_eq!(r.grapheme_indices().size_hint(), (0, Some(0)));
+    assert_eq!(r.grapheme_indices().next(), None);
+    assert_eq!(r.split_word_bound_indices().size_hint(), (0, Some(0)));
+    assert_eq!(r.split_word_bound_indices().next(), None);
+}